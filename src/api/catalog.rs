@@ -0,0 +1,42 @@
+use crate::chesses::items::catalog::item_catalog;
+use crate::chesses::items::models::Item;
+use crate::chesses::skills::catalog::skill_catalog;
+use crate::chesses::skills::models::Skill;
+use crate::chesses::units::catalog::unit_catalog;
+use crate::chesses::units::models::ChessTemplate;
+use crate::data::find_chess;
+use crate::game::augments::{augment_catalog, Augment};
+use axum::Json;
+use serde::Serialize;
+
+/// 圖鑑中的棋子：戰鬥模板加上商店價格
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogUnit {
+    pub cost: u32,
+    #[serde(flatten)]
+    pub template: ChessTemplate,
+}
+
+pub async fn list_units() -> Json<Vec<CatalogUnit>> {
+    let units = unit_catalog()
+        .into_iter()
+        .map(|template| CatalogUnit {
+            cost: find_chess(&template.chess).map_or(0, |chess| chess.cost),
+            template,
+        })
+        .collect();
+    Json(units)
+}
+
+pub async fn list_skills() -> Json<Vec<Skill>> {
+    Json(skill_catalog())
+}
+
+pub async fn list_items() -> Json<Vec<Item>> {
+    Json(item_catalog())
+}
+
+pub async fn list_augments() -> Json<Vec<Augment>> {
+    Json(augment_catalog())
+}
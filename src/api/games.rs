@@ -0,0 +1,33 @@
+use super::error::ApiError;
+use super::ApiState;
+use crate::game::GameInfo;
+use crate::types::response::ErrorCode;
+use axum::extract::{Path, State};
+use axum::http::header;
+use axum::response::IntoResponse;
+use axum::Json;
+
+pub async fn list_games(State(state): State<ApiState>) -> Json<Vec<GameInfo>> {
+    Json(state.games.list())
+}
+
+pub async fn get_game(State(state): State<ApiState>, Path(game_id): Path<String>) -> Result<Json<GameInfo>, ApiError> {
+    state.games.get(&game_id).map(Json).ok_or_else(|| game_not_found(&game_id))
+}
+
+/// 以附件形式下載重播紀錄；設定了觀戰延遲時，進行中的遊戲要等結束後才能下載
+pub async fn download_replay(
+    State(state): State<ApiState>,
+    Path(game_id): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    if state.hides_live(&game_id) {
+        return Err(ApiError::conflict(ErrorCode::GameInProgress, format!("replay of game {} is available after the game ends", game_id)));
+    }
+    let replay = state.games.replay(&game_id).ok_or_else(|| game_not_found(&game_id))?;
+    let disposition = format!("attachment; filename=\"{}-replay.json\"", replay.game_id);
+    Ok(([(header::CONTENT_DISPOSITION, disposition)], Json(replay)))
+}
+
+fn game_not_found(game_id: &str) -> ApiError {
+    ApiError::not_found(ErrorCode::GameNotFound, format!("game not found: {}", game_id))
+}
@@ -0,0 +1,163 @@
+use crate::connection::ConnectionRegistry;
+use crate::game::GameRegistry;
+use crate::player::PlayerManager;
+use axum::routing::get;
+use axum::{Json, Router};
+use serde_json::{json, Value};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+
+mod catalog;
+mod error;
+mod games;
+mod players;
+
+/// HTTP API 與 WebSocket handler 共用的狀態
+#[derive(Clone)]
+pub struct ApiState {
+    pub player_manager: Arc<PlayerManager>,
+    pub games: Arc<GameRegistry>,
+    pub registry: Arc<ConnectionRegistry>,
+    /// 觀戰延遲；大於 0 時不公開進行中遊戲的即時狀態與重播，避免繞過延遲看牌
+    pub spectator_delay: Duration,
+}
+
+impl ApiState {
+    /// 這場遊戲的即時狀態是否要對外隱藏
+    fn hides_live(&self, game_id: &str) -> bool {
+        !self.spectator_delay.is_zero() && self.games.is_in_progress(game_id)
+    }
+}
+
+/// 非即時操作的 REST API：查詢遊戲、玩家、棋子圖鑑與下載重播
+pub fn router(state: ApiState) -> Router {
+    Router::new()
+        .route("/health", get(health))
+        .route("/games", get(games::list_games))
+        .route("/games/:id", get(games::get_game))
+        .route("/games/:id/replay", get(games::download_replay))
+        .route("/players/:id", get(players::get_player))
+        .route("/catalog/units", get(catalog::list_units))
+        .route("/catalog/skills", get(catalog::list_skills))
+        .route("/catalog/items", get(catalog::list_items))
+        .route("/catalog/augments", get(catalog::list_augments))
+        .with_state(state)
+}
+
+/// 在獨立的埠口提供 HTTP API，`shutdown` 完成後停止接受請求
+pub async fn serve(
+    listener: TcpListener,
+    state: ApiState,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) -> std::io::Result<()> {
+    axum::serve(listener, router(state)).with_graceful_shutdown(shutdown).await
+}
+
+async fn health() -> Json<Value> {
+    Json(json!({ "status": "ok" }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::GameRules;
+    use axum::body::Body;
+    use axum::http::{header, Request, StatusCode};
+    use http_body_util::BodyExt;
+    use tower::ServiceExt;
+
+    fn state() -> ApiState {
+        ApiState {
+            player_manager: Arc::new(PlayerManager::new(GameRules::default())),
+            games: Arc::new(GameRegistry::new()),
+            registry: Arc::new(ConnectionRegistry::new()),
+            spectator_delay: Duration::ZERO,
+        }
+    }
+
+    async fn get_json(state: &ApiState, uri: &str) -> (StatusCode, Value) {
+        let response = router(state.clone())
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_games_and_replay() {
+        let state = state();
+        let game = state.games.create(42, vec!["p1".into()]);
+
+        let (status, games) = get_json(&state, "/games").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(games[0]["id"], game.id.as_str());
+
+        let (_, detail) = get_json(&state, &format!("/games/{}", game.id)).await;
+        assert_eq!(detail["players"][0], "p1");
+
+        let response = router(state.clone())
+            .oneshot(Request::get(format!("/games/{}/replay", game.id)).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert!(response.headers()[header::CONTENT_DISPOSITION].to_str().unwrap().starts_with("attachment"));
+
+        let (status, error) = get_json(&state, "/games/missing").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(error["code"], "GAME_NOT_FOUND");
+    }
+
+    #[tokio::test]
+    async fn test_live_state_hidden_with_spectator_delay() {
+        let state = ApiState { spectator_delay: Duration::from_secs(30), ..state() };
+        let game = state.games.create(42, vec!["p1".into()]);
+        state.games.start(&game.id);
+        state.player_manager.create_player("p1");
+        state.registry.join_game("p1", &game.id);
+
+        // 進行中：重播與玩家的即時棋盤都不公開
+        let (status, error) = get_json(&state, &format!("/games/{}/replay", game.id)).await;
+        assert_eq!((status, error["code"].as_str()), (StatusCode::CONFLICT, Some("GAME_IN_PROGRESS")));
+        let (_, player) = get_json(&state, "/players/p1").await;
+        assert_eq!(player["gameId"], game.id.as_str());
+        assert!(player.get("board").is_none() && player.get("money").is_none());
+
+        // 遊戲結束後恢復公開
+        state.games.finish(&game.id);
+        let (status, _) = get_json(&state, &format!("/games/{}/replay", game.id)).await;
+        assert_eq!(status, StatusCode::OK);
+        let (_, player) = get_json(&state, "/players/p1").await;
+        assert_eq!(player["money"], 100);
+    }
+
+    #[tokio::test]
+    async fn test_players_and_catalog() {
+        let state = state();
+        let (status, player) = get_json(&state, "/players/p1").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(player["money"], 100);
+        assert!(player.get("shop").is_none());
+
+        let (status, _) = get_json(&state, "/players/nobody").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (_, units) = get_json(&state, "/catalog/units").await;
+        let knight = units.as_array().unwrap().iter().find(|u| u["chess"] == "Knight").unwrap();
+        assert_eq!(knight["cost"], 2);
+        assert_eq!(knight["skills"][0]["id"], "shield_bash");
+
+        let (_, skills) = get_json(&state, "/catalog/skills").await;
+        assert!(skills.as_array().unwrap().iter().any(|s| s["id"] == "fireball"));
+
+        let (_, items) = get_json(&state, "/catalog/items").await;
+        let deathblade = items.as_array().unwrap().iter().find(|i| i["id"] == "deathblade").unwrap();
+        assert_eq!(deathblade["recipe"], serde_json::json!(["sword", "sword"]));
+
+        let (_, augments) = get_json(&state, "/catalog/augments").await;
+        let crest = augments.as_array().unwrap().iter().find(|a| a["id"] == "mage_crest").unwrap();
+        assert_eq!((crest["tier"].as_str(), crest["modifiers"]["synergies"].clone()), (Some("gold"), serde_json::json!(["mage"])));
+    }
+}
@@ -0,0 +1,54 @@
+use super::error::ApiError;
+use super::ApiState;
+use crate::control::GameStateControl;
+use crate::types::game_state::{Synergy, UnitOnBench, UnitOnBoard};
+use crate::types::response::ErrorCode;
+use axum::extract::{Path, State};
+use axum::Json;
+use serde::Serialize;
+
+/// 公開的玩家資訊；商店內容只有玩家本人能透過 WebSocket 看到
+/// 設定了觀戰延遲時，進行中遊戲的金錢、棋盤、備戰區、羈絆與符文不公開
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayerView {
+    pub player_id: String,
+    pub game_id: Option<String>,
+    /// 所在遊戲目前的回合，不在遊戲中為 0
+    pub round: u32,
+    pub afk: bool,
+    pub level: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub money: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub board: Option<Vec<UnitOnBoard>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bench: Option<Vec<UnitOnBench>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub synergies: Option<Vec<Synergy>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub augments: Option<Vec<String>>,
+}
+
+pub async fn get_player(State(state): State<ApiState>, Path(player_id): Path<String>) -> Result<Json<PlayerView>, ApiError> {
+    let player = state
+        .player_manager
+        .get_player(&player_id)
+        .ok_or_else(|| ApiError::not_found(ErrorCode::PlayerNotFound, format!("player not found: {}", player_id)))?;
+    let game_id = state.registry.game_of(&player_id);
+    let round = game_id.as_ref().and_then(|game_id| state.games.get(game_id)).map_or(0, |game| game.round);
+    let snapshot = GameStateControl::snapshot(&player, round);
+    let public = !game_id.as_ref().is_some_and(|game_id| state.hides_live(game_id));
+    Ok(Json(PlayerView {
+        game_id,
+        round: snapshot.round,
+        afk: player.afk,
+        player_id,
+        level: snapshot.level,
+        money: public.then_some(snapshot.money),
+        board: public.then_some(snapshot.board),
+        bench: public.then_some(snapshot.bench),
+        synergies: public.then_some(snapshot.synergies),
+        augments: public.then_some(snapshot.augments),
+    }))
+}
@@ -0,0 +1,306 @@
+// 機器人玩家：在伺服器內執行，像一般客戶端一樣握手、登入、排隊、確認配對與遊玩
+//
+// 每個機器人開一條沒有 socket 的連線（`ConnectionRegistry::open`），所有操作都經過 `Router`
+// 送出與客戶端相同的請求，推播也從同一個出站佇列收取，因此會一併走過中介層與各個處理器。
+// 決策依難度對應的 `Profile`（見 `strategy`）；一場遊戲結束後重新排隊。
+
+pub mod strategy;
+
+use crate::connection::{ConnectionContext, ConnectionRegistry, Outbound};
+use crate::data::find_chess;
+use crate::game::carousel::CarouselSlot;
+use crate::game::GameRules;
+use crate::router::Router;
+use crate::types::protocol::PROTOCOL_VERSION;
+use crate::types::game_state::GameState;
+use crate::types::response::{WsRequest, WsResponse};
+use log::*;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use strategy::next_action;
+use tokio::sync::mpsc;
+
+/// 每個動作之間的預設間隔：避免觸發限流，也讓對手看得到操作過程
+pub const DEFAULT_THINK_TIME: Duration = Duration::from_millis(250);
+
+/// 機器人難度
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "camelCase")]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+/// 各難度的策略參數
+#[derive(Debug, Clone)]
+pub struct Profile {
+    /// 平常保留不花的金幣（吃利息）
+    pub savings: i32,
+    /// 生命低於此值時不再存錢
+    pub roll_below_hp: i32,
+    /// 依羈絆挑選棋子與符文
+    pub follows_synergies: bool,
+    /// 近戰站前排、遠程站後排
+    pub positions_by_range: bool,
+    pub equips_items: bool,
+    /// 存款以外的錢也拿來重新整理商店
+    pub rolls: bool,
+    /// 最多升到幾級
+    pub max_level: u32,
+    /// 每次準備階段最多操作幾次
+    pub actions_per_round: usize,
+}
+
+impl Difficulty {
+    pub fn profile(&self) -> Profile {
+        match self {
+            Difficulty::Easy => Profile {
+                savings: 0,
+                roll_below_hp: 0,
+                follows_synergies: false,
+                positions_by_range: false,
+                equips_items: false,
+                rolls: false,
+                max_level: 5,
+                actions_per_round: 4,
+            },
+            Difficulty::Normal => Profile {
+                savings: 20,
+                roll_below_hp: 30,
+                follows_synergies: true,
+                positions_by_range: true,
+                equips_items: true,
+                rolls: false,
+                max_level: 7,
+                actions_per_round: 15,
+            },
+            Difficulty::Hard => Profile {
+                savings: 50,
+                roll_below_hp: 50,
+                follows_synergies: true,
+                positions_by_range: true,
+                equips_items: true,
+                rolls: true,
+                max_level: 9,
+                actions_per_round: 30,
+            },
+        }
+    }
+}
+
+/// 建立機器人並讓它們透過同一個 `Router` 操作
+pub struct BotManager {
+    router: Arc<Router>,
+    registry: Arc<ConnectionRegistry>,
+    think_time: Duration,
+}
+
+impl BotManager {
+    pub fn new(router: Arc<Router>, registry: Arc<ConnectionRegistry>) -> Self {
+        Self { router, registry, think_time: DEFAULT_THINK_TIME }
+    }
+
+    pub fn with_think_time(mut self, think_time: Duration) -> Self {
+        self.think_time = think_time;
+        self
+    }
+
+    /// 啟動一個機器人：登入後開始排隊，回傳它的 playerId
+    pub async fn spawn(&self, difficulty: Difficulty) -> Option<String> {
+        let (bot, pushes) = self.connect(difficulty).await?;
+        let player_id = bot.ctx.player_id()?;
+        bot.send("QueueForMatch", json!({})).await;
+        info!("Bot {} ({:?}) is queueing for a match", player_id, difficulty);
+        tokio::spawn(bot.run(pushes));
+        Some(player_id)
+    }
+
+    /// 開一條連線、完成握手並登入
+    async fn connect(&self, difficulty: Difficulty) -> Option<(Bot, mpsc::UnboundedReceiver<WsResponse>)> {
+        let (handle, mut outbound) = self.registry.open();
+        let bot = Bot {
+            ctx: ConnectionContext::new(handle, SocketAddr::from(([127, 0, 0, 1], 0))),
+            router: self.router.clone(),
+            profile: difficulty.profile(),
+            think_time: self.think_time,
+            rules: GameRules::default(),
+            carousel: Vec::new(),
+        };
+
+        // 出站佇列有上限，操作期間另外轉到沒有上限的佇列，推播才不會被丟掉
+        let (forward, pushes) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            while let Some(outbound) = outbound.recv().await {
+                match outbound {
+                    Outbound::Response(response) => {
+                        if forward.send(response).is_err() {
+                            break;
+                        }
+                    }
+                    Outbound::Close(_) => break,
+                    Outbound::Ping | Outbound::SwitchEncoding(_) => (),
+                }
+            }
+        });
+
+        let hello = bot.send("Hello", json!({ "protocolVersion": PROTOCOL_VERSION })).await;
+        if !succeeded(&hello) {
+            return None;
+        }
+        let login = bot.send("Login", json!({})).await;
+        succeeded(&login).then_some((bot, pushes))
+    }
+}
+
+fn succeeded(response: &WsResponse) -> bool {
+    response.payload.as_ref().is_some_and(|payload| payload["success"] == true)
+}
+
+/// 一個機器人的連線與目前遊戲的資訊
+struct Bot {
+    ctx: ConnectionContext,
+    router: Arc<Router>,
+    profile: Profile,
+    think_time: Duration,
+    /// 目前遊戲的規則（`GameStarted` 帶來）
+    rules: GameRules,
+    /// 進行中的選秀
+    carousel: Vec<CarouselSlot>,
+}
+
+impl Bot {
+    /// 以客戶端相同的格式送出請求，經過所有中介層
+    async fn send(&self, action: &str, payload: Value) -> WsResponse {
+        let request = WsRequest { type_: action.to_string(), payload, request_id: None };
+        let response = self.router.handle(&self.ctx, &request).await;
+        if !succeeded(&response) {
+            debug!("Bot {:?} {} failed: {:?}", self.ctx.player_id(), action, response.payload);
+        }
+        response
+    }
+
+    async fn state(&self) -> Option<GameState> {
+        let response = self.send("GetGameState", json!({})).await;
+        serde_json::from_value(response.payload?.get("state")?.clone()).ok()
+    }
+
+    fn is_me(&self, player_id: &Value) -> bool {
+        self.ctx.player_id().is_some_and(|me| player_id == me.as_str())
+    }
+
+    /// 依推播行動，直到連線關閉
+    async fn run(mut self, mut pushes: mpsc::UnboundedReceiver<WsResponse>) {
+        while let Some(push) = pushes.recv().await {
+            let payload = push.payload.unwrap_or_default();
+            match push.type_.as_str() {
+                "MatchFound" => {
+                    self.send("AcceptMatch", json!({ "gameId": payload["gameId"] })).await;
+                }
+                "MatchCancelled" if payload["requeued"] == false => {
+                    self.send("QueueForMatch", json!({})).await;
+                }
+                "GameStarted" => {
+                    if let Ok(rules) = serde_json::from_value(payload["rules"].clone()) {
+                        self.rules = rules;
+                    }
+                }
+                "CarouselStarted" => {
+                    self.carousel = serde_json::from_value(payload["slots"].clone()).unwrap_or_default();
+                }
+                "CarouselPick" => {
+                    if let Some(slot) = payload["slotIndex"].as_u64().and_then(|i| self.carousel.get_mut(i as usize)) {
+                        slot.picked_by = payload["playerId"].as_str().map(str::to_string);
+                    }
+                }
+                "CarouselWave" if payload["players"].as_array().is_some_and(|players| players.iter().any(|p| self.is_me(p))) => {
+                    self.pick_carousel().await;
+                }
+                "RoundStarted" | "AugmentOffered" => self.play_turn().await,
+                "GameOver" => {
+                    self.rules = GameRules::default();
+                    self.send("QueueForMatch", json!({})).await;
+                }
+                _ => (),
+            }
+        }
+        self.router.disconnected(self.ctx.id());
+    }
+
+    /// 準備階段：反覆取得狀態、決定並送出一個動作，直到沒有要做的事或達到操作上限
+    async fn play_turn(&self) {
+        for _ in 0..self.profile.actions_per_round {
+            let Some(state) = self.state().await else {
+                return;
+            };
+            let Some(action) = next_action(&state, &self.rules, &self.profile) else {
+                return;
+            };
+            let (kind, payload) = action.request();
+            if !succeeded(&self.send(kind, payload).await) {
+                return;
+            }
+            tokio::time::sleep(self.think_time).await;
+        }
+    }
+
+    /// 選秀：挑已經有的棋子（湊合成），否則挑最貴的
+    async fn pick_carousel(&self) {
+        let owned: Vec<String> = self
+            .state()
+            .await
+            .map(|state| state.board.into_iter().map(|u| u.chess).chain(state.bench.into_iter().map(|u| u.chess)).collect())
+            .unwrap_or_default();
+        let choice = self
+            .carousel
+            .iter()
+            .enumerate()
+            .filter(|(_, slot)| slot.picked_by.is_none())
+            .max_by_key(|(index, slot)| (owned.contains(&slot.chess), find_chess(&slot.chess).map_or(0, |c| c.cost), std::cmp::Reverse(*index)));
+        if let Some((slot_index, _)) = choice {
+            self.send("PickCarousel", json!({ "slotIndex": slot_index })).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connection::{PresenceMonitor, SessionSigner, DEFAULT_RECONNECT_GRACE};
+    use crate::control::StateSync;
+    use crate::handlers::{BuyUnitHandler, BuyXPHandler, GameStateMessageHandler, HelloHandler, LoginHandler, MoveUnitHandler, ShopHandler, Typed};
+    use crate::middleware::HandshakeLayer;
+    use crate::player::PlayerManager;
+
+    #[tokio::test]
+    async fn test_bot_plays_a_turn_through_the_handlers() {
+        let player_manager = Arc::new(PlayerManager::new(GameRules::default()));
+        let registry = Arc::new(ConnectionRegistry::new());
+        let sync = Arc::new(StateSync::new(player_manager.clone(), registry.clone()));
+        let presence = Arc::new(PresenceMonitor::new(registry.clone(), player_manager.clone(), DEFAULT_RECONNECT_GRACE));
+        let signer = Arc::new(SessionSigner::random());
+        let mut router = Router::new();
+        router.add_handler(Arc::new(Typed(HelloHandler))).unwrap();
+        router.add_handler(Arc::new(Typed(LoginHandler::new(player_manager.clone(), presence, signer, sync.clone())))).unwrap();
+        router.add_handler(Arc::new(Typed(GameStateMessageHandler::new(sync.clone(), registry.clone())))).unwrap();
+        router.add_handler(Arc::new(Typed(BuyUnitHandler::new(player_manager.clone(), sync.clone())))).unwrap();
+        router.add_handler(Arc::new(Typed(MoveUnitHandler::new(player_manager.clone(), sync.clone())))).unwrap();
+        router.add_handler(Arc::new(Typed(BuyXPHandler::new(player_manager.clone(), sync.clone())))).unwrap();
+        router.add_handler(Arc::new(Typed(ShopHandler::new(player_manager.clone(), sync)))).unwrap();
+        router.layer(Arc::new(HandshakeLayer));
+        let bots = BotManager::new(Arc::new(router), registry).with_think_time(Duration::ZERO);
+
+        let (bot, _pushes) = bots.connect(Difficulty::Normal).await.unwrap();
+        bot.play_turn().await;
+
+        // 買了棋子放上棋盤，但一般難度保留 20 金吃利息
+        let player = player_manager.get_player(&bot.ctx.player_id().unwrap()).unwrap();
+        assert!(!player.board.is_empty() && player.board.len() as u32 <= player.level);
+        assert!(player.money >= bot.profile.savings);
+        assert!(player.money < GameRules::default().starting_gold);
+    }
+}
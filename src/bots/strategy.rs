@@ -0,0 +1,270 @@
+// 機器人的決策：依 `GetGameState` 回傳的狀態決定下一個動作
+//
+// 每次只決定一個動作，送出後重新取得狀態再決定下一個，直到沒有想做的事為止。
+// 優先順序：選符文 → 補滿棋盤 → 以備戰區較強的單位替換 → 裝備道具 → 備戰區滿了先賣 → 買棋子 → 升級 → 重新整理商店。
+
+use super::Profile;
+use crate::chesses::synergies::count_tags;
+use crate::chesses::units::catalog::find_template;
+use crate::data::find_chess;
+use crate::game::augments::{find_augment, modifiers_of};
+use crate::game::pool::copies_of;
+use crate::game::GameRules;
+use crate::player::{PlayerData, PlayerError, PlayerManager, BENCH_SIZE, BOARD_HEIGHT, BOARD_WIDTH, MAX_ITEMS_PER_UNIT};
+use crate::types::game_state::GameState;
+use serde_json::{json, Value};
+
+/// 機器人可以做的動作，與客戶端送出的請求一一對應
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    PickAugment { augment_id: String },
+    BuyUnit { shop_index: usize },
+    SellUnit { unit_id: String },
+    MoveUnit { unit_id: String, position: [u32; 2] },
+    EquipItem { item_id: String, unit_id: String },
+    BuyXp,
+    RefreshShop,
+}
+
+impl Action {
+    /// 請求的 action 與 payload
+    pub fn request(&self) -> (&'static str, Value) {
+        match self {
+            Action::PickAugment { augment_id } => ("PickAugment", json!({ "augmentId": augment_id })),
+            Action::BuyUnit { shop_index } => ("BuyUnit", json!({ "shopIndex": shop_index })),
+            Action::SellUnit { unit_id } => ("SellUnit", json!({ "unitId": unit_id })),
+            Action::MoveUnit { unit_id, position } => ("MoveUnit", json!({ "unitId": unit_id, "position": position })),
+            Action::EquipItem { item_id, unit_id } => ("EquipItem", json!({ "itemId": item_id, "unitId": unit_id })),
+            Action::BuyXp => ("BuyXP", json!({})),
+            Action::RefreshShop => ("RefreshShop", json!({})),
+        }
+    }
+
+    /// 不經過 Router 直接套用到玩家狀態，替離線（AFK）的玩家代為操作時使用
+    pub fn apply(&self, player_manager: &PlayerManager, player_id: &str) -> Result<PlayerData, PlayerError> {
+        match self {
+            Action::PickAugment { augment_id } => player_manager.pick_augment(player_id, augment_id),
+            Action::BuyUnit { shop_index } => player_manager.buy_unit(player_id, *shop_index),
+            Action::SellUnit { unit_id } => player_manager.sell_unit(player_id, unit_id),
+            Action::MoveUnit { unit_id, position } => player_manager.move_unit(player_id, unit_id, Some(*position)),
+            Action::EquipItem { item_id, unit_id } => player_manager.equip_item(player_id, item_id, unit_id),
+            Action::BuyXp => player_manager.buy_xp(player_id),
+            Action::RefreshShop => player_manager.refresh_shop(player_id),
+        }
+    }
+}
+
+/// 準備階段的下一個動作；`None` 代表這回合已經沒有要做的事
+pub fn next_action(state: &GameState, rules: &GameRules, profile: &Profile) -> Option<Action> {
+    if let Some(offer) = &state.augment_offer {
+        return pick_augment(state, &offer.options, profile).map(|augment_id| Action::PickAugment { augment_id });
+    }
+    place(state, profile)
+        .or_else(|| upgrade_board(state))
+        .or_else(|| equip(state, profile))
+        .or_else(|| make_room(state))
+        .or_else(|| buy(state, profile))
+        .or_else(|| level_up(state, rules, profile))
+        .or_else(|| roll(state, rules, profile))
+}
+
+/// 單位強度：一星的費用乘上合成所需的隻數
+fn power(chess: &str, level: u32) -> u32 {
+    find_chess(chess).map_or(1, |piece| piece.cost) * copies_of(level)
+}
+
+/// 不花的金幣：平常存錢吃利息，生命太低時全部拿來搜牌
+fn reserve(state: &GameState, profile: &Profile) -> i32 {
+    if state.hp < profile.roll_below_hp {
+        0
+    } else {
+        profile.savings
+    }
+}
+
+/// 依羈絆挑選時選能替棋盤上最多單位加標籤的符文，否則選第一個
+fn pick_augment(state: &GameState, options: &[String], profile: &Profile) -> Option<String> {
+    if !profile.follows_synergies {
+        return options.first().cloned();
+    }
+    let counts = count_tags(&state.board, &[]);
+    let score = |id: &String| {
+        find_augment(id).map_or(0, |augment| augment.modifiers.synergies.iter().map(|tag| counts.get(tag).copied().unwrap_or(0)).sum())
+    };
+    // 同分時保留前面的選項
+    options.iter().rev().max_by_key(|id| score(id)).cloned()
+}
+
+/// 棋盤還有空位時放上備戰區最強的單位
+fn place(state: &GameState, profile: &Profile) -> Option<Action> {
+    if state.board.len() >= state.level as usize {
+        return None;
+    }
+    let unit = state.bench.iter().max_by_key(|u| power(&u.chess, u.level))?;
+    let position = free_cell(state, &unit.chess, profile)?;
+    Some(Action::MoveUnit { unit_id: unit.id.clone(), position })
+}
+
+/// 備戰區有比棋盤上最弱的單位更強的單位時互換位置
+fn upgrade_board(state: &GameState) -> Option<Action> {
+    let weakest = state.board.iter().min_by_key(|u| power(&u.chess, u.level))?;
+    let strongest = state.bench.iter().max_by_key(|u| power(&u.chess, u.level))?;
+    (power(&strongest.chess, strongest.level) > power(&weakest.chess, weakest.level))
+        .then(|| Action::MoveUnit { unit_id: strongest.id.clone(), position: weakest.position })
+}
+
+/// 道具裝到棋盤上最強、還有空欄位的單位
+fn equip(state: &GameState, profile: &Profile) -> Option<Action> {
+    if !profile.equips_items {
+        return None;
+    }
+    let item = state.items.first()?;
+    let unit = state
+        .board
+        .iter()
+        .filter(|u| u.items.len() < MAX_ITEMS_PER_UNIT)
+        .max_by_key(|u| power(&u.chess, u.level))?;
+    Some(Action::EquipItem { item_id: item.id.clone(), unit_id: unit.id.clone() })
+}
+
+/// 備戰區滿了就賣掉最弱的單位，盡量留下還能合成的
+fn make_room(state: &GameState) -> Option<Action> {
+    if state.bench.len() < BENCH_SIZE {
+        return None;
+    }
+    let copies = |chess: &str, level: u32| {
+        let board = state.board.iter().filter(|u| u.chess == chess && u.level == level).count();
+        board + state.bench.iter().filter(|u| u.chess == chess && u.level == level).count()
+    };
+    let unit = state.bench.iter().min_by_key(|u| (copies(&u.chess, u.level) > 1, power(&u.chess, u.level)))?;
+    Some(Action::SellUnit { unit_id: unit.id.clone() })
+}
+
+/// 買下想要的棋子：已經有的（湊三隻合成）優先，其次是和棋盤同羈絆的，人數不夠時什麼都買
+fn buy(state: &GameState, profile: &Profile) -> Option<Action> {
+    if state.bench.len() >= BENCH_SIZE {
+        return None;
+    }
+    let budget = state.money as i32 - reserve(state, profile);
+    let tags = count_tags(&state.board, &[]);
+    let units = state.board.len() + state.bench.len();
+    let owned = |chess: &str| {
+        state.board.iter().filter(|u| u.chess == chess && u.level == 1).count()
+            + state.bench.iter().filter(|u| u.chess == chess && u.level == 1).count()
+    };
+
+    state
+        .shop
+        .iter()
+        .enumerate()
+        .filter_map(|(index, slot)| slot.as_ref().map(|unit| (index, unit)))
+        .filter(|(_, unit)| power(&unit.chess, unit.level) as i32 <= budget)
+        .filter_map(|(index, unit)| {
+            let synergy = profile.follows_synergies
+                && find_template(&unit.chess).is_some_and(|t| t.synergies.iter().any(|tag| tags.contains_key(tag)));
+            let score = match owned(&unit.chess) {
+                n if n > 0 => 2 + n,
+                _ if synergy => 2,
+                _ if units < state.level as usize => 1,
+                _ => return None,
+            };
+            Some((score, power(&unit.chess, unit.level), index))
+        })
+        .max_by_key(|&(score, power, index)| (score, power, std::cmp::Reverse(index)))
+        .map(|(_, _, shop_index)| Action::BuyUnit { shop_index })
+}
+
+/// 棋盤滿了而且有閒錢時買經驗升級
+fn level_up(state: &GameState, rules: &GameRules, profile: &Profile) -> Option<Action> {
+    let xp_cost = (rules.xp_cost + modifiers_of(&state.augments).xp_cost).max(0);
+    let full = state.board.len() >= state.level as usize;
+    (full && state.level < profile.max_level && state.money as i32 - xp_cost >= reserve(state, profile)).then_some(Action::BuyXp)
+}
+
+/// 免費次數先用掉；會搜牌的難度在存款以外的錢也拿來重新整理
+fn roll(state: &GameState, rules: &GameRules, profile: &Profile) -> Option<Action> {
+    if state.free_refreshes > 0 {
+        return Some(Action::RefreshShop);
+    }
+    (profile.rolls && state.money as i32 - rules.refresh_cost >= reserve(state, profile)).then_some(Action::RefreshShop)
+}
+
+/// 空的格子：分遠近時近戰由前排往後找、遠程由後排往前找，每排由中間往兩邊
+fn free_cell(state: &GameState, chess: &str, profile: &Profile) -> Option<[u32; 2]> {
+    let melee = find_template(chess).is_none_or(|t| t.base_attrs.attack_range <= 1);
+    let rows: Vec<u32> = if melee || !profile.positions_by_range {
+        (0..BOARD_HEIGHT).rev().collect()
+    } else {
+        (0..BOARD_HEIGHT).collect()
+    };
+    let mut columns: Vec<u32> = (0..BOARD_WIDTH).collect();
+    columns.sort_by_key(|&x| (x.abs_diff(BOARD_WIDTH / 2), x));
+    rows.into_iter()
+        .flat_map(|y| columns.iter().map(move |&x| [x, y]))
+        .find(|cell| !state.board.iter().any(|u| u.position == *cell))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bots::Difficulty;
+    use crate::types::game_state::{ShopUnit, UnitOnBench, UnitOnBoard, XpInfo};
+
+    fn state(money: u32, level: u32, board: &[(&str, [u32; 2])], bench: &[&str], shop: &[&str]) -> GameState {
+        GameState {
+            round: 1,
+            money,
+            hp: 100,
+            player_id: "bot".into(),
+            board: board
+                .iter()
+                .enumerate()
+                .map(|(i, (chess, position))| UnitOnBoard { id: format!("b{}", i), chess: chess.to_string(), level: 1, position: *position, items: Vec::new() })
+                .collect(),
+            bench: bench.iter().enumerate().map(|(i, chess)| UnitOnBench { id: format!("s{}", i), chess: chess.to_string(), level: 1, items: Vec::new() }).collect(),
+            shop: shop.iter().map(|chess| Some(ShopUnit { chess: chess.to_string(), level: 1 })).collect(),
+            items: Vec::new(),
+            synergies: Vec::new(),
+            augments: Vec::new(),
+            augment_offer: None,
+            free_refreshes: 0,
+            level,
+            xp: XpInfo { current: 0, required: 2 },
+        }
+    }
+
+    #[test]
+    fn test_places_by_range_then_buys_toward_copies_and_synergies() {
+        let rules = GameRules::default();
+        let hard = Difficulty::Hard.profile();
+
+        // 遠程放後排、近戰放前排，都從中間開始
+        let bench_sniper = state(0, 2, &[], &["Sniper"], &[]);
+        assert_eq!(next_action(&bench_sniper, &rules, &hard), Some(Action::MoveUnit { unit_id: "s0".into(), position: [3, 0] }));
+        let bench_knight = state(0, 2, &[("Sniper", [3, 0])], &["Knight"], &[]);
+        assert_eq!(next_action(&bench_knight, &rules, &hard), Some(Action::MoveUnit { unit_id: "s0".into(), position: [3, 3] }));
+
+        // 已經有的棋子優先，其次是同羈絆的；存款不足時不買
+        let shopping = state(60, 1, &[("Knight", [3, 3])], &[], &["Assassin", "Paladin", "Knight"]);
+        assert_eq!(next_action(&shopping, &rules, &hard), Some(Action::BuyUnit { shop_index: 2 }));
+        let shopping = state(60, 1, &[("Knight", [3, 3])], &[], &["Assassin", "Paladin"]);
+        assert_eq!(next_action(&shopping, &rules, &hard), Some(Action::BuyUnit { shop_index: 1 }));
+        assert_eq!(next_action(&state(50, 1, &[("Knight", [3, 3])], &[], &["Paladin"]), &rules, &hard), None);
+
+        // 簡單難度不看羈絆、不存錢：棋盤滿了就升級
+        let easy = Difficulty::Easy.profile();
+        let shopping = state(10, 1, &[("Knight", [3, 3])], &[], &["Paladin"]);
+        assert_eq!(next_action(&shopping, &rules, &easy), Some(Action::BuyXp));
+    }
+
+    #[test]
+    fn test_swaps_in_stronger_units_and_sells_when_full() {
+        let rules = GameRules::default();
+        let normal = Difficulty::Normal.profile();
+        let stronger = state(0, 1, &[("Knight", [3, 3])], &["Assassin"], &[]);
+        assert_eq!(next_action(&stronger, &rules, &normal), Some(Action::MoveUnit { unit_id: "s0".into(), position: [3, 3] }));
+
+        let full = state(0, 1, &[("Assassin", [3, 3])], &["Knight", "Knight", "Mage", "Tank", "Priest", "Hunter", "Archer", "Berserker", "Paladin"], &[]);
+        assert_eq!(next_action(&full, &rules, &normal), Some(Action::SellUnit { unit_id: "s2".into() }));
+    }
+}
@@ -0,0 +1,104 @@
+// 自動戰鬥：兩個棋盤上的單位依模板屬性與技能互相攻擊，直到一方全滅或時間到
+//
+// 戰鬥以固定的 tick 推進，同樣的棋盤與種子一定得到同樣的結果，重播時可以重新模擬。
+
+mod simulator;
+
+pub use simulator::simulate;
+
+use crate::chesses::items::catalog::find_item;
+use crate::chesses::items::models::Item;
+use crate::chesses::synergies::{count_tags, unit_bonus};
+use crate::chesses::units::catalog::find_template;
+use crate::chesses::units::models::{Attrs, ChessTemplate, SynergyTag};
+use crate::types::game_state::UnitOnBoard;
+use serde::Serialize;
+
+/// 戰鬥雙方；`Home` 在棋盤下半部，`Away` 的位置會鏡射到上半部
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Side {
+    Home,
+    Away,
+}
+
+/// 參戰單位：模板、星級、裝備的道具、羈絆與符文加成，以及在己方半場的位置
+#[derive(Debug, Clone)]
+pub struct Combatant {
+    pub id: String,
+    pub template: ChessTemplate,
+    pub star: u32,
+    pub items: Vec<Item>,
+    pub bonus: Attrs,
+    pub position: [u32; 2],
+}
+
+impl Combatant {
+    /// 玩家棋盤上的單位，找不到模板時略過
+    pub fn from_board(unit: &UnitOnBoard) -> Option<Self> {
+        find_template(&unit.chess).map(|template| Self {
+            id: unit.id.clone(),
+            template,
+            star: unit.level.clamp(1, 3),
+            items: unit.items.iter().filter_map(|id| find_item(id)).collect(),
+            bonus: Attrs::ZERO,
+            position: unit.position,
+        })
+    }
+
+    pub fn from_boards(board: &[UnitOnBoard]) -> Vec<Self> {
+        board.iter().filter_map(Self::from_board).collect()
+    }
+
+    /// 玩家的整個棋盤：依羈絆等級（含額外計入的標籤）與 `stats` 加成替每個單位加上屬性
+    pub fn team(board: &[UnitOnBoard], extra_tags: &[SynergyTag], stats: Attrs) -> Vec<Self> {
+        let counts = count_tags(board, extra_tags);
+        Self::from_boards(board)
+            .into_iter()
+            .map(|mut combatant| {
+                combatant.bonus = stats + unit_bonus(&combatant.template.synergies, &counts);
+                combatant
+            })
+            .collect()
+    }
+}
+
+/// 戰鬥中的事件，`at` 為開戰後的毫秒數；單位以 `home:u001` / `away:u001` 表示
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum BattleEvent {
+    Move { at: u32, unit: String, to: [i32; 2] },
+    Attack { at: u32, unit: String, target: String, damage: i32 },
+    Cast { at: u32, unit: String, skill: String },
+    Damage { at: u32, unit: String, target: String, amount: i32 },
+    Heal { at: u32, unit: String, target: String, amount: i32 },
+    Death { at: u32, unit: String },
+}
+
+/// 戰鬥結束時仍存活的單位
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Survivor {
+    pub id: String,
+    pub side: Side,
+    pub chess: String,
+    pub level: u32,
+    pub hp: i32,
+}
+
+/// 戰鬥結果；時間到還沒分出勝負時 `winner` 為 `None`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BattleOutcome {
+    pub winner: Option<Side>,
+    pub survivors: Vec<Survivor>,
+    pub duration_ms: u32,
+    pub events: Vec<BattleEvent>,
+}
+
+impl BattleOutcome {
+    /// 某一方存活的單位
+    pub fn survivors_of(&self, side: Side) -> impl Iterator<Item = &Survivor> {
+        self.survivors.iter().filter(move |s| s.side == side)
+    }
+}
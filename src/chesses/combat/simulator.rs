@@ -0,0 +1,596 @@
+use super::{BattleEvent, BattleOutcome, Combatant, Side, Survivor};
+use crate::chesses::skills::models::{
+    AoeShape, AttrType, Skill, SkillEffect, SkillTarget, SkillType, StatusEffect, StatusEffectType, TriggerCondition,
+};
+use crate::chesses::units::models::{StarLevel, Unit, UnitState};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+/// 雙方合計的棋盤大小（每方 7 x 4）
+const BOARD_WIDTH: i32 = 7;
+const BOARD_HEIGHT: i32 = 8;
+/// 每個 tick 的長度與戰鬥時間上限（30 秒）
+const TICK_MS: u32 = 100;
+const MAX_TICKS: u32 = 300;
+/// 移動一格所需的 tick 數
+const MOVE_TICKS: u32 = 5;
+/// 每次普攻回復的法力
+const MANA_PER_ATTACK: i32 = 10;
+/// 一、二、三星的生命與攻擊倍率
+const STAR_MULTIPLIER: [f32; 3] = [1.0, 1.8, 3.24];
+
+struct Fighter {
+    key: String,
+    side: Side,
+    star: u32,
+    /// 模板屬性已依星級放大；`state.attack_speed` 以百分之一為單位
+    unit: Unit,
+    position: (i32, i32),
+    next_attack: u32,
+    next_move: u32,
+    /// 已觸發過的 `OnHpBelow` 被動（每場戰鬥只觸發一次）
+    fired: Vec<String>,
+}
+
+impl Fighter {
+    fn new(combatant: Combatant, side: Side) -> Self {
+        let multiplier = STAR_MULTIPLIER[(combatant.star.clamp(1, 3) - 1) as usize];
+        let mut template = combatant.template;
+        template.level = match combatant.star {
+            1 => StarLevel::One,
+            2 => StarLevel::Two,
+            _ => StarLevel::Three,
+        };
+        let attrs = &mut template.base_attrs;
+        attrs.max_hp = (attrs.max_hp as f32 * multiplier).round() as i32;
+        attrs.attack_damage = (attrs.attack_damage as f32 * multiplier).round() as i32;
+
+        // 道具、羈絆與符文加成不隨星級放大；有法力的單位至少需要一次普攻才能施放技能
+        let has_mana = attrs.max_mp > 0;
+        *attrs = *attrs + combatant.bonus;
+        for item in &combatant.items {
+            *attrs = *attrs + item.bonus;
+            template.skills.extend(item.passive.clone());
+        }
+        let attrs = &mut template.base_attrs;
+        if has_mana {
+            attrs.max_mp = attrs.max_mp.max(MANA_PER_ATTACK);
+        }
+
+        let state = UnitState {
+            hp: attrs.max_hp,
+            mp: 0,
+            attack_damage: attrs.attack_damage,
+            ability_power: attrs.ability_power,
+            armor: attrs.armor,
+            magic_resist: attrs.magic_resist,
+            attack_speed: (attrs.attack_speed * 100.0).round() as i32,
+        };
+        let [x, y] = combatant.position.map(|v| v as i32);
+        let position = match side {
+            Side::Home => (x, y),
+            Side::Away => (BOARD_WIDTH - 1 - x, BOARD_HEIGHT - 1 - y),
+        };
+        let prefix = match side {
+            Side::Home => "home",
+            Side::Away => "away",
+        };
+        Self {
+            key: format!("{}:{}", prefix, combatant.id),
+            side,
+            star: combatant.star,
+            unit: Unit { template, state, status_effects: Vec::new() },
+            position,
+            next_attack: 0,
+            next_move: 0,
+            fired: Vec::new(),
+        }
+    }
+
+    fn alive(&self) -> bool {
+        self.unit.state.hp > 0
+    }
+
+    /// 狀態效果加減後的數值
+    fn modified(&self, base: i32, up: StatusEffectType, down: StatusEffectType) -> i32 {
+        self.unit.status_effects.iter().fold(base, |value, effect| {
+            let amount = effect.amount.unwrap_or(0);
+            if effect.kind == up {
+                value + amount
+            } else if effect.kind == down {
+                value - amount
+            } else {
+                value
+            }
+        })
+    }
+
+    fn attack_damage(&self) -> i32 {
+        self.modified(self.unit.state.attack_damage, StatusEffectType::AttackDamageUp, StatusEffectType::AttackDamageDown).max(0)
+    }
+
+    fn ability_power(&self) -> i32 {
+        self.modified(self.unit.state.ability_power, StatusEffectType::AbilityPowerUp, StatusEffectType::AbilityPowerDown).max(0)
+    }
+
+    fn armor(&self) -> i32 {
+        self.modified(self.unit.state.armor, StatusEffectType::ArmorUp, StatusEffectType::ArmorDown)
+    }
+
+    fn magic_resist(&self) -> i32 {
+        self.modified(self.unit.state.magic_resist, StatusEffectType::MagicResistUp, StatusEffectType::MagicResistDown)
+    }
+
+    /// 兩次普攻之間的 tick 數
+    fn attack_interval(&self) -> u32 {
+        let speed = self.modified(self.unit.state.attack_speed, StatusEffectType::AttackSpeedUp, StatusEffectType::AttackSpeedDown);
+        (1000 / speed.max(20)) as u32
+    }
+
+    fn stunned(&self) -> bool {
+        self.unit.status_effects.iter().any(|e| e.kind == StatusEffectType::Stun)
+    }
+
+    fn distance(&self, other: &Fighter) -> i32 {
+        distance(self.position, other.position)
+    }
+}
+
+fn distance(a: (i32, i32), b: (i32, i32)) -> i32 {
+    (a.0 - b.0).abs().max((a.1 - b.1).abs())
+}
+
+fn mitigate(amount: i32, resist: i32) -> i32 {
+    amount * 100 / (100 + resist.max(0))
+}
+
+struct Battle {
+    fighters: Vec<Fighter>,
+    tick: u32,
+    events: Vec<BattleEvent>,
+    rng: StdRng,
+}
+
+/// 模擬一場戰鬥；`home` 與 `away` 的位置都以各自半場表示
+pub fn simulate(home: Vec<Combatant>, away: Vec<Combatant>, seed: u64) -> BattleOutcome {
+    // 雙方交錯排列，避免同一方總是先出手
+    let mut home = home.into_iter().map(|c| Fighter::new(c, Side::Home));
+    let mut away = away.into_iter().map(|c| Fighter::new(c, Side::Away));
+    let mut fighters = Vec::new();
+    loop {
+        match (home.next(), away.next()) {
+            (None, None) => break,
+            (a, b) => fighters.extend(a.into_iter().chain(b)),
+        }
+    }
+
+    let mut battle = Battle { fighters, tick: 0, events: Vec::new(), rng: StdRng::seed_from_u64(seed) };
+    for index in 0..battle.fighters.len() {
+        if let Some(target) = battle.nearest_enemy(index) {
+            battle.trigger(index, target, |condition| matches!(condition, TriggerCondition::Always));
+        }
+    }
+    while battle.tick < MAX_TICKS && battle.both_sides_alive() {
+        for index in 0..battle.fighters.len() {
+            battle.act(index);
+        }
+        battle.tick += 1;
+    }
+    battle.finish()
+}
+
+impl Battle {
+    fn at(&self) -> u32 {
+        self.tick * TICK_MS
+    }
+
+    fn both_sides_alive(&self) -> bool {
+        let alive = |side| self.fighters.iter().any(|f| f.side == side && f.alive());
+        alive(Side::Home) && alive(Side::Away)
+    }
+
+    fn act(&mut self, index: usize) {
+        if !self.fighters[index].alive() {
+            return;
+        }
+        // 狀態效果的持續時間以 tick 計算（模板中以秒表示，套用時換算）
+        let effects = &mut self.fighters[index].unit.status_effects;
+        effects.iter_mut().for_each(|e| e.duration = e.duration.saturating_sub(1));
+        effects.retain(|e| e.duration > 0);
+        if self.fighters[index].stunned() {
+            return;
+        }
+        let Some(target) = self.nearest_enemy(index) else {
+            return;
+        };
+
+        let fighter = &self.fighters[index];
+        if fighter.distance(&self.fighters[target]) <= fighter.unit.template.base_attrs.attack_range.max(1) {
+            if self.tick >= fighter.next_attack {
+                self.attack(index, target);
+            }
+        } else if self.tick >= fighter.next_move {
+            self.step_toward(index, target);
+        }
+    }
+
+    fn nearest_enemy(&self, index: usize) -> Option<usize> {
+        let fighter = &self.fighters[index];
+        self.fighters
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| f.side != fighter.side && f.alive())
+            .min_by_key(|(_, f)| fighter.distance(f))
+            .map(|(i, _)| i)
+    }
+
+    fn occupied(&self, cell: (i32, i32)) -> bool {
+        self.fighters.iter().any(|f| f.alive() && f.position == cell)
+    }
+
+    /// 往目標走一格（只走能縮短距離的空格）
+    fn step_toward(&mut self, index: usize, target: usize) {
+        let from = self.fighters[index].position;
+        let goal = self.fighters[target].position;
+        let best = self
+            .free_neighbours(from)
+            .into_iter()
+            .filter(|&cell| distance(cell, goal) < distance(from, goal))
+            .min_by_key(|&cell| distance(cell, goal));
+        if let Some(cell) = best {
+            let at = self.at();
+            let fighter = &mut self.fighters[index];
+            fighter.position = cell;
+            fighter.next_move = self.tick + MOVE_TICKS;
+            let event = BattleEvent::Move { at, unit: fighter.key.clone(), to: [cell.0, cell.1] };
+            self.events.push(event);
+        }
+    }
+
+    fn free_neighbours(&self, (x, y): (i32, i32)) -> Vec<(i32, i32)> {
+        let mut cells = Vec::new();
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let cell = (x + dx, y + dy);
+                if (dx, dy) != (0, 0)
+                    && (0..BOARD_WIDTH).contains(&cell.0)
+                    && (0..BOARD_HEIGHT).contains(&cell.1)
+                    && !self.occupied(cell)
+                {
+                    cells.push(cell);
+                }
+            }
+        }
+        cells
+    }
+
+    fn attack(&mut self, index: usize, target: usize) {
+        let damage = mitigate(self.fighters[index].attack_damage(), self.fighters[target].armor());
+        let at = self.at();
+        let fighter = &mut self.fighters[index];
+        fighter.next_attack = self.tick + fighter.attack_interval();
+        fighter.unit.state.mp += MANA_PER_ATTACK;
+        let event = BattleEvent::Attack {
+            at,
+            unit: fighter.key.clone(),
+            target: self.fighters[target].key.clone(),
+            damage,
+        };
+        self.events.push(event);
+        self.hurt(index, target, damage);
+        self.trigger(index, target, |condition| matches!(condition, TriggerCondition::OnHit));
+
+        let fighter = &self.fighters[index];
+        if !fighter.alive() {
+            return;
+        }
+        if fighter.unit.template.base_attrs.max_mp > 0 && fighter.unit.state.mp >= fighter.unit.template.base_attrs.max_mp {
+            self.cast(index, target);
+        }
+    }
+
+    /// 施放第一個主動技能，依效果順序執行
+    fn cast(&mut self, index: usize, target: usize) {
+        self.fighters[index].unit.state.mp = 0;
+        let Some(skill) = self.fighters[index]
+            .unit
+            .template
+            .skills
+            .iter()
+            .find(|s| matches!(s.skill_type, SkillType::Active))
+            .cloned()
+        else {
+            return;
+        };
+        self.run_skill(index, target, skill);
+    }
+
+    /// 觸發符合條件的被動技能（道具或觸發型技能）
+    fn trigger(&mut self, index: usize, target: usize, fires: impl Fn(&TriggerCondition) -> bool) {
+        let skills: Vec<Skill> = self.fighters[index]
+            .unit
+            .template
+            .skills
+            .iter()
+            .filter(|s| matches!(s.skill_type, SkillType::Trigger) && s.trigger_condition.as_ref().is_some_and(&fires))
+            .cloned()
+            .collect();
+        for skill in skills {
+            if !self.fighters[index].alive() {
+                return;
+            }
+            self.run_skill(index, target, skill);
+        }
+    }
+
+    fn run_skill(&mut self, index: usize, target: usize, skill: Skill) {
+        let event = BattleEvent::Cast { at: self.at(), unit: self.fighters[index].key.clone(), skill: skill.id.clone() };
+        self.events.push(event);
+
+        let mut effects = skill.skill_effect;
+        effects.sort_by_key(|meta| meta.order);
+        // 同一次施放中的隨機目標保持一致（例如先突進再攻擊同一個敵人）
+        let mut random_enemy = None;
+        let mut random_ally = None;
+        for meta in effects {
+            let selector = match &meta.effect {
+                SkillEffect::PhysicalDamage { target: t, .. }
+                | SkillEffect::MagicalDamage { target: t, .. }
+                | SkillEffect::TrueDamage { target: t, .. }
+                | SkillEffect::Heal { target: t, .. }
+                | SkillEffect::FlatDamage { target: t, .. }
+                | SkillEffect::Dash { target: t, .. }
+                | SkillEffect::Buff { target: t, .. }
+                | SkillEffect::Debuff { target: t, .. } => t.clone(),
+            };
+            let targets = self.select(index, target, &selector, &mut random_enemy, &mut random_ally);
+            for t in targets {
+                self.apply(index, t, &meta.effect);
+            }
+        }
+    }
+
+    fn select(
+        &mut self,
+        index: usize,
+        target: usize,
+        selector: &SkillTarget,
+        random_enemy: &mut Option<usize>,
+        random_ally: &mut Option<usize>,
+    ) -> Vec<usize> {
+        let side = self.fighters[index].side;
+        let enemies: Vec<usize> = (0..self.fighters.len()).filter(|&i| self.fighters[i].side != side && self.fighters[i].alive()).collect();
+        let allies: Vec<usize> = (0..self.fighters.len()).filter(|&i| self.fighters[i].side == side && self.fighters[i].alive()).collect();
+        let within = |center: (i32, i32), range: u32| -> Vec<usize> {
+            enemies.iter().copied().filter(|&i| distance(self.fighters[i].position, center) <= range as i32).collect()
+        };
+        match selector {
+            SkillTarget::SelfTarget => vec![index],
+            SkillTarget::SingleEnemy | SkillTarget::Custom(_) => vec![target],
+            SkillTarget::AllEnemies => enemies,
+            SkillTarget::AllAllies => allies,
+            SkillTarget::SingleAlly => allies
+                .iter()
+                .copied()
+                .min_by_key(|&i| self.fighters[i].unit.state.hp * 100 / self.fighters[i].unit.template.base_attrs.max_hp.max(1))
+                .into_iter()
+                .collect(),
+            SkillTarget::RandomEnemy => {
+                let chosen = random_enemy.or_else(|| enemies.choose(&mut self.rng).copied());
+                *random_enemy = chosen;
+                chosen.into_iter().collect()
+            }
+            SkillTarget::RandomAlly => {
+                let chosen = random_ally.or_else(|| allies.choose(&mut self.rng).copied());
+                *random_ally = chosen;
+                chosen.into_iter().collect()
+            }
+            SkillTarget::AreaOfEffect(AoeShape::Circle { radius }) => within(self.fighters[target].position, *radius),
+            SkillTarget::AreaOfEffect(AoeShape::Line { distance }) => within(self.fighters[index].position, *distance),
+        }
+    }
+
+    fn apply(&mut self, index: usize, target: usize, effect: &SkillEffect) {
+        let scaled = |attr: &AttrType, ratio: f32, fighter: &Fighter| {
+            let base = match attr {
+                AttrType::AttackDamage => fighter.attack_damage(),
+                AttrType::AbilityPower => fighter.ability_power(),
+            };
+            (base as f32 * ratio).round() as i32
+        };
+        let caster = &self.fighters[index];
+        let victim = &self.fighters[target];
+        match effect {
+            SkillEffect::PhysicalDamage { attr, ratio, .. } => {
+                let amount = mitigate(scaled(attr, *ratio, caster), victim.armor());
+                self.skill_damage(index, target, amount);
+            }
+            SkillEffect::MagicalDamage { attr, ratio, .. } => {
+                let amount = mitigate(scaled(attr, *ratio, caster), victim.magic_resist());
+                self.skill_damage(index, target, amount);
+            }
+            SkillEffect::TrueDamage { attr, ratio, .. } => {
+                let amount = scaled(attr, *ratio, caster);
+                self.skill_damage(index, target, amount);
+            }
+            SkillEffect::FlatDamage { amount, .. } => self.skill_damage(index, target, *amount as i32),
+            SkillEffect::Heal { attr, ratio, .. } => {
+                let max_hp = victim.unit.template.base_attrs.max_hp;
+                let amount = scaled(attr, *ratio, caster).min(max_hp - victim.unit.state.hp).max(0);
+                self.fighters[target].unit.state.hp += amount;
+                let event = BattleEvent::Heal {
+                    at: self.at(),
+                    unit: self.fighters[index].key.clone(),
+                    target: self.fighters[target].key.clone(),
+                    amount,
+                };
+                self.events.push(event);
+            }
+            SkillEffect::Dash { .. } => {
+                let goal = self.fighters[target].position;
+                let landing = self
+                    .free_neighbours(goal)
+                    .into_iter()
+                    .min_by_key(|&cell| distance(cell, self.fighters[index].position));
+                if let Some(cell) = landing {
+                    self.fighters[index].position = cell;
+                    let event = BattleEvent::Move { at: self.at(), unit: self.fighters[index].key.clone(), to: [cell.0, cell.1] };
+                    self.events.push(event);
+                }
+            }
+            SkillEffect::Buff { effect, .. } | SkillEffect::Debuff { effect, .. } => {
+                let status = StatusEffect {
+                    kind: effect.kind,
+                    amount: effect.amount,
+                    duration: effect.duration * 1000 / TICK_MS,
+                };
+                self.fighters[target].unit.status_effects.push(status);
+            }
+        }
+    }
+
+    fn skill_damage(&mut self, index: usize, target: usize, amount: i32) {
+        if !self.fighters[target].alive() {
+            return;
+        }
+        let event = BattleEvent::Damage {
+            at: self.at(),
+            unit: self.fighters[index].key.clone(),
+            target: self.fighters[target].key.clone(),
+            amount,
+        };
+        self.events.push(event);
+        self.hurt(index, target, amount);
+    }
+
+    /// 先扣護盾再扣生命，之後處理擊殺、隊友陣亡與低血量的被動
+    fn hurt(&mut self, source: usize, target: usize, mut amount: i32) {
+        let at = self.at();
+        let fighter = &mut self.fighters[target];
+        for shield in fighter.unit.status_effects.iter_mut().filter(|e| e.kind == StatusEffectType::Shield) {
+            let absorbed = shield.amount.unwrap_or(0).min(amount);
+            shield.amount = Some(shield.amount.unwrap_or(0) - absorbed);
+            amount -= absorbed;
+        }
+        fighter.unit.status_effects.retain(|e| e.kind != StatusEffectType::Shield || e.amount.unwrap_or(0) > 0);
+
+        let was_alive = fighter.alive();
+        fighter.unit.state.hp -= amount;
+        if !was_alive {
+            return;
+        }
+        if !fighter.alive() {
+            let event = BattleEvent::Death { at, unit: fighter.key.clone() };
+            self.events.push(event);
+            if let Some(next) = self.nearest_enemy(source) {
+                self.trigger(source, next, |condition| matches!(condition, TriggerCondition::OnKill));
+            }
+            let side = self.fighters[target].side;
+            for ally in 0..self.fighters.len() {
+                if self.fighters[ally].side == side && self.fighters[ally].alive() {
+                    if let Some(next) = self.nearest_enemy(ally) {
+                        self.trigger(ally, next, |condition| matches!(condition, TriggerCondition::OnAllyDeath));
+                    }
+                }
+            }
+            return;
+        }
+
+        let percent = fighter.unit.state.hp * 100 / fighter.unit.template.base_attrs.max_hp.max(1);
+        let below: Vec<Skill> = fighter
+            .unit
+            .template
+            .skills
+            .iter()
+            .filter(|s| matches!(s.trigger_condition, Some(TriggerCondition::OnHpBelow { percent: p }) if percent < p as i32))
+            .filter(|s| !fighter.fired.contains(&s.id))
+            .cloned()
+            .collect();
+        fighter.fired.extend(below.iter().map(|s| s.id.clone()));
+        if let Some(next) = self.nearest_enemy(target) {
+            for skill in below {
+                self.run_skill(target, next, skill);
+            }
+        }
+    }
+
+    fn finish(self) -> BattleOutcome {
+        let alive = |side| self.fighters.iter().any(|f| f.side == side && f.alive());
+        let winner = match (alive(Side::Home), alive(Side::Away)) {
+            (true, false) => Some(Side::Home),
+            (false, true) => Some(Side::Away),
+            _ => None,
+        };
+        let survivors = self
+            .fighters
+            .iter()
+            .filter(|f| f.alive())
+            .map(|f| Survivor {
+                id: f.key.split_once(':').map_or(f.key.clone(), |(_, id)| id.to_string()),
+                side: f.side,
+                chess: f.unit.template.chess.clone(),
+                level: f.star,
+                hp: f.unit.state.hp,
+            })
+            .collect();
+        BattleOutcome {
+            winner,
+            survivors,
+            duration_ms: self.tick * TICK_MS,
+            events: self.events,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chesses::items::catalog::find_item;
+    use crate::chesses::units::models::Attrs;
+    use crate::chesses::units::catalog::find_template;
+
+    fn unit(id: &str, chess: &str, star: u32, position: [u32; 2]) -> Combatant {
+        Combatant { id: id.into(), template: find_template(chess).unwrap(), star, items: Vec::new(), bonus: Attrs::ZERO, position }
+    }
+
+    #[test]
+    fn test_stronger_board_wins_deterministically() {
+        let home = vec![unit("u1", "Knight", 2, [3, 3]), unit("u2", "Mage", 2, [3, 0])];
+        let away = vec![unit("u1", "Tank", 2, [3, 3])];
+        let outcome = simulate(home.clone(), away.clone(), 9);
+        assert_eq!(outcome.winner, Some(Side::Home));
+        assert!(outcome.survivors_of(Side::Away).next().is_none());
+        assert!(outcome.events.iter().any(|e| matches!(e, BattleEvent::Cast { .. })));
+        assert!(outcome.events.iter().any(|e| matches!(e, BattleEvent::Death { unit, .. } if unit == "away:u1")));
+
+        let again = simulate(home, away, 9);
+        assert_eq!(again.events, outcome.events);
+    }
+
+    #[test]
+    fn test_items_add_stats_and_trigger_passives() {
+        let mut armed = unit("u1", "Knight", 2, [3, 3]);
+        armed.items = ["deathblade", "bloodthirster"].iter().filter_map(|id| find_item(id)).collect();
+        let mut knight = unit("u2", "Knight", 1, [3, 3]);
+        knight.items = vec![find_item("warmogs_armor").unwrap()];
+        let outcome = simulate(vec![armed], vec![knight, unit("u4", "Archer", 1, [6, 0])], 3);
+        assert_eq!(outcome.winner, Some(Side::Home));
+
+        let cast = |unit: &str, skill: &str| {
+            outcome.events.iter().filter(|e| matches!(e, BattleEvent::Cast { unit: u, skill: s, .. } if u == unit && s == skill)).count()
+        };
+        assert!(cast("home:u1", "bloodthirster") > 0);
+        assert!(cast("home:u1", "deathblade") > 0);
+        // 低血量被動只觸發一次
+        assert_eq!(cast("away:u2", "warmogs_armor"), 1);
+    }
+
+    #[test]
+    fn test_empty_boards() {
+        let outcome = simulate(vec![unit("u1", "Archer", 1, [0, 0])], Vec::new(), 1);
+        assert_eq!(outcome.winner, Some(Side::Home));
+        assert_eq!(outcome.survivors[0].id, "u1");
+        assert_eq!(simulate(Vec::new(), Vec::new(), 1).winner, None);
+    }
+}
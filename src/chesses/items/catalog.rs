@@ -0,0 +1,315 @@
+use crate::chesses::items::models::{Item, ItemKind};
+use crate::chesses::skills::catalog::status;
+use crate::chesses::skills::models::{
+    AoeShape, AttrType, Skill, SkillEffect, SkillEffectMeta, SkillTarget, SkillType, StatusEffectType, TriggerCondition,
+};
+use crate::chesses::units::models::Attrs;
+use StatusEffectType::*;
+
+/// 基礎道具：id、名稱、(生命, 法力上限, 護甲, 魔抗, 攻擊, 法強)、攻速
+type ComponentRow = (&'static str, &'static str, [i32; 6], f32);
+
+const COMPONENTS: &[ComponentRow] = &[
+    ("sword", "B.F. Sword", [0, 0, 0, 0, 10, 0], 0.0),
+    ("bow", "Recurve Bow", [0, 0, 0, 0, 0, 0], 0.1),
+    ("rod", "Needlessly Large Rod", [0, 0, 0, 0, 0, 10], 0.0),
+    ("tear", "Tear of the Goddess", [0, -15, 0, 0, 0, 0], 0.0),
+    ("vest", "Chain Vest", [0, 0, 20, 0, 0, 0], 0.0),
+    ("cloak", "Negatron Cloak", [0, 0, 0, 20, 0, 0], 0.0),
+    ("belt", "Giant's Belt", [150, 0, 0, 0, 0, 0], 0.0),
+    ("glove", "Sparring Gloves", [0, 0, 0, 0, 5, 0], 0.05),
+];
+
+/// 合成道具：id、名稱、配方、被動；屬性為兩個基礎道具的總和
+type CombinedRow = (&'static str, &'static str, [&'static str; 2], Option<fn() -> Skill>);
+
+const COMBINED: &[CombinedRow] = &[
+    ("deathblade", "Deathblade", ["sword", "sword"], Some(deathblade)),
+    ("giant_slayer", "Giant Slayer", ["sword", "bow"], None),
+    ("hextech_gunblade", "Hextech Gunblade", ["sword", "rod"], None),
+    ("spear_of_shojin", "Spear of Shojin", ["sword", "tear"], None),
+    ("guardian_angel", "Guardian Angel", ["sword", "vest"], Some(guardian_angel)),
+    ("bloodthirster", "Bloodthirster", ["sword", "cloak"], Some(bloodthirster)),
+    ("steraks_gage", "Sterak's Gage", ["sword", "belt"], Some(steraks_gage)),
+    ("infinity_edge", "Infinity Edge", ["sword", "glove"], Some(infinity_edge)),
+    ("rapid_firecannon", "Rapid Firecannon", ["bow", "bow"], Some(rapid_firecannon)),
+    ("guinsoos_rageblade", "Guinsoo's Rageblade", ["bow", "rod"], None),
+    ("statikk_shiv", "Statikk Shiv", ["bow", "tear"], Some(statikk_shiv)),
+    ("titans_resolve", "Titan's Resolve", ["bow", "vest"], None),
+    ("runaans_hurricane", "Runaan's Hurricane", ["bow", "cloak"], None),
+    ("zekes_herald", "Zeke's Herald", ["bow", "belt"], Some(zekes_herald)),
+    ("last_whisper", "Last Whisper", ["bow", "glove"], Some(last_whisper)),
+    ("rabadons_deathcap", "Rabadon's Deathcap", ["rod", "rod"], Some(rabadons_deathcap)),
+    ("archangels_staff", "Archangel's Staff", ["rod", "tear"], None),
+    ("locket_of_the_iron_solari", "Locket of the Iron Solari", ["rod", "vest"], Some(locket)),
+    ("ionic_spark", "Ionic Spark", ["rod", "cloak"], None),
+    ("morellonomicon", "Morellonomicon", ["rod", "belt"], Some(morellonomicon)),
+    ("jeweled_gauntlet", "Jeweled Gauntlet", ["rod", "glove"], None),
+    ("blue_buff", "Blue Buff", ["tear", "tear"], None),
+    ("protectors_vow", "Protector's Vow", ["tear", "vest"], None),
+    ("chalice_of_power", "Chalice of Power", ["tear", "cloak"], None),
+    ("redemption", "Redemption", ["tear", "belt"], Some(redemption)),
+    ("hand_of_justice", "Hand of Justice", ["tear", "glove"], None),
+    ("bramble_vest", "Bramble Vest", ["vest", "vest"], Some(bramble_vest)),
+    ("gargoyle_stoneplate", "Gargoyle Stoneplate", ["vest", "cloak"], None),
+    ("sunfire_cape", "Sunfire Cape", ["vest", "belt"], Some(sunfire_cape)),
+    ("steadfast_heart", "Steadfast Heart", ["vest", "glove"], None),
+    ("dragons_claw", "Dragon's Claw", ["cloak", "cloak"], Some(dragons_claw)),
+    ("evenshroud", "Evenshroud", ["cloak", "belt"], None),
+    ("quicksilver", "Quicksilver", ["cloak", "glove"], None),
+    ("warmogs_armor", "Warmog's Armor", ["belt", "belt"], Some(warmogs_armor)),
+    ("guardbreaker", "Guardbreaker", ["belt", "glove"], None),
+    ("thiefs_gloves", "Thief's Gloves", ["glove", "glove"], None),
+];
+
+/// 戰鬥中不會結束的增益（超過戰鬥時間上限）
+const WHOLE_BATTLE: u32 = 60;
+
+/// 所有基礎道具與合成道具
+pub fn item_catalog() -> Vec<Item> {
+    let components = COMPONENTS.iter().map(|&(id, name, [max_hp, max_mp, armor, magic_resist, attack_damage, ability_power], attack_speed)| Item {
+        id: id.into(),
+        name: name.into(),
+        kind: ItemKind::Component,
+        recipe: None,
+        bonus: Attrs { max_hp, max_mp, armor, magic_resist, attack_damage, ability_power, attack_speed, attack_range: 0 },
+        passive: None,
+    });
+    let components: Vec<Item> = components.collect();
+    let bonus_of = |id: &str| components.iter().find(|item| item.id == id).map_or(Attrs::ZERO, |item| item.bonus);
+    let combined: Vec<Item> = COMBINED
+        .iter()
+        .map(|&(id, name, [a, b], passive)| Item {
+            id: id.into(),
+            name: name.into(),
+            kind: ItemKind::Combined,
+            recipe: Some([a.into(), b.into()]),
+            bonus: bonus_of(a) + bonus_of(b),
+            passive: passive.map(|skill| skill()),
+        })
+        .collect();
+    components.into_iter().chain(combined).collect()
+}
+
+/// 依 id 查詢道具
+pub fn find_item(id: &str) -> Option<Item> {
+    item_catalog().into_iter().find(|item| item.id == id)
+}
+
+/// 兩個基礎道具合成的道具（不分順序）
+pub fn combine(a: &str, b: &str) -> Option<Item> {
+    let (id, _, _, _) = COMBINED.iter().find(|(_, _, recipe, _)| *recipe == [a, b] || *recipe == [b, a])?;
+    find_item(id)
+}
+
+/// 被動技能：符合觸發條件時依列出的順序執行效果
+fn passive(id: &str, name: &str, description: &str, condition: TriggerCondition, effects: Vec<SkillEffect>) -> Skill {
+    Skill {
+        id: id.into(),
+        name: name.into(),
+        description: description.into(),
+        skill_type: SkillType::Trigger,
+        trigger_condition: Some(condition),
+        skill_effect: effects
+            .into_iter()
+            .enumerate()
+            .map(|(i, effect)| SkillEffectMeta { order: i as u8 + 1, effect })
+            .collect(),
+    }
+}
+
+fn buff(kind: StatusEffectType, amount: i32, duration: u32, target: SkillTarget) -> SkillEffect {
+    SkillEffect::Buff { effect: status(kind, Some(amount), duration), target }
+}
+
+fn deathblade() -> Skill {
+    passive(
+        "deathblade",
+        "Deathblade",
+        "Gain attack damage after each takedown.",
+        TriggerCondition::OnKill,
+        vec![buff(AttackDamageUp, 20, WHOLE_BATTLE, SkillTarget::SelfTarget)],
+    )
+}
+
+fn guardian_angel() -> Skill {
+    passive(
+        "guardian_angel",
+        "Guardian Angel",
+        "Gain a large shield when dropping below 25% health.",
+        TriggerCondition::OnHpBelow { percent: 25 },
+        vec![buff(Shield, 400, 3, SkillTarget::SelfTarget)],
+    )
+}
+
+fn bloodthirster() -> Skill {
+    passive(
+        "bloodthirster",
+        "Bloodthirster",
+        "Basic attacks heal for part of the damage dealt.",
+        TriggerCondition::OnHit,
+        vec![SkillEffect::Heal { attr: AttrType::AttackDamage, ratio: 0.25, target: SkillTarget::SelfTarget }],
+    )
+}
+
+fn steraks_gage() -> Skill {
+    passive(
+        "steraks_gage",
+        "Sterak's Gage",
+        "Gain attack damage when dropping below 50% health.",
+        TriggerCondition::OnHpBelow { percent: 50 },
+        vec![buff(AttackDamageUp, 30, WHOLE_BATTLE, SkillTarget::SelfTarget)],
+    )
+}
+
+fn infinity_edge() -> Skill {
+    passive(
+        "infinity_edge",
+        "Infinity Edge",
+        "Bonus attack damage for the whole battle.",
+        TriggerCondition::Always,
+        vec![buff(AttackDamageUp, 15, WHOLE_BATTLE, SkillTarget::SelfTarget)],
+    )
+}
+
+fn rapid_firecannon() -> Skill {
+    passive(
+        "rapid_firecannon",
+        "Rapid Firecannon",
+        "Bonus attack speed for the whole battle.",
+        TriggerCondition::Always,
+        vec![buff(AttackSpeedUp, 30, WHOLE_BATTLE, SkillTarget::SelfTarget)],
+    )
+}
+
+fn statikk_shiv() -> Skill {
+    passive(
+        "statikk_shiv",
+        "Statikk Shiv",
+        "Basic attacks zap a random enemy.",
+        TriggerCondition::OnHit,
+        vec![SkillEffect::FlatDamage { amount: 25, target: SkillTarget::RandomEnemy }],
+    )
+}
+
+fn zekes_herald() -> Skill {
+    passive(
+        "zekes_herald",
+        "Zeke's Herald",
+        "All allies gain attack speed at the start of battle.",
+        TriggerCondition::Always,
+        vec![buff(AttackSpeedUp, 15, WHOLE_BATTLE, SkillTarget::AllAllies)],
+    )
+}
+
+fn last_whisper() -> Skill {
+    passive(
+        "last_whisper",
+        "Last Whisper",
+        "Basic attacks shred the target's armor.",
+        TriggerCondition::OnHit,
+        vec![SkillEffect::Debuff { effect: status(ArmorDown, Some(20), 3), target: SkillTarget::SingleEnemy }],
+    )
+}
+
+fn rabadons_deathcap() -> Skill {
+    passive(
+        "rabadons_deathcap",
+        "Rabadon's Deathcap",
+        "Bonus ability power for the whole battle.",
+        TriggerCondition::Always,
+        vec![buff(AbilityPowerUp, 40, WHOLE_BATTLE, SkillTarget::SelfTarget)],
+    )
+}
+
+fn locket() -> Skill {
+    passive(
+        "locket_of_the_iron_solari",
+        "Locket of the Iron Solari",
+        "Shield all allies at the start of battle.",
+        TriggerCondition::Always,
+        vec![buff(Shield, 150, 5, SkillTarget::AllAllies)],
+    )
+}
+
+fn morellonomicon() -> Skill {
+    passive(
+        "morellonomicon",
+        "Morellonomicon",
+        "Basic attacks weaken the target's magic resist.",
+        TriggerCondition::OnHit,
+        vec![SkillEffect::Debuff { effect: status(MagicResistDown, Some(15), 3), target: SkillTarget::SingleEnemy }],
+    )
+}
+
+fn redemption() -> Skill {
+    passive(
+        "redemption",
+        "Redemption",
+        "Shield all allies whenever an ally dies.",
+        TriggerCondition::OnAllyDeath,
+        vec![buff(Shield, 120, 3, SkillTarget::AllAllies)],
+    )
+}
+
+fn bramble_vest() -> Skill {
+    passive(
+        "bramble_vest",
+        "Bramble Vest",
+        "Bonus armor for the whole battle.",
+        TriggerCondition::Always,
+        vec![buff(ArmorUp, 40, WHOLE_BATTLE, SkillTarget::SelfTarget)],
+    )
+}
+
+fn sunfire_cape() -> Skill {
+    passive(
+        "sunfire_cape",
+        "Sunfire Cape",
+        "Basic attacks burn adjacent enemies.",
+        TriggerCondition::OnHit,
+        vec![SkillEffect::FlatDamage { amount: 20, target: SkillTarget::AreaOfEffect(AoeShape::Circle { radius: 1 }) }],
+    )
+}
+
+fn dragons_claw() -> Skill {
+    passive(
+        "dragons_claw",
+        "Dragon's Claw",
+        "Bonus magic resist for the whole battle.",
+        TriggerCondition::Always,
+        vec![buff(MagicResistUp, 40, WHOLE_BATTLE, SkillTarget::SelfTarget)],
+    )
+}
+
+fn warmogs_armor() -> Skill {
+    passive(
+        "warmogs_armor",
+        "Warmog's Armor",
+        "Gain a shield when dropping below 50% health.",
+        TriggerCondition::OnHpBelow { percent: 50 },
+        vec![buff(Shield, 300, 5, SkillTarget::SelfTarget)],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_component_pair_combines() {
+        let components: Vec<Item> = item_catalog().into_iter().filter(Item::is_component).collect();
+        assert_eq!(components.len(), 8);
+        for (i, a) in components.iter().enumerate() {
+            for b in &components[i..] {
+                let combined = combine(&a.id, &b.id).unwrap_or_else(|| panic!("no recipe for {} + {}", a.id, b.id));
+                assert_eq!(combine(&b.id, &a.id).unwrap().id, combined.id);
+                assert_eq!(combined.kind, ItemKind::Combined);
+                assert_eq!(combined.bonus.attack_damage, a.bonus.attack_damage + b.bonus.attack_damage);
+            }
+        }
+        assert_eq!(item_catalog().len(), 8 + 36);
+        assert!(find_item("deathblade").unwrap().passive.is_some());
+        assert!(combine("deathblade", "sword").is_none());
+    }
+}
@@ -0,0 +1,30 @@
+use serde::Serialize;
+use crate::chesses::skills::models::Skill;
+use crate::chesses::units::models::Attrs;
+
+/// 道具的主要資料結構
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Item {
+    pub id: String,                         // e.g., "sword", "deathblade"
+    pub name: String,                       // 顯示名稱
+    pub kind: ItemKind,
+    pub recipe: Option<[String; 2]>,        // 合成道具由哪兩個基礎道具合成
+    pub bonus: Attrs,                       // 裝備後增加的屬性
+    pub passive: Option<Skill>,             // 裝備後獲得的被動（觸發型技能）
+}
+
+/// 道具種類
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ItemKind {
+    /// 基礎道具，兩個可以合成一個合成道具
+    Component,
+    Combined,
+}
+
+impl Item {
+    pub fn is_component(&self) -> bool {
+        self.kind == ItemKind::Component
+    }
+}
@@ -1,3 +1,5 @@
 pub mod skills;
 pub mod units;
-pub mod synergies;
\ No newline at end of file
+pub mod items;
+pub mod synergies;
+pub mod combat;
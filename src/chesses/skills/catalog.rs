@@ -0,0 +1,137 @@
+use crate::chesses::skills::example::fireball::fireball;
+use crate::chesses::skills::models::{
+    AoeShape, AttrType, Skill, SkillEffect, SkillEffectMeta, SkillTarget, SkillType, StatusEffect, StatusEffectType,
+};
+
+/// 商店棋子使用的所有技能
+pub fn skill_catalog() -> Vec<Skill> {
+    vec![
+        fireball(),
+        shield_bash(),
+        backstab(),
+        holy_light(),
+        volley(),
+        cleave(),
+        barrier(),
+        war_cry(),
+        curse(),
+    ]
+}
+
+/// 依 id 查詢技能
+#[cfg(test)]
+pub fn find_skill(id: &str) -> Option<Skill> {
+    skill_catalog().into_iter().find(|skill| skill.id == id)
+}
+
+/// 主動技能：效果依列出的順序執行
+fn active(id: &str, name: &str, description: &str, effects: Vec<SkillEffect>) -> Skill {
+    Skill {
+        id: id.into(),
+        name: name.into(),
+        description: description.into(),
+        skill_type: SkillType::Active,
+        trigger_condition: None,
+        skill_effect: effects
+            .into_iter()
+            .enumerate()
+            .map(|(i, effect)| SkillEffectMeta { order: i as u8 + 1, effect })
+            .collect(),
+    }
+}
+
+pub fn status(kind: StatusEffectType, amount: Option<i32>, duration: u32) -> StatusEffect {
+    StatusEffect { kind, amount, duration }
+}
+
+pub fn shield_bash() -> Skill {
+    active(
+        "shield_bash",
+        "Shield Bash",
+        "Bash the current target and stun it briefly.",
+        vec![
+            SkillEffect::PhysicalDamage { attr: AttrType::AttackDamage, ratio: 1.2, target: SkillTarget::SingleEnemy },
+            SkillEffect::Debuff { effect: status(StatusEffectType::Stun, None, 1), target: SkillTarget::SingleEnemy },
+        ],
+    )
+}
+
+pub fn backstab() -> Skill {
+    active(
+        "backstab",
+        "Backstab",
+        "Leap behind a random enemy and strike for heavy damage.",
+        vec![
+            SkillEffect::Dash { distance: 3, target: SkillTarget::RandomEnemy },
+            SkillEffect::PhysicalDamage { attr: AttrType::AttackDamage, ratio: 2.2, target: SkillTarget::RandomEnemy },
+        ],
+    )
+}
+
+pub fn holy_light() -> Skill {
+    active(
+        "holy_light",
+        "Holy Light",
+        "Heal the most wounded ally.",
+        vec![SkillEffect::Heal { attr: AttrType::AbilityPower, ratio: 2.0, target: SkillTarget::SingleAlly }],
+    )
+}
+
+pub fn volley() -> Skill {
+    active(
+        "volley",
+        "Volley",
+        "Fire a line of arrows through several enemies.",
+        vec![SkillEffect::PhysicalDamage {
+            attr: AttrType::AttackDamage,
+            ratio: 0.9,
+            target: SkillTarget::AreaOfEffect(AoeShape::Line { distance: 3 }),
+        }],
+    )
+}
+
+pub fn cleave() -> Skill {
+    active(
+        "cleave",
+        "Cleave",
+        "Swing at every adjacent enemy.",
+        vec![SkillEffect::PhysicalDamage {
+            attr: AttrType::AttackDamage,
+            ratio: 1.3,
+            target: SkillTarget::AreaOfEffect(AoeShape::Circle { radius: 1 }),
+        }],
+    )
+}
+
+pub fn barrier() -> Skill {
+    active(
+        "barrier",
+        "Barrier",
+        "Gain a shield that absorbs damage.",
+        vec![SkillEffect::Buff { effect: status(StatusEffectType::Shield, Some(250), 4), target: SkillTarget::SelfTarget }],
+    )
+}
+
+pub fn war_cry() -> Skill {
+    active(
+        "war_cry",
+        "War Cry",
+        "Increase the attack speed of all allies.",
+        vec![SkillEffect::Buff {
+            effect: status(StatusEffectType::AttackSpeedUp, Some(30), 5),
+            target: SkillTarget::AllAllies,
+        }],
+    )
+}
+
+pub fn curse() -> Skill {
+    active(
+        "curse",
+        "Curse",
+        "Damage all enemies and shred their armor.",
+        vec![
+            SkillEffect::MagicalDamage { attr: AttrType::AbilityPower, ratio: 0.6, target: SkillTarget::AllEnemies },
+            SkillEffect::Debuff { effect: status(StatusEffectType::ArmorDown, Some(20), 4), target: SkillTarget::AllEnemies },
+        ],
+    )
+}
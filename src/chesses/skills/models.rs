@@ -124,7 +124,7 @@ pub struct StatusEffect {
 }
 
 /// 狀態類型
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub enum StatusEffectType {
     /* Buff */
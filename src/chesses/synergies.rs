@@ -0,0 +1,86 @@
+// 羈絆：棋盤上擁有同一標籤的不同棋子數量，每 2 隻提升一級（最多 3 級）
+//
+// 每一級替擁有該標籤的單位加上固定的屬性加成；強化符文可以額外計入標籤數量。
+
+use crate::chesses::units::catalog::find_template;
+use crate::chesses::units::models::{Attrs, SynergyTag};
+use crate::types::game_state::UnitOnBoard;
+use std::collections::{BTreeMap, BTreeSet};
+use SynergyTag::*;
+
+pub const ALL_TAGS: [SynergyTag; 11] = [Knight, Mage, Assassin, Human, Orc, Undead, Warrior, Ranger, Support, Beast, Elemental];
+
+/// 標籤在協定中的名稱（與 serde 一致，例如 `knight`）
+pub fn tag_name(tag: SynergyTag) -> String {
+    serde_json::to_value(tag).ok().and_then(|v| v.as_str().map(str::to_string)).unwrap_or_default()
+}
+
+pub fn bonus_level(count: u32) -> u32 {
+    (count / 2).min(3)
+}
+
+/// 各標籤的數量（同名棋子只算一次），`extra` 為額外計入的標籤
+pub fn count_tags(board: &[UnitOnBoard], extra: &[SynergyTag]) -> BTreeMap<SynergyTag, u32> {
+    let mut members: BTreeMap<SynergyTag, BTreeSet<&str>> = BTreeMap::new();
+    for unit in board {
+        for tag in find_template(&unit.chess).map(|t| t.synergies).unwrap_or_default() {
+            members.entry(tag).or_default().insert(unit.chess.as_str());
+        }
+    }
+    let mut counts: BTreeMap<SynergyTag, u32> = members.into_iter().map(|(tag, units)| (tag, units.len() as u32)).collect();
+    for tag in extra {
+        *counts.entry(*tag).or_default() += 1;
+    }
+    counts
+}
+
+/// 每一級羈絆給擁有該標籤單位的屬性加成
+pub fn tag_bonus(tag: SynergyTag) -> Attrs {
+    let mut bonus = Attrs::ZERO;
+    match tag {
+        Knight => bonus.armor = 15,
+        Mage => bonus.ability_power = 20,
+        Assassin => bonus.attack_damage = 12,
+        Human => bonus.max_hp = 80,
+        Orc => bonus.max_hp = 120,
+        Undead => bonus.magic_resist = 15,
+        Warrior => bonus.attack_damage = 8,
+        Ranger => bonus.attack_speed = 0.1,
+        Support => bonus.max_mp = -10,
+        Beast => bonus.attack_speed = 0.05,
+        Elemental => bonus.ability_power = 15,
+    }
+    bonus
+}
+
+/// 依羈絆等級算出某個單位的加成
+pub fn unit_bonus(tags: &[SynergyTag], counts: &BTreeMap<SynergyTag, u32>) -> Attrs {
+    tags.iter().fold(Attrs::ZERO, |total, tag| {
+        let level = bonus_level(counts.get(tag).copied().unwrap_or(0));
+        (0..level).fold(total, |total, _| total + tag_bonus(*tag))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit(chess: &str) -> UnitOnBoard {
+        UnitOnBoard { id: chess.into(), chess: chess.into(), level: 1, position: [0, 0], items: Vec::new() }
+    }
+
+    #[test]
+    fn test_counts_distinct_units_and_extra_tags() {
+        let board = vec![unit("Knight"), unit("Knight"), unit("Paladin"), unit("Mage")];
+        let counts = count_tags(&board, &[]);
+        assert_eq!(counts[&Knight], 2);
+        assert_eq!(counts[&Human], 3);
+        assert_eq!(bonus_level(counts[&Human]), 1);
+
+        let counts = count_tags(&board, &[Mage]);
+        assert_eq!(counts[&Mage], 2);
+        assert_eq!(unit_bonus(&[Knight, Human], &counts).armor, 15);
+        assert_eq!(unit_bonus(&[Mage, Human], &counts).ability_power, 20);
+        assert_eq!(tag_name(Elemental), "elemental");
+    }
+}
@@ -0,0 +1,84 @@
+use crate::chesses::skills::catalog::{
+    backstab, barrier, cleave, curse, holy_light, shield_bash, volley, war_cry,
+};
+use crate::chesses::skills::example::fireball::fireball;
+use crate::chesses::skills::models::Skill;
+use crate::chesses::units::models::{Attrs, ChessTemplate, StarLevel, SynergyTag};
+use SynergyTag::*;
+
+/// 一星屬性表：名稱、(生命, 法力, 護甲, 魔抗, 攻擊, 法強)、攻速、射程、技能、羈絆
+type Row = (&'static str, [i32; 6], f32, i32, fn() -> Skill, &'static [SynergyTag]);
+
+const UNITS: &[Row] = &[
+    ("Mage", [500, 80, 20, 30, 40, 100], 0.65, 3, fireball, &[Mage, Human]),
+    ("Knight", [650, 100, 45, 30, 55, 0], 0.6, 1, shield_bash, &[Knight, Human]),
+    ("Assassin", [550, 60, 25, 25, 80, 0], 0.8, 1, backstab, &[Assassin, Undead]),
+    ("Tank", [850, 120, 55, 40, 45, 0], 0.55, 1, barrier, &[Knight, Orc]),
+    ("Priest", [500, 70, 20, 35, 35, 90], 0.6, 3, holy_light, &[Support, Human]),
+    ("Hunter", [550, 80, 20, 20, 65, 0], 0.75, 4, volley, &[Ranger, Beast]),
+    ("Archer", [500, 80, 20, 20, 65, 0], 0.75, 4, volley, &[Ranger, Human]),
+    ("Berserker", [700, 80, 35, 25, 70, 0], 0.75, 1, cleave, &[Warrior, Orc]),
+    ("Paladin", [700, 90, 45, 35, 50, 60], 0.6, 1, holy_light, &[Knight, Human]),
+    ("Warlock", [520, 80, 20, 30, 40, 100], 0.6, 3, curse, &[Mage, Undead]),
+    ("Necromancer", [520, 90, 20, 35, 35, 110], 0.6, 3, curse, &[Mage, Undead]),
+    ("Druid", [600, 80, 25, 35, 40, 80], 0.6, 2, holy_light, &[Support, Beast]),
+    ("Shaman", [600, 80, 25, 35, 45, 70], 0.65, 2, war_cry, &[Support, Orc]),
+    ("Blademaster", [650, 70, 30, 25, 75, 0], 0.8, 1, cleave, &[Warrior, Orc]),
+    ("Sniper", [480, 100, 15, 20, 85, 0], 0.6, 5, volley, &[Ranger, Human]),
+    ("Engineer", [600, 90, 35, 30, 45, 60], 0.6, 2, barrier, &[Support, Human]),
+    ("Beastmaster", [650, 80, 30, 30, 60, 0], 0.7, 1, war_cry, &[Warrior, Beast]),
+    ("Phantom", [520, 60, 20, 30, 75, 0], 0.85, 1, backstab, &[Assassin, Undead]),
+    ("Guardian", [900, 120, 60, 50, 40, 0], 0.5, 1, barrier, &[Knight, Elemental]),
+    ("Elemental", [550, 80, 25, 40, 40, 100], 0.6, 3, fireball, &[Mage, Elemental]),
+];
+
+/// 商店棋子的一星戰鬥模板，名稱與 `data::all_chess_pieces` 一致
+pub fn unit_catalog() -> Vec<ChessTemplate> {
+    UNITS
+        .iter()
+        .map(|&(name, [max_hp, max_mp, armor, magic_resist, attack_damage, ability_power], attack_speed, attack_range, skill, tags)| {
+            ChessTemplate {
+                id: format!("{}_1", name.to_lowercase()),
+                chess: name.into(),
+                level: StarLevel::One,
+                description: None,
+                base_attrs: Attrs {
+                    max_hp,
+                    max_mp,
+                    armor,
+                    magic_resist,
+                    attack_damage,
+                    ability_power,
+                    attack_speed,
+                    attack_range,
+                },
+                skills: vec![skill()],
+                synergies: tags.to_vec(),
+            }
+        })
+        .collect()
+}
+
+/// 依棋子名稱查詢一星模板
+pub fn find_template(name: &str) -> Option<ChessTemplate> {
+    unit_catalog().into_iter().find(|template| template.chess == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chesses::skills::catalog::find_skill;
+    use crate::data::all_chess_pieces;
+
+    #[test]
+    fn test_every_shop_unit_has_a_template() {
+        for piece in all_chess_pieces() {
+            let template = find_template(&piece.name).unwrap_or_else(|| panic!("missing template for {}", piece.name));
+            assert!(!template.synergies.is_empty());
+            for skill in &template.skills {
+                assert!(find_skill(&skill.id).is_some(), "{} is not in the skill catalog", skill.id);
+            }
+        }
+        assert_eq!(unit_catalog().len(), all_chess_pieces().len());
+    }
+}
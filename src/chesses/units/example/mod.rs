@@ -1 +0,0 @@
-pub mod garen;
\ No newline at end of file
@@ -1,2 +1,2 @@
 pub mod models;
-pub mod example;
\ No newline at end of file
+pub mod catalog;
@@ -29,8 +29,39 @@ pub struct Attrs {
     pub attack_range: i32,
 }
 
+impl Attrs {
+    /// 全部為 0，作為道具等加成的起點
+    pub const ZERO: Attrs = Attrs {
+        max_hp: 0,
+        max_mp: 0,
+        armor: 0,
+        magic_resist: 0,
+        attack_damage: 0,
+        ability_power: 0,
+        attack_speed: 0.0,
+        attack_range: 0,
+    };
+}
+
+impl std::ops::Add for Attrs {
+    type Output = Attrs;
+
+    fn add(self, other: Attrs) -> Attrs {
+        Attrs {
+            max_hp: self.max_hp + other.max_hp,
+            max_mp: self.max_mp + other.max_mp,
+            armor: self.armor + other.armor,
+            magic_resist: self.magic_resist + other.magic_resist,
+            attack_damage: self.attack_damage + other.attack_damage,
+            ability_power: self.ability_power + other.ability_power,
+            attack_speed: self.attack_speed + other.attack_speed,
+            attack_range: self.attack_range + other.attack_range,
+        }
+    }
+}
+
 /// 羈絆系統標籤（Trait / Origin / Class）
-#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
 #[serde(rename_all = "snake_case")]
 pub enum SynergyTag {
     Knight,
@@ -39,6 +70,11 @@ pub enum SynergyTag {
     Human,
     Orc,
     Undead,
+    Warrior,
+    Ranger,
+    Support,
+    Beast,
+    Elemental,
     // …其他羈絆
 }
 
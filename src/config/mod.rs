@@ -0,0 +1,535 @@
+// 伺服器設定：TOML 檔 < 環境變數 < 命令列參數
+//
+// 所有欄位都有預設值，設定檔只需要寫要覆寫的部分。
+// 啟動時驗證一次，之後以 `Config` 的各區塊傳給需要的元件。
+
+use crate::bots::{Difficulty, DEFAULT_THINK_TIME};
+use crate::connection::session::DEFAULT_TOKEN_TTL;
+use crate::connection::{SessionSigner, DEFAULT_RECONNECT_GRACE};
+use crate::game::rules::PLANNING_SECS;
+use crate::game::GameRules;
+use crate::websocket::HeartbeatConfig;
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// 機器人數量上限
+const MAX_BOTS: usize = 64;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub network: NetworkConfig,
+    pub requests: RequestsConfig,
+    pub timing: TimingConfig,
+    pub economy: EconomyConfig,
+    pub matchmaking: MatchmakingConfig,
+    pub session: SessionConfig,
+    pub persistence: PersistenceConfig,
+    pub bots: BotsConfig,
+}
+
+/// 網路設定
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NetworkConfig {
+    /// WebSocket 監聽位址
+    pub bind: String,
+    /// HTTP API 監聽位址
+    pub http_bind: String,
+}
+
+/// 請求處理設定
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RequestsConfig {
+    /// 每條連線每秒補充的請求數
+    pub rate_per_second: u32,
+    /// 每條連線最多可累積的請求數（瞬間爆量）
+    pub rate_burst: u32,
+    /// 處理超過此時間（毫秒）的請求記一筆警告
+    pub slow_request_millis: u64,
+}
+
+/// 時間設定（秒）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TimingConfig {
+    /// 心跳 ping 的間隔
+    pub ping_interval_secs: u64,
+    /// 多久沒收到任何訊息就關閉連線
+    pub idle_timeout_secs: u64,
+    /// 斷線後保留座位的寬限期
+    pub reconnect_grace_secs: u64,
+    /// 每回合準備（等待）階段的長度
+    pub planning_secs: u64,
+    /// 關機時等待連線結束的期限
+    pub shutdown_deadline_secs: u64,
+    /// 關機通知中建議客戶端多久後重連
+    pub reconnect_after_secs: u64,
+    /// 觀戰畫面的延遲，避免實況被看牌（0 為即時）
+    pub spectator_delay_secs: u64,
+}
+
+/// 經濟設定（金幣）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EconomyConfig {
+    pub starting_money: i32,
+    pub xp_cost: i32,
+    pub refresh_cost: i32,
+}
+
+/// 配對設定
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MatchmakingConfig {
+    /// 每場遊戲的人數
+    pub lobby_size: usize,
+    /// 配對成功後等待玩家確認的時間（秒）
+    pub ready_check_secs: u64,
+    /// 初始的 rating 差距上限；未設定時不看 rating
+    pub rating_window: Option<u32>,
+    /// 每排隊一秒，rating 差距上限放寬多少
+    pub rating_window_growth: u32,
+}
+
+/// Session token 設定
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SessionConfig {
+    /// 簽章金鑰；未設定時每次啟動隨機產生，重啟後舊 token 失效
+    pub secret: Option<String>,
+    pub token_ttl_secs: u64,
+}
+
+/// 狀態保存設定
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PersistenceConfig {
+    /// 玩家資料檔；啟動時載入、關機時寫回，未設定則不保存
+    pub state_file: Option<PathBuf>,
+    /// 遊戲資料檔；關機時保存已開始的遊戲，啟動時載入並接續進行中遊戲的回合流程
+    pub games_file: Option<PathBuf>,
+}
+
+/// 機器人設定
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BotsConfig {
+    /// 啟動時建立並開始排隊的機器人數量
+    pub count: usize,
+    pub difficulty: Difficulty,
+    /// 每個動作之間的間隔（毫秒）
+    pub think_millis: u64,
+}
+
+impl Default for BotsConfig {
+    fn default() -> Self {
+        Self { count: 0, difficulty: Difficulty::default(), think_millis: DEFAULT_THINK_TIME.as_millis() as u64 }
+    }
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            bind: "127.0.0.1:9002".to_string(),
+            http_bind: "127.0.0.1:9003".to_string(),
+        }
+    }
+}
+
+impl Default for RequestsConfig {
+    fn default() -> Self {
+        Self {
+            rate_per_second: 20,
+            rate_burst: 40,
+            slow_request_millis: 200,
+        }
+    }
+}
+
+impl RequestsConfig {
+    pub fn slow_request(&self) -> Duration {
+        Duration::from_millis(self.slow_request_millis)
+    }
+}
+
+impl Default for TimingConfig {
+    fn default() -> Self {
+        let heartbeat = HeartbeatConfig::default();
+        Self {
+            ping_interval_secs: heartbeat.ping_interval.as_secs(),
+            idle_timeout_secs: heartbeat.idle_timeout.as_secs(),
+            reconnect_grace_secs: DEFAULT_RECONNECT_GRACE.as_secs(),
+            planning_secs: 60,
+            shutdown_deadline_secs: 10,
+            reconnect_after_secs: 5,
+            spectator_delay_secs: 0,
+        }
+    }
+}
+
+impl Default for EconomyConfig {
+    fn default() -> Self {
+        Self {
+            starting_money: 100,
+            xp_cost: 4,
+            refresh_cost: 2,
+        }
+    }
+}
+
+impl Default for MatchmakingConfig {
+    fn default() -> Self {
+        Self {
+            lobby_size: 8,
+            ready_check_secs: 15,
+            rating_window: None,
+            rating_window_growth: 10,
+        }
+    }
+}
+
+impl MatchmakingConfig {
+    pub fn ready_check(&self) -> Duration {
+        Duration::from_secs(self.ready_check_secs)
+    }
+
+    /// 排隊 `waited` 之後可接受的 rating 差距；`None` 代表不限制
+    pub fn rating_window_after(&self, waited: Duration) -> Option<u32> {
+        self.rating_window
+            .map(|initial| initial.saturating_add(self.rating_window_growth.saturating_mul(waited.as_secs() as u32)))
+    }
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            secret: None,
+            token_ttl_secs: DEFAULT_TOKEN_TTL.as_secs(),
+        }
+    }
+}
+
+impl TimingConfig {
+    pub fn heartbeat(&self) -> HeartbeatConfig {
+        HeartbeatConfig {
+            ping_interval: Duration::from_secs(self.ping_interval_secs),
+            idle_timeout: Duration::from_secs(self.idle_timeout_secs),
+        }
+    }
+
+    pub fn reconnect_grace(&self) -> Duration {
+        Duration::from_secs(self.reconnect_grace_secs)
+    }
+
+    pub fn spectator_delay(&self) -> Duration {
+        Duration::from_secs(self.spectator_delay_secs)
+    }
+
+    pub fn shutdown_deadline(&self) -> Duration {
+        Duration::from_secs(self.shutdown_deadline_secs)
+    }
+
+    pub fn reconnect_after(&self) -> Duration {
+        Duration::from_secs(self.reconnect_after_secs)
+    }
+}
+
+impl SessionConfig {
+    /// 依設定建立簽章器
+    pub fn signer(&self) -> SessionSigner {
+        let ttl = Duration::from_secs(self.token_ttl_secs);
+        match &self.secret {
+            Some(secret) => SessionSigner::new(secret.as_bytes().to_vec(), ttl),
+            None => SessionSigner::random().with_ttl(ttl),
+        }
+    }
+}
+
+/// 設定載入或驗證失敗
+#[derive(Debug)]
+pub enum ConfigError {
+    Read(PathBuf, std::io::Error),
+    Parse(PathBuf, toml::de::Error),
+    Invalid(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Read(path, e) => write!(f, "cannot read config {}: {}", path.display(), e),
+            ConfigError::Parse(path, e) => write!(f, "invalid config {}: {}", path.display(), e),
+            ConfigError::Invalid(reason) => write!(f, "invalid config: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// 命令列參數；每個參數也可以用對應的環境變數設定（命令列優先）
+#[derive(Debug, Default, Parser)]
+#[command(version, about = "Chess Fight WebSocket server")]
+pub struct Cli {
+    /// TOML 設定檔
+    #[arg(short, long, env = "CHESS_FIGHT_CONFIG")]
+    pub config: Option<PathBuf>,
+    /// WebSocket 監聽位址
+    #[arg(long, env = "CHESS_FIGHT_BIND")]
+    pub bind: Option<String>,
+    /// HTTP API 監聽位址
+    #[arg(long, env = "CHESS_FIGHT_HTTP_BIND")]
+    pub http_bind: Option<String>,
+    /// 每條連線每秒可送出的請求數
+    #[arg(long, env = "CHESS_FIGHT_RATE_PER_SECOND")]
+    pub rate_per_second: Option<u32>,
+    /// 每條連線瞬間最多可送出的請求數
+    #[arg(long, env = "CHESS_FIGHT_RATE_BURST")]
+    pub rate_burst: Option<u32>,
+    /// 慢請求的警告門檻（毫秒）
+    #[arg(long, env = "CHESS_FIGHT_SLOW_REQUEST_MILLIS")]
+    pub slow_request_millis: Option<u64>,
+    /// 心跳間隔（秒）
+    #[arg(long, env = "CHESS_FIGHT_PING_INTERVAL_SECS")]
+    pub ping_interval_secs: Option<u64>,
+    /// 閒置逾時（秒）
+    #[arg(long, env = "CHESS_FIGHT_IDLE_TIMEOUT_SECS")]
+    pub idle_timeout_secs: Option<u64>,
+    /// 斷線保留座位的寬限期（秒）
+    #[arg(long, env = "CHESS_FIGHT_RECONNECT_GRACE_SECS")]
+    pub reconnect_grace_secs: Option<u64>,
+    /// 準備階段長度（秒）
+    #[arg(long, env = "CHESS_FIGHT_PLANNING_SECS")]
+    pub planning_secs: Option<u64>,
+    /// 關機時等待連線結束的期限（秒）
+    #[arg(long, env = "CHESS_FIGHT_SHUTDOWN_DEADLINE_SECS")]
+    pub shutdown_deadline_secs: Option<u64>,
+    /// 關機通知中建議客戶端重新連線的等待時間（秒）
+    #[arg(long, env = "CHESS_FIGHT_RECONNECT_AFTER_SECS")]
+    pub reconnect_after_secs: Option<u64>,
+    /// 觀戰延遲（秒）
+    #[arg(long, env = "CHESS_FIGHT_SPECTATOR_DELAY_SECS")]
+    pub spectator_delay_secs: Option<u64>,
+    /// 初始金幣
+    #[arg(long, env = "CHESS_FIGHT_STARTING_MONEY")]
+    pub starting_money: Option<i32>,
+    /// 購買經驗的花費
+    #[arg(long, env = "CHESS_FIGHT_XP_COST")]
+    pub xp_cost: Option<i32>,
+    /// 重新整理商店的花費
+    #[arg(long, env = "CHESS_FIGHT_REFRESH_COST")]
+    pub refresh_cost: Option<i32>,
+    /// 每場遊戲的人數
+    #[arg(long, env = "CHESS_FIGHT_LOBBY_SIZE")]
+    pub lobby_size: Option<usize>,
+    /// 配對確認時間（秒）
+    #[arg(long, env = "CHESS_FIGHT_READY_CHECK_SECS")]
+    pub ready_check_secs: Option<u64>,
+    /// 初始的 rating 差距上限
+    #[arg(long, env = "CHESS_FIGHT_RATING_WINDOW")]
+    pub rating_window: Option<u32>,
+    /// Session token 簽章金鑰
+    #[arg(long, env = "CHESS_FIGHT_SESSION_SECRET", hide_env_values = true)]
+    pub session_secret: Option<String>,
+    /// 玩家資料檔
+    #[arg(long, env = "CHESS_FIGHT_STATE_FILE")]
+    pub state_file: Option<PathBuf>,
+    /// 遊戲資料檔
+    #[arg(long, env = "CHESS_FIGHT_GAMES_FILE")]
+    pub games_file: Option<PathBuf>,
+    /// 啟動時建立的機器人數量
+    #[arg(long, env = "CHESS_FIGHT_BOTS")]
+    pub bots: Option<usize>,
+    /// 機器人難度
+    #[arg(long, env = "CHESS_FIGHT_BOT_DIFFICULTY", value_enum)]
+    pub bot_difficulty: Option<Difficulty>,
+    /// 機器人每個動作之間的間隔（毫秒）
+    #[arg(long, env = "CHESS_FIGHT_BOT_THINK_MILLIS")]
+    pub bot_think_millis: Option<u64>,
+}
+
+impl Config {
+    /// 從命令列與環境變數載入設定
+    pub fn load() -> Result<Self, ConfigError> {
+        Self::from_cli(Cli::parse())
+    }
+
+    pub fn from_cli(cli: Cli) -> Result<Self, ConfigError> {
+        let mut config = match &cli.config {
+            Some(path) => {
+                let text = std::fs::read_to_string(path).map_err(|e| ConfigError::Read(path.clone(), e))?;
+                toml::from_str(&text).map_err(|e| ConfigError::Parse(path.clone(), e))?
+            }
+            None => Config::default(),
+        };
+
+        // 命令列 / 環境變數覆寫設定檔
+        if let Some(bind) = cli.bind {
+            config.network.bind = bind;
+        }
+        if let Some(http_bind) = cli.http_bind {
+            config.network.http_bind = http_bind;
+        }
+        let requests = &mut config.requests;
+        requests.rate_per_second = cli.rate_per_second.unwrap_or(requests.rate_per_second);
+        requests.rate_burst = cli.rate_burst.unwrap_or(requests.rate_burst);
+        requests.slow_request_millis = cli.slow_request_millis.unwrap_or(requests.slow_request_millis);
+        let timing = &mut config.timing;
+        timing.ping_interval_secs = cli.ping_interval_secs.unwrap_or(timing.ping_interval_secs);
+        timing.idle_timeout_secs = cli.idle_timeout_secs.unwrap_or(timing.idle_timeout_secs);
+        timing.reconnect_grace_secs = cli.reconnect_grace_secs.unwrap_or(timing.reconnect_grace_secs);
+        timing.planning_secs = cli.planning_secs.unwrap_or(timing.planning_secs);
+        timing.spectator_delay_secs = cli.spectator_delay_secs.unwrap_or(timing.spectator_delay_secs);
+        timing.shutdown_deadline_secs = cli.shutdown_deadline_secs.unwrap_or(timing.shutdown_deadline_secs);
+        timing.reconnect_after_secs = cli.reconnect_after_secs.unwrap_or(timing.reconnect_after_secs);
+        let economy = &mut config.economy;
+        economy.starting_money = cli.starting_money.unwrap_or(economy.starting_money);
+        economy.xp_cost = cli.xp_cost.unwrap_or(economy.xp_cost);
+        economy.refresh_cost = cli.refresh_cost.unwrap_or(economy.refresh_cost);
+        let matchmaking = &mut config.matchmaking;
+        matchmaking.lobby_size = cli.lobby_size.unwrap_or(matchmaking.lobby_size);
+        matchmaking.ready_check_secs = cli.ready_check_secs.unwrap_or(matchmaking.ready_check_secs);
+        if cli.rating_window.is_some() {
+            matchmaking.rating_window = cli.rating_window;
+        }
+        if cli.session_secret.is_some() {
+            config.session.secret = cli.session_secret;
+        }
+        if cli.state_file.is_some() {
+            config.persistence.state_file = cli.state_file;
+        }
+        if cli.games_file.is_some() {
+            config.persistence.games_file = cli.games_file;
+        }
+        config.bots.count = cli.bots.unwrap_or(config.bots.count);
+        config.bots.difficulty = cli.bot_difficulty.unwrap_or(config.bots.difficulty);
+        config.bots.think_millis = cli.bot_think_millis.unwrap_or(config.bots.think_millis);
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// 啟動前檢查設定是否合理
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let invalid = |reason: &str| Err(ConfigError::Invalid(reason.to_string()));
+
+        if self.network.bind.parse::<SocketAddr>().is_err() {
+            return invalid("network.bind must be an address like 127.0.0.1:9002");
+        }
+        if self.network.http_bind.parse::<SocketAddr>().is_err() {
+            return invalid("network.http_bind must be an address like 127.0.0.1:9003");
+        }
+        if self.network.http_bind == self.network.bind {
+            return invalid("network.http_bind must differ from network.bind");
+        }
+        let requests = &self.requests;
+        if requests.rate_per_second == 0 {
+            return invalid("requests.rate_per_second must be greater than 0");
+        }
+        if requests.rate_burst < requests.rate_per_second {
+            return invalid("requests.rate_burst must be at least requests.rate_per_second");
+        }
+        let timing = &self.timing;
+        if timing.ping_interval_secs == 0 {
+            return invalid("timing.ping_interval_secs must be greater than 0");
+        }
+        if timing.idle_timeout_secs <= timing.ping_interval_secs {
+            return invalid("timing.idle_timeout_secs must be longer than timing.ping_interval_secs");
+        }
+        if !PLANNING_SECS.contains(&timing.planning_secs) {
+            return invalid(&format!("timing.planning_secs must be between {} and {}", PLANNING_SECS.start(), PLANNING_SECS.end()));
+        }
+        if timing.shutdown_deadline_secs == 0 {
+            return invalid("timing.shutdown_deadline_secs must be greater than 0");
+        }
+        if self.session.secret.as_ref().is_some_and(|secret| secret.len() < 16) {
+            return invalid("session.secret must be at least 16 bytes");
+        }
+        let economy = &self.economy;
+        if economy.starting_money < 0 || economy.xp_cost < 0 || economy.refresh_cost < 0 {
+            return invalid("economy values must not be negative");
+        }
+        let matchmaking = &self.matchmaking;
+        if !(2..=8).contains(&matchmaking.lobby_size) {
+            return invalid("matchmaking.lobby_size must be between 2 and 8");
+        }
+        if matchmaking.ready_check_secs == 0 {
+            return invalid("matchmaking.ready_check_secs must be greater than 0");
+        }
+        // 預設規則也要通過自訂房間的檢查，否則之後每個 SetLobbyRules 都會失敗
+        if let Err(e) = GameRules::from_config(economy, timing).validate() {
+            return invalid(&e.to_string());
+        }
+        if self.bots.count > MAX_BOTS {
+            return invalid(&format!("bots.count must be at most {}", MAX_BOTS));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults_match_previous_constants() {
+        let config = Config::default();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.network.bind, "127.0.0.1:9002");
+        assert_eq!(config.timing.heartbeat(), HeartbeatConfig::default());
+        assert_eq!(config.timing.reconnect_grace(), DEFAULT_RECONNECT_GRACE);
+        assert_eq!(config.economy, EconomyConfig { starting_money: 100, xp_cost: 4, refresh_cost: 2 });
+    }
+
+    #[test]
+    fn test_partial_toml_keeps_defaults() {
+        let config: Config = toml::from_str("[economy]\nstarting_money = 50\n").unwrap();
+        assert_eq!(config.economy.starting_money, 50);
+        assert_eq!(config.economy.xp_cost, 4);
+        assert_eq!(config.timing, TimingConfig::default());
+
+        assert!(toml::from_str::<Config>("[economy]\nstarting_gold = 50\n").is_err());
+    }
+
+    #[test]
+    fn test_cli_overrides_file() {
+        let path = std::env::temp_dir().join(format!("chess_fight_config_{}.toml", std::process::id()));
+        std::fs::write(&path, "[network]\nbind = \"0.0.0.0:9100\"\n[economy]\nxp_cost = 5\n").unwrap();
+
+        let cli = Cli::parse_from(["server", "--config", path.to_str().unwrap(), "--xp-cost", "6", "--bots", "3", "--bot-difficulty", "hard", "--bot-think-millis", "100", "--reconnect-after-secs", "10"]);
+        let config = Config::from_cli(cli).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(config.network.bind, "0.0.0.0:9100");
+        assert_eq!(config.economy.xp_cost, 6);
+        assert_eq!(config.bots, BotsConfig { count: 3, difficulty: Difficulty::Hard, think_millis: 100 });
+        assert_eq!(config.timing.reconnect_after_secs, 10);
+
+        let cli = Cli::parse_from(["server", "--rate-per-second", "5", "--rate-burst", "10", "--slow-request-millis", "50"]);
+        let config = Config::from_cli(cli).unwrap();
+        assert_eq!(config.requests, RequestsConfig { rate_per_second: 5, rate_burst: 10, slow_request_millis: 50 });
+    }
+
+    #[test]
+    fn test_validation_rejects_bad_values() {
+        let mut config = Config::default();
+        config.timing.idle_timeout_secs = config.timing.ping_interval_secs;
+        assert!(matches!(config.validate(), Err(ConfigError::Invalid(_))));
+
+        // 準備時間與自訂房間共用同一個範圍
+        for planning_secs in [2, 600] {
+            let cli = Cli { planning_secs: Some(planning_secs), ..Cli::default() };
+            assert!(Config::from_cli(cli).is_err());
+        }
+
+        let cli = Cli { rate_per_second: Some(10), rate_burst: Some(5), ..Cli::default() };
+        assert!(Config::from_cli(cli).is_err());
+
+        let cli = Cli { bind: Some("not an address".to_string()), ..Cli::default() };
+        assert!(Config::from_cli(cli).is_err());
+    }
+}
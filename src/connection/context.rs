@@ -0,0 +1,111 @@
+use super::{ConnectionHandle, ConnectionId, Outbound, PushError};
+use crate::types::codec::Encoding;
+use crate::types::protocol::Feature;
+use std::net::SocketAddr;
+use std::sync::{Mutex, RwLock};
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+
+/// Hello 握手協商出的結果
+#[derive(Debug, Clone, PartialEq)]
+pub struct Handshake {
+    pub protocol_version: u32,
+    pub features: Vec<Feature>,
+}
+
+/// 每條連線的上下文，會傳給中介層與每個處理器
+/// 登入成功後綁定玩家身分，處理器應以此為準而不是信任 payload 中的 playerId
+pub struct ConnectionContext {
+    handle: ConnectionHandle,
+    peer_addr: SocketAddr,
+    player_id: RwLock<Option<String>>,
+    encoding: RwLock<Encoding>,
+    handshake: RwLock<Option<Handshake>>,
+    after_reply: Mutex<Vec<Outbound>>,
+}
+
+impl ConnectionContext {
+    pub fn new(handle: ConnectionHandle, peer_addr: SocketAddr) -> Self {
+        Self {
+            handle,
+            peer_addr,
+            player_id: RwLock::new(None),
+            encoding: RwLock::new(Encoding::default()),
+            handshake: RwLock::new(None),
+            after_reply: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// 以握手時協商好的編碼建立
+    pub fn with_encoding(self, encoding: Encoding) -> Self {
+        *self.encoding.write().unwrap() = encoding;
+        self
+    }
+
+    pub fn id(&self) -> ConnectionId {
+        self.handle.id()
+    }
+
+    pub fn peer_addr(&self) -> SocketAddr {
+        self.peer_addr
+    }
+
+    /// 此連線的出站端
+    pub fn handle(&self) -> &ConnectionHandle {
+        &self.handle
+    }
+
+    /// 已登入的玩家
+    pub fn player_id(&self) -> Option<String> {
+        self.player_id.read().unwrap().clone()
+    }
+
+    /// 登入成功後綁定玩家身分
+    pub fn authenticate(&self, player_id: &str) {
+        *self.player_id.write().unwrap() = Some(player_id.to_string());
+    }
+
+    /// 目前使用的訊息編碼
+    pub fn encoding(&self) -> Encoding {
+        *self.encoding.read().unwrap()
+    }
+
+    /// 握手結果，尚未送出 Hello 時為 None
+    pub fn handshake(&self) -> Option<Handshake> {
+        self.handshake.read().unwrap().clone()
+    }
+
+    pub fn complete_handshake(&self, handshake: Handshake) {
+        *self.handshake.write().unwrap() = Some(handshake);
+    }
+
+    /// 要求切換編碼；會在目前這則回應送出之後才生效，
+    /// 讓客戶端以原本的編碼收到確認
+    pub fn request_encoding(&self, encoding: Encoding) {
+        if encoding != self.encoding() {
+            self.after_reply.lock().unwrap().push(Outbound::SwitchEncoding(encoding));
+        }
+    }
+
+    /// 送出目前這則回應之後關閉連線
+    pub fn close_after_reply(&self, code: CloseCode, reason: &str) {
+        let frame = CloseFrame {
+            code,
+            reason: reason.to_string().into(),
+        };
+        self.after_reply.lock().unwrap().push(Outbound::Close(Some(frame)));
+    }
+
+    /// 回應送出後呼叫：依序排入處理器要求的後續動作
+    pub async fn flush_after_reply(&self) -> Result<(), PushError> {
+        let pending = std::mem::take(&mut *self.after_reply.lock().unwrap());
+        for item in pending {
+            // 收訊端立即切換，寫入端則排在回應之後
+            if let Outbound::SwitchEncoding(encoding) = &item {
+                *self.encoding.write().unwrap() = *encoding;
+            }
+            self.handle.enqueue(item).await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,114 @@
+// 連線層：每條 WebSocket 連線的出站佇列與連線註冊表
+//
+// `handle_client` 不再直接持有 `write`，而是由獨立的寫入任務消化出站佇列，
+// 因此遊戲迴圈、計時器或其他玩家的動作都能透過註冊表主動推播訊息給客戶端。
+
+use crate::types::codec::Encoding;
+use crate::types::response::WsResponse;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+
+pub mod context;
+pub mod presence;
+pub mod registry;
+pub mod session;
+
+pub use context::{ConnectionContext, Handshake};
+pub use presence::{PresenceMonitor, DEFAULT_RECONNECT_GRACE};
+pub use registry::{ConnectionRegistry, PushError, Replay};
+#[cfg(test)]
+pub use registry::TestClients;
+pub use session::SessionSigner;
+
+/// 連線編號（由註冊表遞增配發）
+pub type ConnectionId = u64;
+
+/// 每條連線出站佇列的容量上限
+pub const OUTBOUND_QUEUE_SIZE: usize = 64;
+
+/// 出站佇列中的項目，由寫入任務依序送出
+#[derive(Debug)]
+pub enum Outbound {
+    Response(WsResponse),                  // 一般回應或推播訊息
+    Ping,                                  // 心跳
+    Close(Option<CloseFrame<'static>>),    // 送出 Close frame 後停止寫入
+    SwitchEncoding(Encoding),              // 之後的訊息改用新的編碼
+}
+
+/// 連線的出站端，可複製給註冊表或其他任務使用
+#[derive(Debug, Clone)]
+pub struct ConnectionHandle {
+    id: ConnectionId,
+    sender: mpsc::Sender<Outbound>,
+}
+
+impl ConnectionHandle {
+    pub fn new(id: ConnectionId, sender: mpsc::Sender<Outbound>) -> Self {
+        Self { id, sender }
+    }
+
+    pub fn id(&self) -> ConnectionId {
+        self.id
+    }
+
+    /// 回覆客戶端自己的請求
+    /// 佇列已滿時會等待寫入任務消化，讓讀取端自然產生背壓
+    pub async fn send(&self, response: WsResponse) -> Result<(), PushError> {
+        self.sender
+            .send(Outbound::Response(response))
+            .await
+            .map_err(|_| PushError::Closed)
+    }
+
+    /// 主動推播，不等待佇列空間
+    /// 佇列已滿代表客戶端消化太慢，直接回報 `QueueFull` 由呼叫端決定如何處理
+    pub fn try_push(&self, response: WsResponse) -> Result<(), PushError> {
+        self.sender
+            .try_send(Outbound::Response(response))
+            .map_err(|err| match err {
+                mpsc::error::TrySendError::Full(_) => PushError::QueueFull,
+                mpsc::error::TrySendError::Closed(_) => PushError::Closed,
+            })
+    }
+
+    /// 排入一次心跳
+    pub async fn ping(&self) -> Result<(), PushError> {
+        self.sender
+            .send(Outbound::Ping)
+            .await
+            .map_err(|_| PushError::Closed)
+    }
+
+    /// 排入任意出站項目
+    pub async fn enqueue(&self, item: Outbound) -> Result<(), PushError> {
+        self.sender.send(item).await.map_err(|_| PushError::Closed)
+    }
+
+    /// 排在佇列中既有訊息之後關閉連線
+    pub async fn close(&self, code: CloseCode, reason: &str) -> Result<(), PushError> {
+        let frame = CloseFrame {
+            code,
+            reason: reason.to_string().into(),
+        };
+        self.sender
+            .send(Outbound::Close(Some(frame)))
+            .await
+            .map_err(|_| PushError::Closed)
+    }
+
+    /// 排入 Close frame，不等待佇列空間
+    /// 佇列已滿時回報 `QueueFull`，呼叫端應另外以期限強制結束連線
+    pub fn try_close(&self, code: CloseCode, reason: &str) -> Result<(), PushError> {
+        let frame = CloseFrame {
+            code,
+            reason: reason.to_string().into(),
+        };
+        self.sender
+            .try_send(Outbound::Close(Some(frame)))
+            .map_err(|err| match err {
+                mpsc::error::TrySendError::Full(_) => PushError::QueueFull,
+                mpsc::error::TrySendError::Closed(_) => PushError::Closed,
+            })
+    }
+}
@@ -0,0 +1,143 @@
+// 玩家在線狀態：斷線後保留座位一段寬限期，逾時未重連則標記為 AFK
+
+use super::{ConnectionContext, ConnectionId, ConnectionRegistry, Replay};
+use crate::player::PlayerManager;
+use crate::types::response::WsResponse;
+use log::*;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 斷線後保留座位的寬限期
+pub const DEFAULT_RECONNECT_GRACE: Duration = Duration::from_secs(60);
+
+pub struct PresenceMonitor {
+    registry: Arc<ConnectionRegistry>,
+    player_manager: Arc<PlayerManager>,
+    grace: Duration,
+}
+
+impl PresenceMonitor {
+    pub fn new(registry: Arc<ConnectionRegistry>, player_manager: Arc<PlayerManager>, grace: Duration) -> Self {
+        Self { registry, player_manager, grace }
+    }
+
+    /// 登入後：將連線已驗證的玩家綁定到此連線；尚未驗證的連線不綁定，回傳 false
+    pub fn attach(&self, ctx: &ConnectionContext) -> bool {
+        let Some(player_id) = ctx.player_id() else {
+            return false;
+        };
+        self.registry.bind(&player_id, None, ctx.handle());
+        self.on_connected(&player_id);
+        true
+    }
+
+    /// 重新連線：將已驗證的玩家綁定到新的連線並取出遺漏的推播
+    pub fn resume(&self, ctx: &ConnectionContext, last_seq: u64) -> Option<Replay> {
+        let player_id = ctx.player_id()?;
+        let replay = self.registry.resume(&player_id, ctx.handle(), last_seq);
+        self.on_connected(&player_id);
+        Some(replay)
+    }
+
+    /// 連線結束：玩家保留座位，寬限期過後仍未重連則標記為 AFK
+    pub fn on_disconnect(self: &Arc<Self>, connection_id: ConnectionId) {
+        for (player_id, since) in self.registry.disconnect(connection_id) {
+            self.notify_game(&player_id, "disconnected");
+
+            let monitor = self.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(monitor.grace).await;
+                if monitor.registry.is_disconnected_since(&player_id, since) {
+                    info!("Player {} did not reconnect within {:?}, marking AFK", player_id, monitor.grace);
+                    monitor.player_manager.set_afk(&player_id, true);
+                    monitor.notify_game(&player_id, "afk");
+                }
+            });
+        }
+    }
+
+    fn on_connected(&self, player_id: &str) {
+        if self.player_manager.set_afk(player_id, false) {
+            info!("Player {} is back, clearing AFK", player_id);
+        }
+        self.notify_game(player_id, "connected");
+    }
+
+    /// 通知同場遊戲的其他玩家
+    fn notify_game(&self, player_id: &str, status: &str) {
+        if let Some(game_id) = self.registry.game_of(player_id) {
+            let message = WsResponse::new("PlayerConnection", json!({
+                "gameId": game_id,
+                "playerId": player_id,
+                "status": status
+            }));
+            self.registry.broadcast_game(&game_id, &message, Some(player_id));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::GameRules;
+
+    fn monitor(grace: Duration) -> Arc<PresenceMonitor> {
+        Arc::new(PresenceMonitor::new(
+            Arc::new(ConnectionRegistry::new()),
+            Arc::new(PlayerManager::new(GameRules::default())),
+            grace,
+        ))
+    }
+
+    fn is_afk(monitor: &PresenceMonitor, player_id: &str) -> bool {
+        monitor.player_manager.get_player(player_id).unwrap().afk
+    }
+
+    fn connect(monitor: &PresenceMonitor, player_id: Option<&str>) -> (ConnectionContext, tokio::sync::mpsc::Receiver<crate::connection::Outbound>) {
+        let (handle, receiver) = monitor.registry.open();
+        let ctx = ConnectionContext::new(handle, "127.0.0.1:9000".parse().unwrap());
+        if let Some(player_id) = player_id {
+            ctx.authenticate(player_id);
+        }
+        (ctx, receiver)
+    }
+
+    #[tokio::test]
+    async fn test_marked_afk_after_grace() {
+        let monitor = monitor(Duration::from_millis(20));
+        let (ctx, _receiver) = connect(&monitor, Some("p1"));
+        assert!(monitor.attach(&ctx));
+
+        monitor.on_disconnect(ctx.id());
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert!(is_afk(&monitor, "p1"));
+
+        // 重新連線後清除 AFK
+        let (ctx, _receiver) = connect(&monitor, Some("p1"));
+        monitor.resume(&ctx, 0).unwrap();
+        assert!(!is_afk(&monitor, "p1"));
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_within_grace_is_not_afk() {
+        let monitor = monitor(Duration::from_millis(40));
+        let (ctx, _receiver) = connect(&monitor, Some("p1"));
+        monitor.attach(&ctx);
+        monitor.on_disconnect(ctx.id());
+
+        let (ctx, _receiver) = connect(&monitor, Some("p1"));
+        monitor.resume(&ctx, 0).unwrap();
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        assert!(!is_afk(&monitor, "p1"));
+    }
+
+    #[test]
+    fn test_unauthenticated_connection_is_not_bound() {
+        let monitor = monitor(DEFAULT_RECONNECT_GRACE);
+        let (ctx, _receiver) = connect(&monitor, None);
+        assert!(!monitor.attach(&ctx));
+        assert!(monitor.resume(&ctx, 0).is_none());
+        assert!(monitor.registry.connections().len() == 1 && !monitor.registry.is_connected("p1"));
+    }
+}
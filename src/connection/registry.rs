@@ -0,0 +1,609 @@
+use super::{ConnectionHandle, ConnectionId, Outbound, OUTBOUND_QUEUE_SIZE};
+use crate::types::response::WsResponse;
+use log::*;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio::task::AbortHandle;
+
+/// 每位玩家保留最近幾則推播，供斷線重連後補送
+pub const REPLAY_BUFFER_SIZE: usize = 128;
+
+/// 推播失敗原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PushError {
+    NotConnected, // 玩家目前沒有綁定任何連線
+    QueueFull,    // 出站佇列已滿（客戶端消化太慢）
+    Closed,       // 連線已關閉
+}
+
+impl fmt::Display for PushError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PushError::NotConnected => write!(f, "player is not connected"),
+            PushError::QueueFull => write!(f, "outbound queue is full"),
+            PushError::Closed => write!(f, "connection is closed"),
+        }
+    }
+}
+
+/// 補送結果
+#[derive(Debug)]
+pub struct Replay {
+    /// lastSeq 之後、仍在緩衝區中的推播（依 seq 排序）
+    pub missed: Vec<WsResponse>,
+    /// 緩衝區是否涵蓋了所有遺漏的推播；為 false 時客戶端應重新同步完整狀態
+    pub complete: bool,
+}
+
+/// 玩家在註冊表中的狀態
+/// 斷線後仍保留（包含座位與推播緩衝），直到重新連線
+struct PlayerEntry {
+    handle: Option<ConnectionHandle>,
+    game_id: Option<String>,
+    next_seq: u64,
+    backlog: VecDeque<WsResponse>,
+    disconnected_at: Option<Instant>,
+}
+
+impl PlayerEntry {
+    fn new() -> Self {
+        Self {
+            handle: None,
+            game_id: None,
+            next_seq: 1,
+            backlog: VecDeque::new(),
+            disconnected_at: None,
+        }
+    }
+
+    /// 為推播配發 seq 並放入緩衝區
+    fn record(&mut self, mut message: WsResponse) -> WsResponse {
+        message.seq = Some(self.next_seq);
+        self.next_seq += 1;
+        if self.backlog.len() >= REPLAY_BUFFER_SIZE {
+            self.backlog.pop_front();
+        }
+        self.backlog.push_back(message.clone());
+        message
+    }
+}
+
+/// 觀戰推播的出口：即時觀戰直接排入出站佇列，有延遲時交給依序送出的延遲任務
+enum Feed {
+    Live(ConnectionHandle),
+    Delayed(mpsc::UnboundedSender<(tokio::time::Instant, WsResponse)>, Duration, AbortHandle),
+}
+
+/// 一條觀戰中的連線
+struct Spectator {
+    game_id: String,
+    /// 只看這位玩家；`None` 時看所有玩家
+    player_id: Option<String>,
+    feed: Feed,
+}
+
+impl Spectator {
+    fn new(handle: &ConnectionHandle, game_id: &str, player_id: Option<&str>, delay: Duration) -> Self {
+        let feed = if delay.is_zero() {
+            Feed::Live(handle.clone())
+        } else {
+            // sender 被丟掉後仍會把已排入的推播送完，除非以 `cancel` 中止
+            let (sender, mut receiver) = mpsc::unbounded_channel::<(tokio::time::Instant, WsResponse)>();
+            let handle = handle.clone();
+            let task = tokio::spawn(async move {
+                while let Some((due, message)) = receiver.recv().await {
+                    tokio::time::sleep_until(due).await;
+                    if handle.try_push(message) == Err(PushError::Closed) {
+                        break;
+                    }
+                }
+            });
+            Feed::Delayed(sender, delay, task.abort_handle())
+        };
+        Self {
+            game_id: game_id.to_string(),
+            player_id: player_id.map(str::to_string),
+            feed,
+        }
+    }
+
+    fn watches(&self, game_id: &str, player_id: &str) -> bool {
+        self.game_id == game_id && self.player_id.as_deref().is_none_or(|watched| watched == player_id)
+    }
+
+    fn send(&self, message: WsResponse) {
+        match &self.feed {
+            Feed::Live(handle) => {
+                if let Err(PushError::QueueFull) = handle.try_push(message) {
+                    warn!("Outbound queue full for spectator {}, dropping push", handle.id());
+                }
+            }
+            Feed::Delayed(sender, delay, _) => {
+                let _ = sender.send((tokio::time::Instant::now() + *delay, message));
+            }
+        }
+    }
+
+    /// 丟掉還在延遲中的推播
+    fn cancel(&self) {
+        if let Feed::Delayed(_, _, task) = &self.feed {
+            task.abort();
+        }
+    }
+}
+
+/// 轉給觀戰者的推播：不帶玩家自己的 seq，並補上 playerId 讓觀戰者分辨是誰的推播
+fn spectator_copy(message: &WsResponse, player_id: &str) -> WsResponse {
+    let mut message = message.clone();
+    message.seq = None;
+    if let Some(Value::Object(payload)) = message.payload.as_mut() {
+        payload.entry("playerId").or_insert_with(|| player_id.into());
+    }
+    message
+}
+
+#[derive(Default)]
+struct RegistryInner {
+    connections: HashMap<ConnectionId, ConnectionHandle>, // 所有開啟中的連線（含未登入）
+    players: HashMap<String, PlayerEntry>,   // playerId -> 玩家狀態
+    games: HashMap<String, HashSet<String>>, // gameId -> playerIds
+    spectators: HashMap<ConnectionId, Spectator>, // 觀戰中的連線
+}
+
+impl RegistryInner {
+    fn leave_game(&mut self, player_id: &str, game_id: &str) {
+        if let Some(members) = self.games.get_mut(game_id) {
+            members.remove(player_id);
+            if members.is_empty() {
+                self.games.remove(game_id);
+            }
+        }
+    }
+}
+
+/// 連線註冊表
+/// 以 playerId 與 gameId 為索引保存每條連線的出站端，供伺服器主動推播
+pub struct ConnectionRegistry {
+    inner: Mutex<RegistryInner>,
+    next_id: AtomicU64,
+    queue_size: usize,
+}
+
+impl ConnectionRegistry {
+    pub fn new() -> Self {
+        Self::with_queue_size(OUTBOUND_QUEUE_SIZE)
+    }
+
+    /// 指定每條連線的出站佇列容量
+    pub fn with_queue_size(queue_size: usize) -> Self {
+        Self {
+            inner: Mutex::new(RegistryInner::default()),
+            next_id: AtomicU64::new(1),
+            queue_size: queue_size.max(1),
+        }
+    }
+
+    /// 為新連線建立出站佇列
+    /// 回傳的 receiver 交給該連線的寫入任務
+    pub fn open(&self) -> (ConnectionHandle, mpsc::Receiver<Outbound>) {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (sender, receiver) = mpsc::channel(self.queue_size);
+        let handle = ConnectionHandle::new(id, sender);
+        self.inner.lock().unwrap().connections.insert(id, handle.clone());
+        (handle, receiver)
+    }
+
+    /// 所有開啟中的連線
+    pub fn connections(&self) -> Vec<ConnectionHandle> {
+        self.inner.lock().unwrap().connections.values().cloned().collect()
+    }
+
+    /// 將玩家綁定到連線（同一玩家只保留最新的連線）
+    /// - `game_id`: 玩家所在的遊戲，為 `None` 時沿用先前的遊戲
+    pub fn bind(&self, player_id: &str, game_id: Option<&str>, handle: &ConnectionHandle) {
+        let mut inner = self.inner.lock().unwrap();
+        let entry = inner
+            .players
+            .entry(player_id.to_string())
+            .or_insert_with(PlayerEntry::new);
+        let previous_game = entry.game_id.clone();
+        let game_id = game_id.map(str::to_string).or(previous_game.clone());
+
+        entry.handle = Some(handle.clone());
+        entry.game_id = game_id.clone();
+        entry.disconnected_at = None;
+
+        if let Some(previous) = previous_game.filter(|g| Some(g) != game_id.as_ref()) {
+            inner.leave_game(player_id, &previous);
+        }
+        if let Some(game_id) = game_id {
+            inner
+                .games
+                .entry(game_id)
+                .or_default()
+                .insert(player_id.to_string());
+        }
+    }
+
+    /// 把玩家加入遊戲（不影響目前的連線），會離開先前所在的遊戲
+    pub fn join_game(&self, player_id: &str, game_id: &str) {
+        let mut inner = self.inner.lock().unwrap();
+        let entry = inner
+            .players
+            .entry(player_id.to_string())
+            .or_insert_with(PlayerEntry::new);
+        let previous = entry.game_id.replace(game_id.to_string());
+        if let Some(previous) = previous.filter(|g| g != game_id) {
+            inner.leave_game(player_id, &previous);
+        }
+        inner
+            .games
+            .entry(game_id.to_string())
+            .or_default()
+            .insert(player_id.to_string());
+    }
+
+    /// 玩家目前是否有連線
+    pub fn is_connected(&self, player_id: &str) -> bool {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .players
+            .get(player_id)
+            .is_some_and(|entry| entry.handle.is_some())
+    }
+
+    /// 連線結束時解除綁定
+    /// 玩家保留在原本的遊戲中，推播會繼續緩衝，回傳斷線的玩家與斷線時間
+    pub fn disconnect(&self, connection_id: ConnectionId) -> Vec<(String, Instant)> {
+        let mut inner = self.inner.lock().unwrap();
+        let now = Instant::now();
+        inner.connections.remove(&connection_id);
+        if let Some(spectator) = inner.spectators.remove(&connection_id) {
+            spectator.cancel();
+        }
+        inner
+            .players
+            .iter_mut()
+            .filter(|(_, entry)| entry.handle.as_ref().map(|h| h.id()) == Some(connection_id))
+            .map(|(player_id, entry)| {
+                entry.handle = None;
+                entry.disconnected_at = Some(now);
+                (player_id.clone(), now)
+            })
+            .collect()
+    }
+
+    /// 玩家是否仍處於同一次斷線中（尚未重新連線）
+    pub fn is_disconnected_since(&self, player_id: &str, since: Instant) -> bool {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .players
+            .get(player_id)
+            .map(|entry| entry.disconnected_at == Some(since))
+            .unwrap_or(false)
+    }
+
+    /// 重新連線：綁定新的連線並取出 `last_seq` 之後遺漏的推播
+    pub fn resume(&self, player_id: &str, handle: &ConnectionHandle, last_seq: u64) -> Replay {
+        self.bind(player_id, None, handle);
+
+        let inner = self.inner.lock().unwrap();
+        let Some(entry) = inner.players.get(player_id) else {
+            return Replay { missed: Vec::new(), complete: true };
+        };
+        let missed: Vec<WsResponse> = entry
+            .backlog
+            .iter()
+            .filter(|message| message.seq.unwrap_or(0) > last_seq)
+            .cloned()
+            .collect();
+        // 最早的緩衝推播若已超過 lastSeq + 1，代表中間有推播被擠出緩衝區
+        let oldest = entry.backlog.front().and_then(|m| m.seq).unwrap_or(entry.next_seq);
+        let complete = oldest <= last_seq + 1 || last_seq + 1 >= entry.next_seq;
+        Replay { missed, complete }
+    }
+
+    /// 查詢玩家所在的遊戲
+    pub fn game_of(&self, player_id: &str) -> Option<String> {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .players
+            .get(player_id)
+            .and_then(|entry| entry.game_id.clone())
+    }
+
+    /// 取得某場遊戲中的所有玩家（包含暫時斷線、仍保留座位的玩家）
+    pub fn players_in_game(&self, game_id: &str) -> Vec<String> {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .games
+            .get(game_id)
+            .map(|members| members.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// 推播訊息給指定玩家
+    /// 訊息一律會配發 seq 並進入補送緩衝區，即使玩家目前斷線（回傳 `NotConnected`）；
+    /// 正在觀戰這位玩家的連線也會收到一份
+    pub fn push(&self, player_id: &str, message: WsResponse) -> Result<(), PushError> {
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(game_id) = inner.players.get(player_id).and_then(|entry| entry.game_id.as_deref()) {
+            for spectator in inner.spectators.values().filter(|s| s.watches(game_id, player_id)) {
+                spectator.send(spectator_copy(&message, player_id));
+            }
+        }
+        Self::deliver(&mut inner, player_id, message)
+    }
+
+    /// 只送給綁定或加入過遊戲的玩家；不認識的 playerId 直接丟棄，不為它保留紀錄
+    fn deliver(inner: &mut RegistryInner, player_id: &str, message: WsResponse) -> Result<(), PushError> {
+        let Some(entry) = inner.players.get_mut(player_id) else {
+            return Err(PushError::NotConnected);
+        };
+        let message = entry.record(message);
+
+        let Some(handle) = entry.handle.as_ref() else {
+            return Err(PushError::NotConnected);
+        };
+        let result = handle.try_push(message);
+        if let Err(PushError::QueueFull) = result {
+            warn!("Outbound queue full for player {}, dropping push", player_id);
+        }
+        result
+    }
+
+    /// 廣播訊息給某場遊戲中的所有玩家
+    /// - `except`: 不需要收到訊息的玩家（例如發送者本人）
+    ///
+    /// 觀戰這場遊戲的連線各收到一份；回傳成功送達的玩家人數
+    pub fn broadcast_game(&self, game_id: &str, message: &WsResponse, except: Option<&str>) -> usize {
+        let members = self.players_in_game(game_id);
+        let mut inner = self.inner.lock().unwrap();
+        for spectator in inner.spectators.values().filter(|s| s.game_id == game_id) {
+            spectator.send(message.clone());
+        }
+        members
+            .iter()
+            .filter(|player_id| Some(player_id.as_str()) != except)
+            .filter(|player_id| Self::deliver(&mut inner, player_id, message.clone()).is_ok())
+            .count()
+    }
+
+    /// 讓連線觀戰某場遊戲（`player_id` 為 `None` 時看所有玩家），取代先前的觀戰；
+    /// 之後的推播延遲 `delay` 才送出
+    pub fn spectate(&self, handle: &ConnectionHandle, game_id: &str, player_id: Option<&str>, delay: Duration) {
+        let spectator = Spectator::new(handle, game_id, player_id, delay);
+        if let Some(previous) = self.inner.lock().unwrap().spectators.insert(handle.id(), spectator) {
+            previous.cancel();
+        }
+    }
+
+    /// 連線正在觀戰的遊戲
+    pub fn spectating(&self, connection_id: ConnectionId) -> Option<String> {
+        let inner = self.inner.lock().unwrap();
+        inner.spectators.get(&connection_id).map(|s| s.game_id.clone())
+    }
+
+    /// 停止觀戰（還在延遲中的推播不再送出），回傳原本觀戰的遊戲
+    pub fn stop_spectating(&self, connection_id: ConnectionId) -> Option<String> {
+        let spectator = self.inner.lock().unwrap().spectators.remove(&connection_id)?;
+        spectator.cancel();
+        Some(spectator.game_id)
+    }
+
+    /// 遊戲結束時解除所有觀戰（已排入延遲佇列的推播仍會送出）
+    pub fn end_spectating(&self, game_id: &str) -> usize {
+        let mut inner = self.inner.lock().unwrap();
+        let before = inner.spectators.len();
+        inner.spectators.retain(|_, s| s.game_id != game_id);
+        before - inner.spectators.len()
+    }
+
+    /// 經由觀戰的出口（含延遲）推播給觀戰者，例如開始觀戰時的快照
+    pub fn push_spectator(&self, connection_id: ConnectionId, message: WsResponse) -> bool {
+        let inner = self.inner.lock().unwrap();
+        inner.spectators.get(&connection_id).map(|s| s.send(message)).is_some()
+    }
+}
+
+/// 測試用：替每位玩家開一條連線並綁定，之後可取出各自收到的推播
+#[cfg(test)]
+pub struct TestClients {
+    receivers: HashMap<String, mpsc::Receiver<Outbound>>,
+    _handles: Vec<ConnectionHandle>,
+}
+
+#[cfg(test)]
+impl TestClients {
+    pub fn connect(registry: &ConnectionRegistry, player_ids: &[&str], game_id: Option<&str>) -> Self {
+        let mut receivers = HashMap::new();
+        let mut handles = Vec::new();
+        for &id in player_ids {
+            let (handle, receiver) = registry.open();
+            registry.bind(id, game_id, &handle);
+            receivers.insert(id.to_string(), receiver);
+            handles.push(handle);
+        }
+        Self { receivers, _handles: handles }
+    }
+
+    /// 取出玩家目前佇列中的所有推播
+    pub fn pushes(&mut self, player_id: &str) -> Vec<WsResponse> {
+        let receiver = self.receivers.get_mut(player_id).unwrap();
+        let mut pushes = Vec::new();
+        while let Ok(Outbound::Response(response)) = receiver.try_recv() {
+            pushes.push(response);
+        }
+        pushes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(text: &str) -> WsResponse {
+        WsResponse::ok(Some(serde_json::json!({ "text": text })))
+    }
+
+    #[test]
+    fn test_push_to_bound_player() {
+        let registry = ConnectionRegistry::new();
+        let (handle, mut receiver) = registry.open();
+        registry.bind("p1", Some("g1"), &handle);
+
+        assert!(registry.push("p1", message("hi")).is_ok());
+        assert!(matches!(receiver.try_recv(), Ok(Outbound::Response(_))));
+        assert_eq!(registry.push("p2", message("hi")), Err(PushError::NotConnected));
+        // 不認識的玩家不會留下紀錄
+        assert!(!registry.inner.lock().unwrap().players.contains_key("p2"));
+    }
+
+    #[test]
+    fn test_bounded_queue_reports_full() {
+        let registry = ConnectionRegistry::with_queue_size(2);
+        let (handle, _receiver) = registry.open();
+        registry.bind("p1", None, &handle);
+
+        assert!(registry.push("p1", message("1")).is_ok());
+        assert!(registry.push("p1", message("2")).is_ok());
+        assert_eq!(registry.push("p1", message("3")), Err(PushError::QueueFull));
+    }
+
+    #[test]
+    fn test_broadcast_game_skips_sender() {
+        let registry = ConnectionRegistry::new();
+        let (h1, mut r1) = registry.open();
+        let (h2, mut r2) = registry.open();
+        registry.bind("p1", Some("g1"), &h1);
+        registry.bind("p2", Some("g1"), &h2);
+
+        let delivered = registry.broadcast_game("g1", &message("hello"), Some("p1"));
+        assert_eq!(delivered, 1);
+        assert!(r1.try_recv().is_err());
+        assert!(r2.try_recv().is_ok());
+    }
+
+    #[test]
+    fn test_disconnect_keeps_seat() {
+        let registry = ConnectionRegistry::new();
+        let (handle, _receiver) = registry.open();
+        registry.bind("p1", Some("g1"), &handle);
+
+        let disconnected = registry.disconnect(handle.id());
+        assert_eq!(disconnected.len(), 1);
+        assert!(registry.is_disconnected_since("p1", disconnected[0].1));
+        assert_eq!(registry.players_in_game("g1"), vec!["p1".to_string()]);
+        assert_eq!(registry.game_of("p1").as_deref(), Some("g1"));
+    }
+
+    #[test]
+    fn test_tracks_open_connections() {
+        let registry = ConnectionRegistry::new();
+        let (h1, _r1) = registry.open();
+        let (_h2, _r2) = registry.open();
+        assert_eq!(registry.connections().len(), 2);
+
+        registry.disconnect(h1.id());
+        let remaining: Vec<ConnectionId> = registry.connections().iter().map(|h| h.id()).collect();
+        assert_eq!(remaining, vec![h1.id() + 1]);
+    }
+
+    #[test]
+    fn test_resume_replays_missed_pushes() {
+        let registry = ConnectionRegistry::new();
+        let (old, _old_receiver) = registry.open();
+        registry.bind("p1", Some("g1"), &old);
+        registry.push("p1", message("1")).unwrap();
+        registry.disconnect(old.id());
+
+        assert_eq!(registry.push("p1", message("2")), Err(PushError::NotConnected));
+        assert_eq!(registry.push("p1", message("3")), Err(PushError::NotConnected));
+
+        let (new, _new_receiver) = registry.open();
+        let replay = registry.resume("p1", &new, 1);
+        let seqs: Vec<u64> = replay.missed.iter().filter_map(|m| m.seq).collect();
+        assert_eq!(seqs, vec![2, 3]);
+        assert!(replay.complete);
+        assert!(registry.push("p1", message("4")).is_ok());
+    }
+
+    fn received(receiver: &mut mpsc::Receiver<Outbound>) -> Vec<serde_json::Value> {
+        std::iter::from_fn(|| match receiver.try_recv() {
+            Ok(Outbound::Response(response)) => Some(response.payload.unwrap()),
+            _ => None,
+        })
+        .collect()
+    }
+
+    #[test]
+    fn test_spectator_receives_watched_player_and_game_pushes() {
+        let registry = ConnectionRegistry::new();
+        let (h1, _r1) = registry.open();
+        let (h2, _r2) = registry.open();
+        let (watcher, mut feed) = registry.open();
+        registry.bind("p1", Some("g1"), &h1);
+        registry.bind("p2", Some("g1"), &h2);
+        registry.spectate(&watcher, "g1", Some("p1"), Duration::ZERO);
+        assert_eq!(registry.spectating(watcher.id()).as_deref(), Some("g1"));
+
+        registry.push("p1", message("p1 only")).unwrap();
+        registry.push("p2", message("p2 only")).unwrap();
+        registry.broadcast_game("g1", &message("everyone"), None);
+        let payloads = received(&mut feed);
+        assert_eq!(payloads.len(), 2);
+        assert_eq!(payloads[0]["playerId"], "p1");
+        assert_eq!(payloads[1]["text"], "everyone");
+
+        // 觀戰者不算在遊戲的玩家中，遊戲結束後不再收到推播
+        assert_eq!(registry.players_in_game("g1").len(), 2);
+        assert_eq!(registry.end_spectating("g1"), 1);
+        registry.push("p1", message("after")).unwrap();
+        assert!(received(&mut feed).is_empty());
+        assert!(registry.spectating(watcher.id()).is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_spectator_delay_keeps_order() {
+        let registry = ConnectionRegistry::new();
+        let (h1, _r1) = registry.open();
+        let (watcher, mut feed) = registry.open();
+        registry.bind("p1", Some("g1"), &h1);
+        registry.spectate(&watcher, "g1", None, Duration::from_secs(30));
+
+        registry.push("p1", message("1")).unwrap();
+        registry.push("p1", message("2")).unwrap();
+        tokio::time::sleep(Duration::from_secs(29)).await;
+        assert!(received(&mut feed).is_empty());
+
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        let texts: Vec<_> = received(&mut feed).iter().map(|p| p["text"].clone()).collect();
+        assert_eq!(texts, vec!["1", "2"]);
+
+        // 主動停止觀戰時，延遲中的推播不再送出
+        registry.push("p1", message("3")).unwrap();
+        assert_eq!(registry.stop_spectating(watcher.id()).as_deref(), Some("g1"));
+        tokio::time::sleep(Duration::from_secs(31)).await;
+        assert!(received(&mut feed).is_empty());
+    }
+
+    #[test]
+    fn test_resume_reports_gap() {
+        let registry = ConnectionRegistry::new();
+        let (handle, _receiver) = registry.open();
+        registry.bind("p1", None, &handle);
+        registry.disconnect(handle.id());
+        for i in 0..(REPLAY_BUFFER_SIZE + 10) {
+            let _ = registry.push("p1", message(&i.to_string()));
+        }
+
+        let replay = registry.resume("p1", &handle, 0);
+        assert!(!replay.complete);
+        assert_eq!(replay.missed.len(), REPLAY_BUFFER_SIZE);
+    }
+}
@@ -0,0 +1,107 @@
+// Session token：登入時簽發，之後可用來驗證身分（重新連線等）
+//
+// 格式為 `{playerId}.{issuedAt}.{signature}`，signature 是 HMAC-SHA256 的十六進位字串。
+
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use sha2::Sha256;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Token 預設有效時間
+pub const DEFAULT_TOKEN_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+pub struct SessionSigner {
+    secret: Vec<u8>,
+    ttl: Duration,
+}
+
+impl SessionSigner {
+    pub fn new(secret: Vec<u8>, ttl: Duration) -> Self {
+        Self { secret, ttl }
+    }
+
+    /// 以隨機金鑰建立（伺服器重啟後舊 token 全部失效）
+    pub fn random() -> Self {
+        let mut secret = vec![0u8; 32];
+        rand::thread_rng().fill_bytes(&mut secret);
+        Self::new(secret, DEFAULT_TOKEN_TTL)
+    }
+
+    /// 指定 token 有效時間
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// 為玩家簽發 token
+    pub fn issue(&self, player_id: &str) -> String {
+        let issued_at = now_secs();
+        let body = format!("{}.{}", player_id, issued_at);
+        format!("{}.{}", body, self.sign(&body))
+    }
+
+    /// 驗證 token，成功時回傳 playerId
+    pub fn verify(&self, token: &str) -> Option<String> {
+        let (body, signature) = token.rsplit_once('.')?;
+        let (player_id, issued_at) = body.rsplit_once('.')?;
+        let issued_at: u64 = issued_at.parse().ok()?;
+
+        let mut mac = self.mac();
+        mac.update(body.as_bytes());
+        mac.verify_slice(&hex::decode(signature).ok()?).ok()?;
+
+        if now_secs().saturating_sub(issued_at) > self.ttl.as_secs() {
+            return None;
+        }
+        Some(player_id.to_string())
+    }
+
+    fn sign(&self, body: &str) -> String {
+        let mut mac = self.mac();
+        mac.update(body.as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    fn mac(&self) -> HmacSha256 {
+        HmacSha256::new_from_slice(&self.secret).expect("HMAC accepts keys of any length")
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_issue_and_verify() {
+        let signer = SessionSigner::random();
+        let token = signer.issue("pAbc123");
+        assert_eq!(signer.verify(&token).as_deref(), Some("pAbc123"));
+    }
+
+    #[test]
+    fn test_tampered_token_is_rejected() {
+        let signer = SessionSigner::random();
+        let token = signer.issue("p1");
+        let forged = token.replacen("p1", "p2", 1);
+        assert_eq!(signer.verify(&forged), None);
+        assert_eq!(SessionSigner::random().verify(&token), None);
+        assert_eq!(signer.verify("garbage"), None);
+    }
+
+    #[test]
+    fn test_expired_token_is_rejected() {
+        let signer = SessionSigner::new(b"secret".to_vec(), Duration::from_secs(60));
+        let body = format!("p1.{}", now_secs() - 120);
+        let token = format!("{}.{}", body, signer.sign(&body));
+        assert_eq!(signer.verify(&token), None);
+    }
+}
@@ -1,2 +1,4 @@
 pub mod game_state_control;
-pub use game_state_control::GameStateControl;
\ No newline at end of file
+pub mod state_sync;
+pub use game_state_control::GameStateControl;
+pub use state_sync::StateSync;
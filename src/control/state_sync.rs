@@ -0,0 +1,264 @@
+// 狀態同步：每位玩家的狀態帶有版本號，變更後只推播差異（JSON Patch）
+//
+// 加入遊戲或 Resync 時才送完整快照；客戶端收到版本不連續的差異時應送出
+// `Resync { sinceVersion }`，伺服器會補送歷史差異，歷史不足時改送快照。
+
+use super::GameStateControl;
+use crate::connection::ConnectionRegistry;
+use crate::game::GameRegistry;
+use crate::player::PlayerManager;
+use crate::types::game_state::GameState;
+use crate::types::patch::{self, PatchOp};
+use crate::types::response::WsResponse;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+/// 每位玩家保留的差異數量
+pub const DELTA_HISTORY_SIZE: usize = 64;
+
+/// 一次變更產生的差異
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StateDelta {
+    pub version: u64,
+    pub ops: Vec<PatchOp>,
+}
+
+/// Resync 的結果：補送差異或完整快照
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Resync {
+    pub version: u64,
+    pub deltas: Vec<StateDelta>,
+    /// 歷史差異不足以補齊時才有值
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<GameState>,
+}
+
+struct Tracked {
+    version: u64,
+    state: Value, // 最後送出的版本
+    history: VecDeque<StateDelta>,
+}
+
+pub struct StateSync {
+    player_manager: Arc<PlayerManager>,
+    registry: Arc<ConnectionRegistry>,
+    /// 取得玩家所在遊戲目前的回合；未設定時回合為 0
+    games: Option<Arc<GameRegistry>>,
+    tracked: Mutex<HashMap<String, Tracked>>,
+}
+
+impl StateSync {
+    pub fn new(player_manager: Arc<PlayerManager>, registry: Arc<ConnectionRegistry>) -> Self {
+        Self {
+            player_manager,
+            registry,
+            games: None,
+            tracked: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 狀態帶上玩家所在遊戲目前的回合
+    pub fn with_games(mut self, games: Arc<GameRegistry>) -> Self {
+        self.games = Some(games);
+        self
+    }
+
+    /// 目前的狀態與版本
+    pub fn current(&self, player_id: &str) -> Option<(u64, GameState)> {
+        let state = self.state_of(player_id)?;
+        let version = self.record(player_id, &state, true);
+        Some((version, state))
+    }
+
+    /// 變更後呼叫：與上次送出的狀態比較，有差異時遞增版本並推播 `StateDelta`
+    pub fn publish(&self, player_id: &str) -> Option<u64> {
+        let state = self.state_of(player_id)?;
+        Some(self.record(player_id, &state, true))
+    }
+
+    /// 加入遊戲時推播完整快照 `StateSnapshot`
+    pub fn send_snapshot(&self, player_id: &str) -> Option<u64> {
+        let state = self.state_of(player_id)?;
+        let version = self.record(player_id, &state, false);
+        let push = WsResponse::new("StateSnapshot", json!({
+            "playerId": player_id,
+            "version": version,
+            "state": state,
+        }));
+        let _ = self.registry.push(player_id, push);
+        Some(version)
+    }
+
+    /// 補送 `since_version` 之後的差異；歷史不足時回傳完整快照
+    pub fn resync(&self, player_id: &str, since_version: u64) -> Option<Resync> {
+        let state = self.state_of(player_id)?;
+        let version = self.record(player_id, &state, true);
+
+        let tracked = self.tracked.lock().unwrap();
+        let history = &tracked.get(player_id)?.history;
+        let oldest = history.front().map(|delta| delta.version).unwrap_or(version + 1);
+        let covered = since_version <= version && (since_version == version || oldest <= since_version + 1);
+
+        Some(if covered {
+            Resync {
+                version,
+                deltas: history.iter().filter(|delta| delta.version > since_version).cloned().collect(),
+                state: None,
+            }
+        } else {
+            Resync { version, deltas: Vec::new(), state: Some(state) }
+        })
+    }
+
+    fn state_of(&self, player_id: &str) -> Option<GameState> {
+        let player = self.player_manager.get_player(player_id)?;
+        let round = self
+            .games
+            .as_ref()
+            .and_then(|games| games.get(&self.registry.game_of(player_id)?))
+            .map_or(0, |game| game.round);
+        Some(GameStateControl::snapshot(&player, round))
+    }
+
+    /// 記錄新狀態並回傳版本；`push_delta` 為 true 時把差異推播給玩家
+    fn record(&self, player_id: &str, state: &GameState, push_delta: bool) -> u64 {
+        let value = serde_json::to_value(state).unwrap_or(Value::Null);
+        let mut tracked = self.tracked.lock().unwrap();
+        let entry = match tracked.get_mut(player_id) {
+            Some(entry) => entry,
+            None => {
+                tracked.insert(player_id.to_string(), Tracked {
+                    version: 0,
+                    state: value,
+                    history: VecDeque::new(),
+                });
+                return 0;
+            }
+        };
+
+        let ops = patch::diff(&entry.state, &value);
+        if ops.is_empty() {
+            return entry.version;
+        }
+
+        entry.version += 1;
+        entry.state = value;
+        let delta = StateDelta { version: entry.version, ops };
+        if entry.history.len() >= DELTA_HISTORY_SIZE {
+            entry.history.pop_front();
+        }
+        entry.history.push_back(delta.clone());
+        let version = entry.version;
+        drop(tracked);
+
+        if push_delta {
+            let push = WsResponse::new("StateDelta", json!({
+                "playerId": player_id,
+                "version": delta.version,
+                "ops": delta.ops,
+            }));
+            let _ = self.registry.push(player_id, push);
+        }
+        version
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::GameRules;
+    use crate::connection::Outbound;
+
+    fn setup() -> (StateSync, Arc<PlayerManager>, tokio::sync::mpsc::Receiver<Outbound>) {
+        let player_manager = Arc::new(PlayerManager::new(GameRules::default()));
+        let registry = Arc::new(ConnectionRegistry::new());
+        let (handle, receiver) = registry.open();
+        registry.bind("p1", None, &handle);
+        (StateSync::new(player_manager.clone(), registry), player_manager, receiver)
+    }
+
+    fn next_push(receiver: &mut tokio::sync::mpsc::Receiver<Outbound>) -> WsResponse {
+        match receiver.try_recv() {
+            Ok(Outbound::Response(response)) => response,
+            other => panic!("unexpected outbound: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_snapshot_then_deltas() {
+        let (sync, player_manager, mut receiver) = setup();
+
+        assert_eq!(sync.send_snapshot("p1"), Some(0));
+        let snapshot = next_push(&mut receiver);
+        assert_eq!(snapshot.type_, "StateSnapshot");
+        assert_eq!(snapshot.payload.unwrap()["state"]["money"], 100);
+
+        player_manager.buy_xp("p1").unwrap();
+        assert_eq!(sync.publish("p1"), Some(1));
+        let delta = next_push(&mut receiver).payload.unwrap();
+        assert_eq!(delta["version"], 1);
+        assert!(delta["ops"]
+            .as_array()
+            .unwrap()
+            .contains(&json!({ "op": "replace", "path": "/money", "value": 96 })));
+
+        // 沒有變更時不推播、版本不變
+        assert_eq!(sync.publish("p1"), Some(1));
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_state_reports_current_round() {
+        let player_manager = Arc::new(PlayerManager::new(GameRules::default()));
+        let registry = Arc::new(ConnectionRegistry::new());
+        let games = Arc::new(GameRegistry::new());
+        let game = games.create(1, vec!["p1".into()]);
+        registry.join_game("p1", &game.id);
+        let sync = StateSync::new(player_manager.clone(), registry).with_games(games.clone());
+        player_manager.create_player("p1");
+
+        assert_eq!(sync.current("p1").unwrap().1.round, 0);
+        games.set_round(&game.id, 3);
+        sync.publish("p1");
+        assert_eq!(sync.current("p1").unwrap().1.round, 3);
+    }
+
+    #[test]
+    fn test_resync_returns_missing_deltas() {
+        let (sync, player_manager, _receiver) = setup();
+        sync.send_snapshot("p1");
+        for _ in 0..3 {
+            player_manager.buy_xp("p1").unwrap();
+            sync.publish("p1");
+        }
+
+        let resync = sync.resync("p1", 1).unwrap();
+        assert_eq!(resync.version, 3);
+        assert_eq!(resync.deltas.iter().map(|d| d.version).collect::<Vec<_>>(), vec![2, 3]);
+        assert!(resync.state.is_none());
+
+        assert!(sync.resync("p1", 3).unwrap().deltas.is_empty());
+        // 客戶端版本比伺服器新，只能重送快照
+        assert!(sync.resync("p1", 9).unwrap().state.is_some());
+    }
+
+    #[test]
+    fn test_resync_falls_back_to_snapshot_when_history_is_gone() {
+        let (sync, player_manager, _receiver) = setup();
+        sync.send_snapshot("p1");
+        for _ in 0..(DELTA_HISTORY_SIZE + 2) {
+            let mut player = player_manager.get_player("p1").unwrap();
+            player.money += 1;
+            player_manager.update_player(player);
+            sync.publish("p1");
+        }
+
+        let resync = sync.resync("p1", 0).unwrap();
+        assert!(resync.deltas.is_empty());
+        assert_eq!(resync.state.unwrap().money, 100 + DELTA_HISTORY_SIZE as u32 + 2);
+    }
+}
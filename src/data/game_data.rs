@@ -5,12 +5,9 @@ pub fn all_chess_pieces() -> Vec<ChessPiece> {
         ChessPiece { name: "Mage".into(), cost: 3, level: 1 },
         ChessPiece { name: "Knight".into(), cost: 2, level: 1 },
         ChessPiece { name: "Assassin".into(), cost: 4, level: 1 },
-		ChessPiece { name: "Mage".into(), cost: 3, level: 1 },
 		ChessPiece { name: "Tank".into(), cost: 3, level: 1 },
-		ChessPiece { name: "Knight".into(), cost: 3, level: 1 },
 		ChessPiece { name: "Priest".into(), cost: 3, level: 1 },
 		ChessPiece { name: "Hunter".into(), cost: 3, level: 1 },
-		ChessPiece { name: "Assassin".into(), cost: 3, level: 1 },
 		ChessPiece { name: "Archer".into(), cost: 3, level: 1 },
 		ChessPiece { name: "Berserker".into(), cost: 3, level: 1 },
 		ChessPiece { name: "Paladin".into(), cost: 3, level: 1 },
@@ -29,5 +26,8 @@ pub fn all_chess_pieces() -> Vec<ChessPiece> {
     ]
 }
 
-pub fn initial_money() -> u32 { 100 }
-pub fn initial_experience() -> u32 { 0 }
\ No newline at end of file
+pub fn initial_experience() -> u32 { 0 }
+/// 依名稱查詢棋子資料
+pub fn find_chess(name: &str) -> Option<ChessPiece> {
+    all_chess_pieces().into_iter().find(|cp| cp.name == name)
+}
@@ -1,2 +1,2 @@
 pub mod game_data;
-pub use game_data::{all_chess_pieces,initial_money,initial_experience};
\ No newline at end of file
+pub use game_data::{all_chess_pieces, find_chess, initial_experience};
\ No newline at end of file
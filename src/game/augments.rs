@@ -0,0 +1,215 @@
+// 強化符文：特定回合所有玩家各從同一階級的符文中三選一
+//
+// 符文以 `Modifiers` 表示對經濟、商店、羈絆與戰鬥屬性的修正；玩家身上所有符文的修正相加後，
+// 由 `PlayerManager`（收入、重新整理、經驗、商店）、羈絆計算與戰鬥模擬各自套用。
+
+use super::pairing::stage_of;
+use crate::chesses::synergies::{tag_name, ALL_TAGS};
+use crate::chesses::units::models::{Attrs, SynergyTag};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// 每次選擇提供的符文數量
+pub const AUGMENT_CHOICES: usize = 3;
+/// 每次選擇可以重抽的次數
+pub const AUGMENT_REROLLS: u32 = 1;
+
+/// 第 2-1、3-2、4-2 回合開始時選擇符文
+pub fn has_augment(round: u32) -> bool {
+    matches!(stage_of(round), (2, 1) | (3, 2) | (4, 2))
+}
+
+/// 符文階級；同一次選擇所有玩家的階級相同
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AugmentTier {
+    Silver,
+    Gold,
+    Prismatic,
+}
+
+impl AugmentTier {
+    pub fn random(rng: &mut impl Rng) -> Self {
+        *[AugmentTier::Silver, AugmentTier::Gold, AugmentTier::Prismatic].choose(rng).unwrap()
+    }
+}
+
+/// 對玩家的各種修正；多個符文的修正相加
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Modifiers {
+    /// 每回合額外收入
+    pub income: i32,
+    /// 每回合免費重新整理商店的次數
+    pub free_refreshes: u32,
+    /// 購買經驗的花費增減
+    pub xp_cost: i32,
+    /// 商店額外格數
+    pub shop_slots: usize,
+    /// 各費用棋子出現機率的加成（百分比，索引為費用 - 1）
+    pub shop_odds: [i32; 5],
+    /// 額外計入的羈絆標籤
+    pub synergies: Vec<SynergyTag>,
+    /// 棋盤上所有單位的屬性加成
+    pub stats: Attrs,
+}
+
+impl Default for Modifiers {
+    fn default() -> Self {
+        Self {
+            income: 0,
+            free_refreshes: 0,
+            xp_cost: 0,
+            shop_slots: 0,
+            shop_odds: [0; 5],
+            synergies: Vec::new(),
+            stats: Attrs::ZERO,
+        }
+    }
+}
+
+impl Modifiers {
+    fn merge(mut self, other: &Modifiers) -> Self {
+        self.income += other.income;
+        self.free_refreshes += other.free_refreshes;
+        self.xp_cost += other.xp_cost;
+        self.shop_slots += other.shop_slots;
+        for (odds, extra) in self.shop_odds.iter_mut().zip(other.shop_odds) {
+            *odds += extra;
+        }
+        self.synergies.extend(&other.synergies);
+        self.stats = self.stats + other.stats;
+        self
+    }
+
+    /// 商店中某個費用的棋子的抽選權重
+    pub fn shop_weight(&self, cost: u32) -> i32 {
+        let index = cost.clamp(1, self.shop_odds.len() as u32) as usize - 1;
+        (100 + self.shop_odds[index]).max(0)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Augment {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub tier: AugmentTier,
+    pub modifiers: Modifiers,
+}
+
+/// 玩家尚未決定的符文選擇
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AugmentOffer {
+    pub tier: AugmentTier,
+    pub options: Vec<String>,
+    /// 剩餘的重抽次數
+    pub rerolls: u32,
+}
+
+fn augment(id: &str, name: &str, description: &str, tier: AugmentTier, modify: impl FnOnce(&mut Modifiers)) -> Augment {
+    let mut modifiers = Modifiers::default();
+    modify(&mut modifiers);
+    Augment { id: id.into(), name: name.into(), description: description.into(), tier, modifiers }
+}
+
+/// 所有強化符文；羈絆徽章與皇冠依標籤產生
+pub fn augment_catalog() -> Vec<Augment> {
+    use AugmentTier::*;
+    let mut augments = vec![
+        augment("allowance", "Allowance", "Gain 1 extra gold every round.", Silver, |m| m.income = 1),
+        augment("rolling_for_days_1", "Rolling For Days I", "Your first shop refresh each round is free.", Silver, |m| m.free_refreshes = 1),
+        augment("study_hall", "Study Hall", "Buying XP costs 1 less gold.", Silver, |m| m.xp_cost = -1),
+        augment("armor_plating", "Armor Plating", "Your units gain 15 armor and magic resist.", Silver, |m| {
+            m.stats.armor = 15;
+            m.stats.magic_resist = 15;
+        }),
+        augment("featherweights", "Featherweights", "Your units gain 10% attack speed.", Silver, |m| m.stats.attack_speed = 0.1),
+        augment("rich_get_richer", "Rich Get Richer", "Gain 3 extra gold every round.", Gold, |m| m.income = 3),
+        augment("rolling_for_days_2", "Rolling For Days II", "Your first two shop refreshes each round are free.", Gold, |m| m.free_refreshes = 2),
+        augment("trade_sector", "Trade Sector", "Your shop has one extra slot.", Gold, |m| m.shop_slots = 1),
+        augment("upward_mobility", "Upward Mobility", "Higher-cost units appear more often in your shop.", Gold, |m| m.shop_odds = [0, 0, 25, 50, 50]),
+        augment("level_up", "Level Up!", "Buying XP costs 2 less gold.", Gold, |m| m.xp_cost = -2),
+        augment("celestial_blessing", "Celestial Blessing", "Your units gain 150 health and 10 attack damage.", Gold, |m| {
+            m.stats.max_hp = 150;
+            m.stats.attack_damage = 10;
+        }),
+        augment("hedge_fund", "Hedge Fund", "Gain 6 extra gold every round.", Prismatic, |m| m.income = 6),
+        augment("golden_ticket", "Golden Ticket", "Three free shop refreshes and 2 extra gold every round.", Prismatic, |m| {
+            m.free_refreshes = 3;
+            m.income = 2;
+        }),
+        augment("big_shop", "Big Shop", "Two extra shop slots and much better odds for expensive units.", Prismatic, |m| {
+            m.shop_slots = 2;
+            m.shop_odds = [0, 0, 50, 100, 100];
+        }),
+        augment("cybernetic_uplink", "Cybernetic Uplink", "Your units gain 300 health and 20 attack damage.", Prismatic, |m| {
+            m.stats.max_hp = 300;
+            m.stats.attack_damage = 20;
+        }),
+    ];
+    for tag in ALL_TAGS {
+        let name = tag_name(tag);
+        augments.push(augment(
+            &format!("{}_crest", name),
+            &format!("{:?} Crest", tag),
+            &format!("Gain 1 extra {:?} for synergies.", tag),
+            Gold,
+            |m| m.synergies = vec![tag],
+        ));
+        augments.push(augment(
+            &format!("{}_crown", name),
+            &format!("{:?} Crown", tag),
+            &format!("Gain 2 extra {:?} for synergies.", tag),
+            Prismatic,
+            |m| m.synergies = vec![tag, tag],
+        ));
+    }
+    augments
+}
+
+pub fn find_augment(id: &str) -> Option<Augment> {
+    augment_catalog().into_iter().find(|augment| augment.id == id)
+}
+
+/// 玩家所有符文的修正總和
+pub fn modifiers_of(augments: &[String]) -> Modifiers {
+    augments
+        .iter()
+        .filter_map(|id| find_augment(id))
+        .fold(Modifiers::default(), |total, augment| total.merge(&augment.modifiers))
+}
+
+/// 從指定階級抽出符文選項，排除玩家已經擁有的符文
+pub fn roll_options(tier: AugmentTier, owned: &[String], rng: &mut impl Rng) -> Vec<String> {
+    let candidates: Vec<Augment> = augment_catalog()
+        .into_iter()
+        .filter(|augment| augment.tier == tier && !owned.contains(&augment.id))
+        .collect();
+    candidates.choose_multiple(rng, AUGMENT_CHOICES).map(|augment| augment.id.clone()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_modifiers_stack() {
+        let owned = vec!["allowance".to_string(), "rich_get_richer".to_string(), "mage_crest".to_string(), "armor_plating".to_string()];
+        let modifiers = modifiers_of(&owned);
+        assert_eq!(modifiers.income, 4);
+        assert_eq!(modifiers.synergies, vec![SynergyTag::Mage]);
+        assert_eq!(modifiers.stats.armor, 15);
+        assert_eq!(modifiers_of(&["big_shop".to_string()]).shop_weight(4), 200);
+
+        let options = roll_options(AugmentTier::Silver, &owned, &mut StdRng::seed_from_u64(1));
+        assert_eq!(options.len(), AUGMENT_CHOICES);
+        assert!(options.iter().all(|id| !owned.contains(id) && find_augment(id).unwrap().tier == AugmentTier::Silver));
+        assert!(has_augment(4) && has_augment(12) && !has_augment(5));
+    }
+}
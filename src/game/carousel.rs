@@ -0,0 +1,236 @@
+// 選秀回合：所有存活玩家從一組共用的「棋子 + 道具」中各挑一組
+//
+// 生命最低的玩家最先挑，每波兩人；時間到還沒挑的玩家由伺服器隨機代選。
+// 棋子在開場時從共用棋子池取出，沒被挑走的在結束時放回。
+
+use super::pairing::stage_of;
+use super::{GameRules, UnitPool};
+use crate::chesses::items::catalog::item_catalog;
+use crate::chesses::items::models::Item;
+use crate::data::all_chess_pieces;
+use crate::types::response::ErrorCode;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+use tokio::sync::futures::Notified;
+use tokio::sync::Notify;
+
+/// 每一波同時挑選的人數
+pub const WAVE_SIZE: usize = 2;
+/// 每一波的挑選時間
+pub const WAVE_SECS: u64 = 5;
+
+/// 第一回合開始前，以及第 2 階段起每階段第 4 回合開始前進行選秀
+pub fn has_carousel(round: u32) -> bool {
+    round == 1 || matches!(stage_of(round), (stage, 4) if stage >= 2)
+}
+
+/// 選秀台上的一組棋子與道具
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CarouselSlot {
+    pub chess: String,
+    pub item: String,
+    pub picked_by: Option<String>,
+}
+
+/// 挑選失敗原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CarouselError {
+    NotActive,
+    NotYourTurn,
+    AlreadyPicked,
+    InvalidSlot,
+    SlotTaken,
+}
+
+impl CarouselError {
+    /// 對應的協定錯誤代碼
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            CarouselError::NotActive => ErrorCode::CarouselNotActive,
+            CarouselError::NotYourTurn | CarouselError::AlreadyPicked => ErrorCode::NotYourTurn,
+            CarouselError::InvalidSlot => ErrorCode::InvalidField,
+            CarouselError::SlotTaken => ErrorCode::SlotTaken,
+        }
+    }
+}
+
+impl fmt::Display for CarouselError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CarouselError::NotActive => write!(f, "no carousel is running in this game"),
+            CarouselError::NotYourTurn => write!(f, "it is not your turn to pick"),
+            CarouselError::AlreadyPicked => write!(f, "you already picked this carousel"),
+            CarouselError::InvalidSlot => write!(f, "slotIndex is out of range"),
+            CarouselError::SlotTaken => write!(f, "this slot was already picked"),
+        }
+    }
+}
+
+/// 一場選秀
+#[derive(Debug, Clone)]
+pub struct Carousel {
+    pub slots: Vec<CarouselSlot>,
+    /// 依生命由低到高分組的挑選順序
+    pub waves: Vec<Vec<String>>,
+    /// 目前開放挑選的波次
+    pub wave: usize,
+}
+
+impl Carousel {
+    /// 擺出比玩家多一組的棋子（依階段限制費用），各配一個隨機基礎道具；`players` 為 (playerId, 生命)
+    pub fn new(round: u32, players: &[(String, i32)], pool: &UnitPool, rules: &GameRules, rng: &mut impl Rng) -> Self {
+        let (stage, _) = stage_of(round);
+        let components: Vec<Item> = item_catalog().into_iter().filter(Item::is_component).collect();
+        let mut slots = Vec::new();
+        for _ in 0..=players.len() {
+            let candidates: Vec<_> = all_chess_pieces()
+                .into_iter()
+                .filter(|piece| piece.cost <= stage + 2 && rules.allows(&piece.name) && pool.remaining(&piece.name) > 0)
+                .collect();
+            let (Some(piece), Some(item)) = (candidates.choose(rng), components.choose(rng)) else {
+                break;
+            };
+            if pool.take(&piece.name, 1) {
+                slots.push(CarouselSlot { chess: piece.name.clone(), item: item.id.clone(), picked_by: None });
+            }
+        }
+
+        // 同生命的玩家先隨機排序，再依生命由低到高穩定排序
+        let mut order = players.to_vec();
+        order.shuffle(rng);
+        order.sort_by_key(|(_, hp)| *hp);
+        let waves = order
+            .chunks(WAVE_SIZE)
+            .map(|wave| wave.iter().map(|(id, _)| id.clone()).collect())
+            .collect();
+        Self { slots, waves, wave: 0 }
+    }
+
+    pub fn current_wave(&self) -> &[String] {
+        self.waves.get(self.wave).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn has_picked(&self, player_id: &str) -> bool {
+        self.slots.iter().any(|slot| slot.picked_by.as_deref() == Some(player_id))
+    }
+
+    /// 目前這一波的玩家都已挑選
+    pub fn wave_done(&self) -> bool {
+        self.current_wave().iter().all(|p| self.has_picked(p))
+    }
+
+    pub fn pick(&mut self, player_id: &str, slot_index: usize) -> Result<CarouselSlot, CarouselError> {
+        if self.has_picked(player_id) {
+            return Err(CarouselError::AlreadyPicked);
+        }
+        if !self.current_wave().iter().any(|p| p == player_id) {
+            return Err(CarouselError::NotYourTurn);
+        }
+        let slot = self.slots.get_mut(slot_index).ok_or(CarouselError::InvalidSlot)?;
+        if slot.picked_by.is_some() {
+            return Err(CarouselError::SlotTaken);
+        }
+        slot.picked_by = Some(player_id.to_string());
+        Ok(slot.clone())
+    }
+
+    /// 隨機一個還沒被挑走的格子
+    pub fn random_free_slot(&self, rng: &mut impl Rng) -> Option<usize> {
+        let free: Vec<usize> = (0..self.slots.len()).filter(|&i| self.slots[i].picked_by.is_none()).collect();
+        free.choose(rng).copied()
+    }
+
+    /// 沒被挑走的棋子
+    pub fn leftovers(&self) -> impl Iterator<Item = &str> {
+        self.slots.iter().filter(|slot| slot.picked_by.is_none()).map(|slot| slot.chess.as_str())
+    }
+}
+
+/// 進行中的選秀（gameId -> 選秀），讓 `PickCarousel` 與回合流程共用
+#[derive(Default)]
+pub struct CarouselRegistry {
+    carousels: Mutex<HashMap<String, Carousel>>,
+    /// 有人挑選時通知回合流程，整波挑完就提早進入下一波
+    picked: Notify,
+}
+
+impl CarouselRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn open(&self, game_id: &str, carousel: Carousel) {
+        self.carousels.lock().unwrap().insert(game_id.to_string(), carousel);
+    }
+
+    pub fn close(&self, game_id: &str) -> Option<Carousel> {
+        self.carousels.lock().unwrap().remove(game_id)
+    }
+
+    /// 對進行中的選秀執行操作
+    pub fn with<R>(&self, game_id: &str, f: impl FnOnce(&mut Carousel) -> R) -> Result<R, CarouselError> {
+        let mut carousels = self.carousels.lock().unwrap();
+        let carousel = carousels.get_mut(game_id).ok_or(CarouselError::NotActive)?;
+        Ok(f(carousel))
+    }
+
+    pub fn pick(&self, game_id: &str, player_id: &str, slot_index: usize) -> Result<CarouselSlot, CarouselError> {
+        let slot = self.with(game_id, |carousel| carousel.pick(player_id, slot_index))??;
+        self.picked.notify_waiters();
+        Ok(slot)
+    }
+
+    /// 下一次挑選的通知；先取得再檢查狀態，才不會漏掉檢查之後的挑選
+    pub fn picked(&self) -> Notified<'_> {
+        self.picked.notified()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn carousel(players: &[(&str, i32)]) -> (Carousel, UnitPool) {
+        let rules = GameRules::default();
+        let pool = UnitPool::new(&rules);
+        let players: Vec<(String, i32)> = players.iter().map(|(id, hp)| (id.to_string(), *hp)).collect();
+        let carousel = Carousel::new(4, &players, &pool, &rules, &mut StdRng::seed_from_u64(5));
+        (carousel, pool)
+    }
+
+    #[test]
+    fn test_schedule() {
+        assert!(has_carousel(1));
+        assert!(!has_carousel(2));
+        assert!(has_carousel(7));
+        assert!(has_carousel(14));
+        assert!(!has_carousel(4));
+    }
+
+    #[test]
+    fn test_lowest_hp_picks_first_in_waves() {
+        let (mut carousel, pool) = carousel(&[("a", 80), ("b", 20), ("c", 50), ("d", 100), ("e", 10)]);
+        assert_eq!(carousel.slots.len(), 6);
+        assert_eq!(carousel.waves, vec![vec!["e", "b"], vec!["c", "a"], vec!["d"]]);
+        let taken: u32 = carousel.slots.iter().map(|slot| (slot.chess == "Knight") as u32).sum();
+        assert_eq!(pool.remaining("Knight"), 22 - taken);
+
+        assert_eq!(carousel.pick("a", 0).unwrap_err(), CarouselError::NotYourTurn);
+        assert_eq!(carousel.pick("e", 9).unwrap_err(), CarouselError::InvalidSlot);
+        assert_eq!(carousel.pick("e", 0).unwrap().picked_by.as_deref(), Some("e"));
+        assert_eq!(carousel.pick("e", 1).unwrap_err(), CarouselError::AlreadyPicked);
+        assert_eq!(carousel.pick("b", 0).unwrap_err(), CarouselError::SlotTaken);
+        assert!(!carousel.wave_done());
+        let free = carousel.random_free_slot(&mut StdRng::seed_from_u64(1)).unwrap();
+        carousel.pick("b", free).unwrap();
+        assert!(carousel.wave_done());
+        assert_eq!(carousel.leftovers().count(), 4);
+    }
+}
@@ -0,0 +1,196 @@
+// 野怪回合：依階段排定的野怪棋盤與掉落表
+//
+// 野怪沿用 `ChessTemplate` 與 `Attrs`，與玩家棋盤使用同一套戰鬥模擬。
+// 擊敗野怪從掉落表隨機得到一組戰利品；落敗不扣生命，但仍會得到安慰獎。
+
+use super::pairing::stage_of;
+use crate::chesses::combat::Combatant;
+use crate::chesses::units::models::{Attrs, ChessTemplate, StarLevel};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::Serialize;
+
+/// 掉落表中的一項
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Loot {
+    Gold(i32),
+    /// 道具 id（基礎道具）
+    Item(&'static str),
+    /// 指定費用的隨機棋子，從共用棋子池取出放到備戰區
+    Unit { cost: u32 },
+}
+
+/// 實際發給玩家的戰利品
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum LootDrop {
+    Gold { amount: i32 },
+    Item { item_id: String, item: String },
+    Unit { unit_id: String, chess: String },
+}
+
+/// 一組野怪：名稱、單位（模板名稱、屬性、己方半場位置）與掉落表
+pub struct CreepWave {
+    pub name: &'static str,
+    units: &'static [(&'static str, CreepStats, [u32; 2])],
+    /// 勝利時從中隨機選一組
+    loot: &'static [&'static [Loot]],
+    /// 落敗時的安慰獎
+    consolation: &'static [Loot],
+}
+
+/// 野怪屬性：(生命, 護甲, 魔抗, 攻擊, 攻速, 射程)
+type CreepStats = (i32, i32, i32, i32, f32, i32);
+
+const MINION: CreepStats = (350, 10, 10, 35, 0.6, 1);
+const CASTER_MINION: CreepStats = (250, 5, 10, 40, 0.6, 3);
+const KRUG: CreepStats = (900, 40, 20, 60, 0.5, 1);
+const WOLF: CreepStats = (750, 25, 25, 80, 0.8, 1);
+const RAPTOR: CreepStats = (800, 30, 30, 90, 0.75, 1);
+const DRAGON: CreepStats = (3200, 60, 60, 160, 0.7, 2);
+
+const WAVES: &[CreepWave] = &[
+    CreepWave {
+        name: "Minions",
+        units: &[("Minion", MINION, [2, 3]), ("Minion", MINION, [4, 3])],
+        loot: &[&[Loot::Gold(2)], &[Loot::Unit { cost: 2 }], &[Loot::Item("sword")], &[Loot::Item("vest")]],
+        consolation: &[Loot::Gold(1)],
+    },
+    CreepWave {
+        name: "Minions",
+        units: &[("Minion", MINION, [2, 3]), ("Minion", MINION, [4, 3]), ("Caster Minion", CASTER_MINION, [3, 1])],
+        loot: &[&[Loot::Gold(3)], &[Loot::Unit { cost: 3 }], &[Loot::Item("bow")], &[Loot::Item("rod")]],
+        consolation: &[Loot::Gold(1)],
+    },
+    CreepWave {
+        name: "Minions",
+        units: &[
+            ("Minion", MINION, [2, 3]),
+            ("Minion", MINION, [4, 3]),
+            ("Caster Minion", CASTER_MINION, [2, 1]),
+            ("Caster Minion", CASTER_MINION, [4, 1]),
+        ],
+        loot: &[&[Loot::Gold(2), Loot::Item("tear")], &[Loot::Item("belt")], &[Loot::Item("cloak")], &[Loot::Unit { cost: 3 }, Loot::Gold(1)]],
+        consolation: &[Loot::Gold(2)],
+    },
+    CreepWave {
+        name: "Krugs",
+        units: &[("Krug", KRUG, [2, 3]), ("Krug", KRUG, [3, 3]), ("Krug", KRUG, [4, 3])],
+        loot: &[&[Loot::Item("glove"), Loot::Gold(2)], &[Loot::Unit { cost: 3 }, Loot::Unit { cost: 3 }], &[Loot::Gold(6)]],
+        consolation: &[Loot::Gold(2)],
+    },
+    CreepWave {
+        name: "Wolves",
+        units: &[("Wolf", WOLF, [1, 3]), ("Wolf", WOLF, [3, 3]), ("Wolf", WOLF, [5, 3]), ("Alpha Wolf", WOLF, [3, 2])],
+        loot: &[&[Loot::Item("sword"), Loot::Item("bow")], &[Loot::Unit { cost: 4 }, Loot::Gold(3)], &[Loot::Gold(8)]],
+        consolation: &[Loot::Gold(3)],
+    },
+    CreepWave {
+        name: "Raptors",
+        units: &[
+            ("Raptor", RAPTOR, [1, 3]),
+            ("Raptor", RAPTOR, [2, 2]),
+            ("Raptor", RAPTOR, [4, 2]),
+            ("Raptor", RAPTOR, [5, 3]),
+            ("Crimson Raptor", RAPTOR, [3, 1]),
+        ],
+        loot: &[&[Loot::Item("rod"), Loot::Item("tear")], &[Loot::Unit { cost: 4 }, Loot::Unit { cost: 4 }], &[Loot::Gold(10)]],
+        consolation: &[Loot::Gold(3)],
+    },
+    CreepWave {
+        name: "Dragon",
+        units: &[("Dragon", DRAGON, [3, 3])],
+        loot: &[&[Loot::Item("belt"), Loot::Item("cloak"), Loot::Gold(4)], &[Loot::Unit { cost: 4 }, Loot::Item("glove")]],
+        consolation: &[Loot::Gold(4)],
+    },
+];
+
+/// 回合對應的野怪：第一階段依序三組小兵，之後每階段最後一回合依階段換成更強的野怪
+pub fn creep_wave(round: u32) -> &'static CreepWave {
+    let index = match stage_of(round) {
+        (1, stage_round) => stage_round as usize - 1,
+        (stage, _) => 1 + stage as usize,
+    };
+    &WAVES[index.min(WAVES.len() - 1)]
+}
+
+impl CreepWave {
+    /// 野怪的戰鬥單位（沒有技能與羈絆）
+    pub fn combatants(&self) -> Vec<Combatant> {
+        self.units
+            .iter()
+            .enumerate()
+            .map(|(index, &(name, (max_hp, armor, magic_resist, attack_damage, attack_speed, attack_range), position))| {
+                let template = ChessTemplate {
+                    id: format!("creep_{}", name.to_lowercase().replace(' ', "_")),
+                    chess: name.into(),
+                    level: StarLevel::One,
+                    description: None,
+                    base_attrs: Attrs {
+                        max_hp,
+                        max_mp: 0,
+                        armor,
+                        magic_resist,
+                        attack_damage,
+                        ability_power: 0,
+                        attack_speed,
+                        attack_range,
+                    },
+                    skills: Vec::new(),
+                    synergies: Vec::new(),
+                };
+                Combatant { id: format!("c{}", index + 1), template, star: 1, items: Vec::new(), bonus: Attrs::ZERO, position }
+            })
+            .collect()
+    }
+
+    /// 勝利時擲一次掉落表
+    pub fn roll_loot(&self, rng: &mut impl Rng) -> Vec<Loot> {
+        self.loot.choose(rng).map(|loot| loot.to_vec()).unwrap_or_default()
+    }
+
+    pub fn consolation(&self) -> Vec<Loot> {
+        self.consolation.to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chesses::combat::{simulate, Side};
+    use crate::chesses::items::catalog::find_item;
+    use crate::data::all_chess_pieces;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_schedule_and_loot_tables() {
+        assert_eq!(creep_wave(1).combatants().len(), 2);
+        assert_eq!(creep_wave(3).combatants().len(), 4);
+        assert_eq!(creep_wave(10).name, "Krugs");
+        assert_eq!(creep_wave(17).name, "Wolves");
+        assert_eq!(creep_wave(100).name, "Dragon");
+
+        let costs: Vec<u32> = all_chess_pieces().iter().map(|piece| piece.cost).collect();
+        for wave in WAVES {
+            for loot in wave.loot.iter().flat_map(|set| set.iter()).chain(wave.consolation) {
+                match loot {
+                    Loot::Item(item) => assert!(find_item(item).is_some_and(|item| item.is_component()), "{} is not a component", item),
+                    Loot::Unit { cost } => assert!(costs.contains(cost), "no unit costs {}", cost),
+                    Loot::Gold(amount) => assert!(*amount > 0),
+                }
+            }
+        }
+        assert!(!creep_wave(1).roll_loot(&mut StdRng::seed_from_u64(1)).is_empty());
+    }
+
+    #[test]
+    fn test_creeps_fight_with_the_same_engine() {
+        let empty = simulate(Vec::new(), creep_wave(1).combatants(), 1);
+        assert_eq!(empty.winner, Some(Side::Away));
+
+        let knight = crate::chesses::units::catalog::find_template("Knight").unwrap();
+        let home = vec![Combatant { id: "u001".into(), template: knight, star: 2, items: Vec::new(), bonus: Attrs::ZERO, position: [3, 3] }];
+        assert_eq!(simulate(home, creep_wave(1).combatants(), 1).winner, Some(Side::Home));
+    }
+}
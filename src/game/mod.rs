@@ -0,0 +1,314 @@
+pub mod augments;
+pub mod carousel;
+pub mod creeps;
+pub mod pairing;
+pub mod pool;
+pub mod rules;
+pub mod rounds;
+pub mod starter;
+
+pub use pool::UnitPool;
+pub use rules::{GameRules, RulesPatch};
+pub use rounds::RoundLoop;
+pub use starter::GameStarter;
+
+use crate::types::protocol::server_time_millis;
+use rand::{distributions::Alphanumeric, Rng};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// 遊戲進行狀態
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum GameStatus {
+    Waiting,
+    InProgress,
+    Finished,
+}
+
+/// 對外公開的遊戲資訊
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GameInfo {
+    pub id: String,
+    pub seed: i64,
+    pub players: Vec<String>,
+    pub status: GameStatus,
+    pub round: u32,
+    pub rules: GameRules,
+    /// 已淘汰的玩家與名次，遊戲結束時包含所有玩家（第 1 名在前）
+    pub standings: Vec<Standing>,
+    /// Unix epoch 毫秒
+    pub created_at: u64,
+}
+
+/// 玩家的最終名次
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Standing {
+    pub player_id: String,
+    pub placement: u32,
+}
+
+/// 重播紀錄中的一筆事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayEvent {
+    pub at: u64,
+    pub kind: String,
+    pub payload: Value,
+}
+
+/// 可下載的遊戲重播：種子加上依時間排列的事件
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayLog {
+    pub game_id: String,
+    pub seed: i64,
+    pub events: Vec<ReplayEvent>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct GameEntry {
+    info: GameInfo,
+    events: Vec<ReplayEvent>,
+}
+
+/// 進行中與已結束的遊戲，WebSocket handler 與 HTTP API 共用
+pub struct GameRegistry {
+    games: Mutex<HashMap<String, GameEntry>>,
+    /// 沒有指定規則時使用（伺服器設定）
+    default_rules: GameRules,
+}
+
+impl GameRegistry {
+    pub fn new() -> Self {
+        Self::with_default_rules(GameRules::default())
+    }
+
+    pub fn with_default_rules(default_rules: GameRules) -> Self {
+        Self {
+            games: Mutex::new(HashMap::new()),
+            default_rules,
+        }
+    }
+
+    /// 伺服器設定的預設規則，自訂房間以此為起點
+    pub fn default_rules(&self) -> &GameRules {
+        &self.default_rules
+    }
+
+    /// 以預設規則建立新遊戲，`players` 為一開始就加入的玩家
+    pub fn create(&self, seed: i64, players: Vec<String>) -> GameInfo {
+        self.create_with_rules(seed, players, self.default_rules.clone())
+    }
+
+    /// 以自訂規則建立新遊戲
+    pub fn create_with_rules(&self, seed: i64, players: Vec<String>, rules: GameRules) -> GameInfo {
+        let mut games = self.games.lock().unwrap();
+        let id = loop {
+            let suffix: String = rand::thread_rng()
+                .sample_iter(&Alphanumeric)
+                .take(8)
+                .map(char::from)
+                .collect();
+            let id = format!("g{}", suffix);
+            if !games.contains_key(&id) {
+                break id;
+            }
+        };
+        let info = GameInfo {
+            id: id.clone(),
+            seed,
+            players,
+            status: GameStatus::Waiting,
+            round: 0,
+            rules,
+            standings: Vec::new(),
+            created_at: server_time_millis(),
+        };
+        let created = ReplayEvent {
+            at: info.created_at,
+            kind: "GameCreated".into(),
+            payload: serde_json::json!({ "seed": seed, "players": info.players }),
+        };
+        games.insert(id, GameEntry { info: info.clone(), events: vec![created] });
+        info
+    }
+
+    pub fn get(&self, game_id: &str) -> Option<GameInfo> {
+        self.games.lock().unwrap().get(game_id).map(|entry| entry.info.clone())
+    }
+
+    /// 遊戲是否正在進行
+    pub fn is_in_progress(&self, game_id: &str) -> bool {
+        self.get(game_id).is_some_and(|game| game.status == GameStatus::InProgress)
+    }
+
+    /// 開始遊戲（配對確認完成）
+    pub fn start(&self, game_id: &str) -> Option<GameInfo> {
+        let mut games = self.games.lock().unwrap();
+        let entry = games.get_mut(game_id)?;
+        entry.info.status = GameStatus::InProgress;
+        entry.events.push(ReplayEvent {
+            at: server_time_millis(),
+            kind: "GameStarted".into(),
+            payload: serde_json::json!({ "players": entry.info.players }),
+        });
+        Some(entry.info.clone())
+    }
+
+    /// 更新目前回合
+    pub fn set_round(&self, game_id: &str, round: u32) {
+        if let Some(entry) = self.games.lock().unwrap().get_mut(game_id) {
+            entry.info.round = round;
+        }
+    }
+
+    /// 記錄淘汰玩家的名次
+    pub fn add_standing(&self, game_id: &str, standing: Standing) {
+        if let Some(entry) = self.games.lock().unwrap().get_mut(game_id) {
+            entry.info.standings.push(standing);
+            entry.info.standings.sort_by_key(|s| s.placement);
+        }
+    }
+
+    /// 結束遊戲並記錄 `GameOver` 事件
+    pub fn finish(&self, game_id: &str) -> Option<GameInfo> {
+        let mut games = self.games.lock().unwrap();
+        let entry = games.get_mut(game_id)?;
+        entry.info.status = GameStatus::Finished;
+        entry.events.push(ReplayEvent {
+            at: server_time_millis(),
+            kind: "GameOver".into(),
+            payload: serde_json::json!({ "standings": entry.info.standings }),
+        });
+        Some(entry.info.clone())
+    }
+
+    /// 移除尚未開始就取消的遊戲
+    pub fn remove(&self, game_id: &str) -> Option<GameInfo> {
+        self.games.lock().unwrap().remove(game_id).map(|entry| entry.info)
+    }
+
+    /// 把已開始的遊戲（含回合、名次與重播紀錄）寫入檔案，回傳寫入的遊戲數
+    /// 尚未開始的遊戲屬於配對或房間流程，不會保存
+    pub fn save(&self, path: &Path) -> io::Result<usize> {
+        let games = self.games.lock().unwrap();
+        let mut entries: Vec<&GameEntry> = games.values().filter(|entry| entry.info.status != GameStatus::Waiting).collect();
+        entries.sort_by(|a, b| a.info.created_at.cmp(&b.info.created_at).then_with(|| a.info.id.cmp(&b.info.id)));
+
+        let json = serde_json::to_vec_pretty(&entries)?;
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, json)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(entries.len())
+    }
+
+    /// 啟動時載入先前保存的遊戲，回傳需要接續回合流程的進行中遊戲
+    /// 檔案不存在時視為沒有資料
+    pub fn load(&self, path: &Path) -> io::Result<Vec<GameInfo>> {
+        let json = match std::fs::read(path) {
+            Ok(json) => json,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        let saved: Vec<GameEntry> = serde_json::from_slice(&json)?;
+
+        let mut games = self.games.lock().unwrap();
+        let mut in_progress = Vec::new();
+        for entry in saved {
+            if entry.info.status == GameStatus::InProgress {
+                in_progress.push(entry.info.clone());
+            }
+            games.insert(entry.info.id.clone(), entry);
+        }
+        Ok(in_progress)
+    }
+
+    /// 所有遊戲，依建立時間排序
+    pub fn list(&self) -> Vec<GameInfo> {
+        let mut games: Vec<GameInfo> = self.games.lock().unwrap().values().map(|entry| entry.info.clone()).collect();
+        games.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+        games
+    }
+
+    /// 在重播紀錄中追加一筆事件
+    pub fn record(&self, game_id: &str, kind: &str, payload: Value) {
+        if let Some(entry) = self.games.lock().unwrap().get_mut(game_id) {
+            entry.events.push(ReplayEvent {
+                at: server_time_millis(),
+                kind: kind.to_string(),
+                payload,
+            });
+        }
+    }
+
+    pub fn replay(&self, game_id: &str) -> Option<ReplayLog> {
+        self.games.lock().unwrap().get(game_id).map(|entry| ReplayLog {
+            game_id: entry.info.id.clone(),
+            seed: entry.info.seed,
+            events: entry.events.clone(),
+        })
+    }
+}
+
+impl Default for GameRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_list_and_replay() {
+        let games = GameRegistry::new();
+        let first = games.create(7, vec!["p1".into()]);
+        let second = games.create(8, vec!["p2".into()]);
+        assert_ne!(first.id, second.id);
+        assert_eq!(games.get(&first.id).unwrap().players, vec!["p1"]);
+        assert_eq!(games.list().len(), 2);
+
+        games.record(&first.id, "BattleResult", serde_json::json!({ "winner": "p1" }));
+        let replay = games.replay(&first.id).unwrap();
+        assert_eq!(replay.seed, 7);
+        let kinds: Vec<&str> = replay.events.iter().map(|e| e.kind.as_str()).collect();
+        assert_eq!(kinds, vec!["GameCreated", "BattleResult"]);
+        assert!(games.replay("missing").is_none());
+
+        assert_eq!(games.start(&second.id).unwrap().status, GameStatus::InProgress);
+        assert!(games.remove(&second.id).is_some());
+        assert_eq!(games.list().len(), 1);
+    }
+
+    #[test]
+    fn test_save_and_load_resumes_games_in_progress() {
+        let games = GameRegistry::new();
+        let waiting = games.create(1, vec!["p1".into()]);
+        let running = games.create(2, vec!["p2".into(), "p3".into()]);
+        games.start(&running.id);
+        games.set_round(&running.id, 4);
+        games.add_standing(&running.id, Standing { player_id: "p3".into(), placement: 2 });
+
+        let path = std::env::temp_dir().join(format!("chess_fight_games_{}.json", std::process::id()));
+        assert_eq!(games.save(&path).unwrap(), 1);
+
+        let restored = GameRegistry::new();
+        let resumed = restored.load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        // 尚未開始的遊戲不保存；進行中的遊戲保留回合、名次與重播紀錄
+        assert!(restored.get(&waiting.id).is_none());
+        assert_eq!(resumed.len(), 1);
+        assert_eq!((resumed[0].id.clone(), resumed[0].round), (running.id.clone(), 4));
+        assert_eq!(resumed[0].standings, vec![Standing { player_id: "p3".into(), placement: 2 }]);
+        assert_eq!(restored.replay(&running.id).unwrap().events.len(), 2);
+    }
+}
@@ -0,0 +1,210 @@
+// 每回合的對手配對
+//
+// 回合依階段編號：第一階段 3 回合，之後每階段 7 回合（例如第 4 回合為 2-1）。
+// 第一階段全部與每個階段的最後一回合為野怪（PvE）回合，其餘回合存活玩家兩兩對戰。
+// 配對時盡量避開最近交手過的對手；存活人數為奇數時，其中一人對上隨機一位其他玩家的幽靈棋盤。
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+
+/// 第一階段的回合數
+const FIRST_STAGE_ROUNDS: u32 = 3;
+/// 第二階段起每個階段的回合數
+const STAGE_ROUNDS: u32 = 7;
+/// 每位玩家記住最近幾個對手
+const HISTORY_LEN: usize = 3;
+/// 對戰紀錄中代表幽靈棋盤的對手
+const GHOST: &str = "#ghost";
+
+/// 回合類型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RoundKind {
+    Pvp,
+    Creep,
+}
+
+/// 回合所屬的階段與階段內的回合（皆從 1 起算）
+pub fn stage_of(round: u32) -> (u32, u32) {
+    if round <= FIRST_STAGE_ROUNDS {
+        (1, round.max(1))
+    } else {
+        let index = round - FIRST_STAGE_ROUNDS - 1;
+        (2 + index / STAGE_ROUNDS, 1 + index % STAGE_ROUNDS)
+    }
+}
+
+pub fn round_kind(round: u32) -> RoundKind {
+    match stage_of(round) {
+        (1, _) => RoundKind::Creep,
+        (_, STAGE_ROUNDS) => RoundKind::Creep,
+        _ => RoundKind::Pvp,
+    }
+}
+
+/// 玩家這回合的對手
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum Opponent {
+    Player { player_id: String },
+    /// 另一位玩家棋盤的複本，對戰結果不影響該玩家
+    Ghost { ghost_of: String },
+    Creep,
+}
+
+/// 記錄對戰歷史並產生每回合的配對，每場遊戲一個
+#[derive(Debug, Default)]
+pub struct Pairer {
+    /// 玩家 -> 最近的對手（最新的在前）
+    history: HashMap<String, VecDeque<String>>,
+}
+
+impl Pairer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 為存活玩家配對並記入歷史；少於 2 人時沒有對戰
+    pub fn pair(&mut self, living: &[String], rng: &mut impl Rng) -> HashMap<String, Opponent> {
+        if living.len() < 2 {
+            return HashMap::new();
+        }
+        let mut slots: Vec<&str> = living.iter().map(String::as_str).collect();
+        if slots.len() % 2 == 1 {
+            slots.push(GHOST);
+        }
+        // 先打亂順序，代價相同的配對之間隨機選擇
+        slots.shuffle(rng);
+
+        let mut best = None;
+        self.search(&mut slots, &mut Vec::new(), 0, &mut best);
+        let (_, pairs) = best.expect("an even number of slots always has a pairing");
+
+        let mut opponents = HashMap::new();
+        for (a, b) in pairs {
+            self.remember(&a, &b);
+            self.remember(&b, &a);
+            if b == GHOST || a == GHOST {
+                let player = if a == GHOST { b } else { a };
+                let others: Vec<&String> = living.iter().filter(|p| **p != player).collect();
+                let ghost_of = (*others.choose(rng).expect("at least one other player")).clone();
+                opponents.insert(player, Opponent::Ghost { ghost_of });
+            } else {
+                opponents.insert(a.clone(), Opponent::Player { player_id: b.clone() });
+                opponents.insert(b, Opponent::Player { player_id: a });
+            }
+        }
+        opponents
+    }
+
+    /// 列舉所有配對方式，保留總代價最低者（8 人時只有 105 種）
+    fn search<'a>(
+        &self,
+        remaining: &mut Vec<&'a str>,
+        current: &mut Vec<(&'a str, &'a str)>,
+        cost: u32,
+        best: &mut Option<(u32, Vec<(String, String)>)>,
+    ) {
+        if best.as_ref().is_some_and(|(best_cost, _)| cost >= *best_cost) {
+            return;
+        }
+        let Some(first) = remaining.pop() else {
+            let pairs = current.iter().map(|(a, b)| (a.to_string(), b.to_string())).collect();
+            *best = Some((cost, pairs));
+            return;
+        };
+        for index in 0..remaining.len() {
+            let other = remaining.remove(index);
+            current.push((first, other));
+            self.search(remaining, current, cost + self.penalty(first, other), best);
+            current.pop();
+            remaining.insert(index, other);
+        }
+        remaining.push(first);
+    }
+
+    /// 越近期交手過代價越高，讓最近一次的對手優先避開
+    fn penalty(&self, a: &str, b: &str) -> u32 {
+        let recency = |from: &str, to: &str| {
+            self.history
+                .get(from)
+                .and_then(|recent| recent.iter().position(|p| p == to))
+                .map_or(0, |index| 1 << (2 * (HISTORY_LEN - 1 - index)))
+        };
+        recency(a, b).max(recency(b, a))
+    }
+
+    fn remember(&mut self, player: &str, opponent: &str) {
+        if player == GHOST {
+            return;
+        }
+        let recent = self.history.entry(player.to_string()).or_default();
+        recent.push_front(opponent.to_string());
+        recent.truncate(HISTORY_LEN);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn players(count: usize) -> Vec<String> {
+        (1..=count).map(|i| format!("p{}", i)).collect()
+    }
+
+    #[test]
+    fn test_round_schedule() {
+        assert_eq!(stage_of(1), (1, 1));
+        assert_eq!(stage_of(4), (2, 1));
+        assert_eq!(stage_of(10), (2, 7));
+        assert_eq!(stage_of(11), (3, 1));
+        let creeps: Vec<u32> = (1..=17).filter(|&r| round_kind(r) == RoundKind::Creep).collect();
+        assert_eq!(creeps, vec![1, 2, 3, 10, 17]);
+    }
+
+    #[test]
+    fn test_avoids_recent_rematches() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut pairer = Pairer::new();
+        let living = players(8);
+        let mut last: Vec<HashMap<String, Opponent>> = Vec::new();
+        for _ in 0..6 {
+            let opponents = pairer.pair(&living, &mut rng);
+            assert_eq!(opponents.len(), 8);
+            for (player, opponent) in &opponents {
+                let Opponent::Player { player_id } = opponent else { panic!("unexpected {:?}", opponent) };
+                assert_eq!(opponents[player_id], Opponent::Player { player_id: player.clone() });
+                // 8 人時可以完全避開最近 3 回合的對手
+                for previous in last.iter().rev().take(HISTORY_LEN) {
+                    assert_ne!(&previous[player], opponent);
+                }
+            }
+            last.push(opponents);
+        }
+    }
+
+    #[test]
+    fn test_odd_players_get_a_ghost() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut pairer = Pairer::new();
+        let living = players(5);
+        let mut ghosted = Vec::new();
+        for _ in 0..3 {
+            let opponents = pairer.pair(&living, &mut rng);
+            let ghosts: Vec<(&String, &Opponent)> = opponents.iter().filter(|(_, o)| matches!(o, Opponent::Ghost { .. })).collect();
+            assert_eq!(ghosts.len(), 1);
+            let (player, Opponent::Ghost { ghost_of }) = ghosts[0] else { unreachable!() };
+            assert_ne!(player, ghost_of);
+            ghosted.push(player.clone());
+        }
+        // 幽靈對手會輪流分配
+        ghosted.sort();
+        ghosted.dedup();
+        assert_eq!(ghosted.len(), 3);
+        assert!(pairer.pair(&players(1), &mut rng).is_empty());
+    }
+}
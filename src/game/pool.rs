@@ -0,0 +1,71 @@
+use super::GameRules;
+use crate::data::{all_chess_pieces, find_chess};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// 依棋子費用決定的每種棋子數量（1 費到 5 費）
+const COPIES_BY_COST: [u32; 5] = [29, 22, 18, 12, 10];
+
+/// 一星棋子換算的張數：二星 3 張、三星 9 張
+pub fn copies_of(level: u32) -> u32 {
+    3u32.pow(level.saturating_sub(1))
+}
+
+/// 同一場遊戲所有玩家共用的棋子池：購買時取出，賣出或玩家淘汰時放回
+#[derive(Debug)]
+pub struct UnitPool {
+    remaining: Mutex<HashMap<String, u32>>,
+}
+
+impl UnitPool {
+    /// 規則允許的每種棋子依費用放入對應數量
+    pub fn new(rules: &GameRules) -> Self {
+        let remaining = all_chess_pieces()
+            .into_iter()
+            .filter(|piece| rules.allows(&piece.name))
+            .map(|piece| {
+                let index = piece.cost.clamp(1, COPIES_BY_COST.len() as u32) as usize - 1;
+                (piece.name, COPIES_BY_COST[index])
+            })
+            .collect();
+        Self { remaining: Mutex::new(remaining) }
+    }
+
+    pub fn remaining(&self, chess: &str) -> u32 {
+        self.remaining.lock().unwrap().get(chess).copied().unwrap_or(0)
+    }
+
+    /// 取出 `copies` 張，數量不足時不取出並回傳 false
+    pub fn take(&self, chess: &str, copies: u32) -> bool {
+        let mut remaining = self.remaining.lock().unwrap();
+        match remaining.get_mut(chess) {
+            Some(count) if *count >= copies => {
+                *count -= copies;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn put_back(&self, chess: &str, copies: u32) {
+        if find_chess(chess).is_some() {
+            *self.remaining.lock().unwrap().entry(chess.to_string()).or_default() += copies;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_take_and_put_back() {
+        let pool = UnitPool::new(&GameRules::default());
+        assert_eq!(pool.remaining("Knight"), 22);
+        assert!(pool.take("Knight", 20));
+        assert!(!pool.take("Knight", copies_of(2)));
+        pool.put_back("Knight", copies_of(2));
+        assert_eq!(pool.remaining("Knight"), 5);
+        assert!(!pool.take("Dragon", 1));
+    }
+}
@@ -0,0 +1,681 @@
+// 回合流程：每場遊戲一個背景任務，重複「準備階段 → 戰鬥」直到遊戲結束
+//
+// 準備階段開始時配對對手，並推播 `RoundStarted` 告知每位玩家本回合的對手；
+// 準備時間結束後鎖定所有人的棋盤進入戰鬥，幽靈對手使用對應玩家本回合棋盤的複本。
+// 輸掉戰鬥的玩家扣除生命，歸零即淘汰並記錄名次，只剩一人時推播 `GameOver`。
+// 野怪回合與野怪棋盤對戰，不扣生命，改為發放戰利品。
+// 部分回合開始前先進行選秀，玩家依生命由低到高分波挑選棋子與道具。
+// 第 2 回合起每回合開始時發放收入；特定回合開始時提供強化符文選擇，準備時間結束仍未選擇則代選。
+// 斷線超過寬限期（AFK）的玩家由機器人策略代為操作：準備階段開始時買棋子、上場與選符文，選秀輪到時立即代選。
+
+use super::augments::{find_augment, has_augment, AugmentTier};
+use super::carousel::{has_carousel, Carousel, CarouselError, CarouselRegistry, CarouselSlot, WAVE_SECS};
+use super::creeps::{creep_wave, Loot};
+use super::pairing::{round_kind, stage_of, Opponent, Pairer, RoundKind};
+use super::{GameInfo, GameRegistry, Standing};
+use crate::chesses::combat::{simulate, BattleOutcome, Combatant, Side, Survivor};
+use crate::bots::strategy::next_action;
+use crate::bots::Difficulty;
+use crate::connection::ConnectionRegistry;
+use crate::control::{GameStateControl, StateSync};
+use crate::data::find_chess;
+use crate::player::PlayerManager;
+use crate::types::game_state::UnitOnBoard;
+use crate::types::response::WsResponse;
+use log::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 各階段輸掉戰鬥的基本傷害（第 7 階段之後維持不變）
+const STAGE_DAMAGE: [i32; 7] = [0, 2, 5, 8, 10, 12, 17];
+
+/// 一位玩家本回合的戰鬥
+struct Fight {
+    player_id: String,
+    side: Side,
+    opponent: Opponent,
+    outcome: Arc<BattleOutcome>,
+    /// 野怪回合依勝負擲出的戰利品
+    loot: Option<Vec<Loot>>,
+}
+
+/// 一場遊戲的回合狀態
+struct GameRounds {
+    game_id: String,
+    round: u32,
+    living: Vec<String>,
+    planning_secs: u64,
+    carousel_wave_secs: u64,
+    pairer: Pairer,
+    /// 以遊戲種子初始化，重播時配對結果相同
+    rng: StdRng,
+    /// 本回合的對手
+    opponents: HashMap<String, Opponent>,
+}
+
+impl GameRounds {
+    /// 從遊戲目前的回合開始（新遊戲為 0，重啟後接續的遊戲從下一回合繼續）
+    fn new(game: &GameInfo) -> Self {
+        Self {
+            game_id: game.id.clone(),
+            round: game.round,
+            living: game.players.iter().filter(|p| !game.standings.iter().any(|s| &s.player_id == *p)).cloned().collect(),
+            planning_secs: game.rules.planning_secs,
+            carousel_wave_secs: WAVE_SECS,
+            pairer: Pairer::new(),
+            rng: StdRng::seed_from_u64(game.seed as u64),
+            opponents: HashMap::new(),
+        }
+    }
+}
+
+pub struct RoundLoop {
+    games: Arc<GameRegistry>,
+    player_manager: Arc<PlayerManager>,
+    registry: Arc<ConnectionRegistry>,
+    sync: Arc<StateSync>,
+    carousels: CarouselRegistry,
+}
+
+impl RoundLoop {
+    pub fn new(
+        games: Arc<GameRegistry>,
+        player_manager: Arc<PlayerManager>,
+        registry: Arc<ConnectionRegistry>,
+        sync: Arc<StateSync>,
+    ) -> Self {
+        Self { games, player_manager, registry, sync, carousels: CarouselRegistry::new() }
+    }
+
+    /// 為剛開始的遊戲啟動回合流程
+    pub fn spawn(self: &Arc<Self>, game: GameInfo) {
+        tokio::spawn(self.clone().run_game(game));
+    }
+
+    async fn run_game(self: Arc<Self>, game: GameInfo) {
+        let mut rounds = GameRounds::new(&game);
+        let planning = Duration::from_secs(rounds.planning_secs);
+        while self.games.is_in_progress(&rounds.game_id) && rounds.living.len() > 1 {
+            if has_carousel(rounds.round + 1) {
+                self.run_carousel(&mut rounds).await;
+            }
+            self.begin_round(&mut rounds);
+            tokio::time::sleep(planning).await;
+            self.resolve_round(&mut rounds);
+        }
+        info!("Round loop for game {} stopped after round {}", rounds.game_id, rounds.round);
+    }
+
+    /// 選秀：依生命由低到高分波挑選，時間到還沒挑的玩家由伺服器隨機代選
+    async fn run_carousel(&self, rounds: &mut GameRounds) {
+        let game_id = rounds.game_id.clone();
+        let round = rounds.round + 1;
+        let Some(first) = rounds.living.first() else {
+            return;
+        };
+        let Some(pool) = self.player_manager.pool_of(first) else {
+            return;
+        };
+        let rules = self.player_manager.rules_of(first);
+        let players: Vec<(String, i32)> = rounds
+            .living
+            .iter()
+            .filter_map(|p| self.player_manager.get_player(p).map(|player| (p.clone(), player.hp)))
+            .collect();
+        let carousel = Carousel::new(round, &players, &pool, &rules, &mut rounds.rng);
+        let message = WsResponse::new(
+            "CarouselStarted",
+            json!({
+                "gameId": game_id,
+                "round": round,
+                "slots": carousel.slots,
+                "waves": carousel.waves,
+                "waveSecs": rounds.carousel_wave_secs,
+            }),
+        );
+        self.registry.broadcast_game(&game_id, &message, None);
+        let waves = carousel.waves.clone();
+        self.carousels.open(&game_id, carousel);
+
+        let wave_time = Duration::from_secs(rounds.carousel_wave_secs);
+        for (wave, players) in waves.iter().enumerate() {
+            let _ = self.carousels.with(&game_id, |carousel| carousel.wave = wave);
+            let message = WsResponse::new("CarouselWave", json!({ "gameId": game_id, "wave": wave, "players": players }));
+            self.registry.broadcast_game(&game_id, &message, None);
+            // AFK 的玩家立即代選，不必等到這一波結束
+            for player_id in players.iter().filter(|p| self.player_manager.get_player(p).is_some_and(|player| player.afk)) {
+                let slot = self.carousels.with(&game_id, |carousel| carousel.random_free_slot(&mut rounds.rng)).ok().flatten();
+                if let Some(slot) = slot {
+                    let _ = self.apply_pick(&game_id, player_id, slot, true);
+                }
+            }
+
+            let deadline = tokio::time::Instant::now() + wave_time;
+            loop {
+                let picked = self.carousels.picked();
+                if self.carousels.with(&game_id, |carousel| carousel.wave_done()).unwrap_or(true) {
+                    break;
+                }
+                if tokio::time::timeout_at(deadline, picked).await.is_err() {
+                    break;
+                }
+            }
+            for player_id in players {
+                let slot = self
+                    .carousels
+                    .with(&game_id, |carousel| {
+                        if carousel.has_picked(player_id) {
+                            None
+                        } else {
+                            carousel.random_free_slot(&mut rounds.rng)
+                        }
+                    })
+                    .ok()
+                    .flatten();
+                if let Some(slot) = slot {
+                    let _ = self.apply_pick(&game_id, player_id, slot, true);
+                }
+            }
+        }
+
+        if let Some(carousel) = self.carousels.close(&game_id) {
+            for chess in carousel.leftovers() {
+                pool.put_back(chess, 1);
+            }
+            self.games.record(&game_id, "Carousel", json!({ "round": round, "slots": carousel.slots }));
+        }
+        let message = WsResponse::new("CarouselEnded", json!({ "gameId": game_id, "round": round }));
+        self.registry.broadcast_game(&game_id, &message, None);
+    }
+
+    /// 玩家在自己所在遊戲的選秀中挑選
+    pub fn pick_carousel(&self, player_id: &str, slot_index: usize) -> Result<CarouselSlot, CarouselError> {
+        let game_id = self.registry.game_of(player_id).ok_or(CarouselError::NotActive)?;
+        self.apply_pick(&game_id, player_id, slot_index, false)
+    }
+
+    /// 記錄挑選、把棋子與道具交給玩家並廣播
+    fn apply_pick(&self, game_id: &str, player_id: &str, slot_index: usize, auto: bool) -> Result<CarouselSlot, CarouselError> {
+        let slot = self.carousels.pick(game_id, player_id, slot_index)?;
+        let _ = self.player_manager.receive_unit(player_id, &slot.chess, vec![slot.item.clone()]);
+        self.sync.publish(player_id);
+        let message = WsResponse::new(
+            "CarouselPick",
+            json!({
+                "gameId": game_id,
+                "playerId": player_id,
+                "slotIndex": slot_index,
+                "chess": slot.chess,
+                "item": slot.item,
+                "auto": auto,
+            }),
+        );
+        self.registry.broadcast_game(game_id, &message, None);
+        Ok(slot)
+    }
+
+    /// 進入下一回合的準備階段：發放收入、配對對手並推播給每位存活玩家，符文回合另外提供符文選擇
+    fn begin_round(&self, rounds: &mut GameRounds) {
+        rounds.round += 1;
+        self.games.set_round(&rounds.game_id, rounds.round);
+        let kind = round_kind(rounds.round);
+        rounds.opponents = match kind {
+            RoundKind::Pvp => rounds.pairer.pair(&rounds.living, &mut rounds.rng),
+            RoundKind::Creep => rounds.living.iter().map(|p| (p.clone(), Opponent::Creep)).collect(),
+        };
+
+        let (stage, stage_round) = stage_of(rounds.round);
+        for player_id in &rounds.living {
+            let message = WsResponse::new(
+                "RoundStarted",
+                json!({
+                    "gameId": rounds.game_id,
+                    "round": rounds.round,
+                    "stage": stage,
+                    "stageRound": stage_round,
+                    "kind": kind,
+                    "planningSecs": rounds.planning_secs,
+                    "opponent": rounds.opponents.get(player_id),
+                }),
+            );
+            let _ = self.registry.push(player_id, message);
+        }
+        if rounds.round > 1 {
+            for player_id in &rounds.living {
+                let _ = self.player_manager.grant_income(player_id);
+            }
+        }
+        if has_augment(rounds.round) {
+            let tier = AugmentTier::random(&mut rounds.rng);
+            self.offer_augments(rounds, tier);
+        }
+        for player_id in &rounds.living {
+            self.auto_play(player_id, rounds.round);
+            self.sync.publish(player_id);
+        }
+        self.games.record(
+            &rounds.game_id,
+            "RoundStarted",
+            json!({ "round": rounds.round, "kind": kind, "opponents": rounds.opponents }),
+        );
+    }
+
+    /// AFK 的玩家依一般難度機器人的策略操作，直到沒有要做的事或達到操作上限
+    fn auto_play(&self, player_id: &str, round: u32) {
+        let profile = Difficulty::Normal.profile();
+        let rules = self.player_manager.rules_of(player_id);
+        for _ in 0..profile.actions_per_round {
+            let Some(player) = self.player_manager.get_player(player_id).filter(|player| player.afk) else {
+                return;
+            };
+            let Some(action) = next_action(&GameStateControl::snapshot(&player, round), &rules, &profile) else {
+                return;
+            };
+            if let Err(e) = action.apply(&self.player_manager, player_id) {
+                debug!("Auto-play for AFK player {} stopped at {:?}: {}", player_id, action, e);
+                return;
+            }
+        }
+    }
+
+    /// 所有存活玩家從同一階級的符文中選擇
+    fn offer_augments(&self, rounds: &GameRounds, tier: AugmentTier) {
+        for player_id in &rounds.living {
+            let Ok(offer) = self.player_manager.offer_augments(player_id, tier) else {
+                continue;
+            };
+            let options: Vec<_> = offer.options.iter().filter_map(|id| find_augment(id)).collect();
+            let message = WsResponse::new(
+                "AugmentOffered",
+                json!({
+                    "gameId": rounds.game_id,
+                    "round": rounds.round,
+                    "tier": tier,
+                    "options": options,
+                    "rerolls": offer.rerolls,
+                }),
+            );
+            let _ = self.registry.push(player_id, message);
+        }
+    }
+
+    /// 準備時間結束：代選尚未選擇的符文、鎖定棋盤、進行戰鬥並結算傷害與淘汰
+    fn resolve_round(&self, rounds: &mut GameRounds) {
+        for player_id in &rounds.living {
+            if let Some(augment_id) = self.player_manager.auto_pick_augment(player_id) {
+                let message = WsResponse::new(
+                    "AugmentPicked",
+                    json!({ "gameId": rounds.game_id, "augment": find_augment(&augment_id), "auto": true }),
+                );
+                let _ = self.registry.push(player_id, message);
+                self.sync.publish(player_id);
+            }
+        }
+
+        // 鎖定棋盤，羈絆（含符文額外計入的標籤）與符文屬性加成在此時計算
+        let mut boards: HashMap<String, Vec<UnitOnBoard>> = HashMap::new();
+        let mut teams: HashMap<String, Vec<Combatant>> = HashMap::new();
+        for player in rounds.living.iter().filter_map(|p| self.player_manager.get_player(p)) {
+            let modifiers = player.modifiers();
+            teams.insert(player.id.clone(), Combatant::team(&player.board, &modifiers.synergies, modifiers.stats));
+            boards.insert(player.id.clone(), player.board);
+        }
+        let board_of = |player_id: &str| teams.get(player_id).cloned().unwrap_or_default();
+        let (stage, _) = stage_of(rounds.round);
+
+        // 每組對戰只模擬一次，雙方共用同一份結果
+        let mut fought: HashSet<String> = HashSet::new();
+        let mut results: Vec<Fight> = Vec::new();
+        let mut battles = Vec::new();
+        for player_id in &rounds.living {
+            if fought.contains(player_id) {
+                continue;
+            }
+            let Some(opponent) = rounds.opponents.get(player_id).cloned() else {
+                continue;
+            };
+            let seed: u64 = rounds.rng.gen();
+            let mut loot = None;
+            let outcome = match &opponent {
+                Opponent::Player { player_id: enemy } => {
+                    let outcome = Arc::new(simulate(board_of(player_id), board_of(enemy), seed));
+                    fought.insert(enemy.clone());
+                    results.push(Fight {
+                        player_id: enemy.clone(),
+                        side: Side::Away,
+                        opponent: Opponent::Player { player_id: player_id.clone() },
+                        outcome: outcome.clone(),
+                        loot: None,
+                    });
+                    outcome
+                }
+                Opponent::Ghost { ghost_of } => Arc::new(simulate(board_of(player_id), board_of(ghost_of), seed)),
+                Opponent::Creep => {
+                    let wave = creep_wave(rounds.round);
+                    let outcome = Arc::new(simulate(board_of(player_id), wave.combatants(), seed));
+                    loot = Some(match outcome.winner {
+                        Some(Side::Home) => wave.roll_loot(&mut rounds.rng),
+                        _ => wave.consolation(),
+                    });
+                    outcome
+                }
+            };
+            battles.push(json!({
+                "home": player_id,
+                "opponent": opponent,
+                "seed": seed,
+                "homeBoard": boards.get(player_id),
+                "awayBoard": match &opponent {
+                    Opponent::Player { player_id: enemy } | Opponent::Ghost { ghost_of: enemy } => json!(boards.get(enemy)),
+                    Opponent::Creep => json!({ "creeps": creep_wave(rounds.round).name }),
+                },
+                "winner": outcome.winner,
+                "survivors": outcome.survivors,
+            }));
+            results.push(Fight { player_id: player_id.clone(), side: Side::Home, opponent, outcome, loot });
+        }
+        self.games.record(&rounds.game_id, "Combat", json!({ "round": rounds.round, "battles": battles }));
+
+        // 輸掉（或平手）的玩家依階段與對方存活單位扣血；野怪回合不扣血，改發戰利品
+        let mut overkill: HashMap<String, i32> = HashMap::new();
+        for Fight { player_id, side, opponent, outcome, loot } in results {
+            let enemy_side = match side {
+                Side::Home => Side::Away,
+                Side::Away => Side::Home,
+            };
+            let (result, damage) = match outcome.winner {
+                Some(winner) if winner == side => ("win", 0),
+                winner => {
+                    let damage = if loot.is_some() { 0 } else { player_damage(stage, outcome.survivors_of(enemy_side)) };
+                    (if winner.is_none() { "draw" } else { "loss" }, damage)
+                }
+            };
+            let drops = loot.and_then(|loot| self.player_manager.grant_loot(&player_id, &loot).ok()).map(|(_, drops)| drops);
+            let hp_before = self.player_manager.get_player(&player_id).map_or(0, |p| p.hp);
+            let Ok(player) = self.player_manager.take_damage(&player_id, damage) else {
+                continue;
+            };
+            if player.hp == 0 {
+                overkill.insert(player_id.clone(), damage - hp_before);
+            }
+            let message = WsResponse::new(
+                "CombatResult",
+                json!({
+                    "gameId": rounds.game_id,
+                    "round": rounds.round,
+                    "opponent": opponent,
+                    "side": side,
+                    "result": result,
+                    "damage": damage,
+                    "hp": player.hp,
+                    "battle": outcome.as_ref(),
+                    "loot": drops,
+                }),
+            );
+            let _ = self.registry.push(&player_id, message);
+            self.sync.publish(&player_id);
+        }
+        self.eliminate(rounds, overkill);
+    }
+
+    /// 生命歸零的玩家依序淘汰（同回合淘汰時超出的傷害越多名次越後面），剩一人時遊戲結束
+    fn eliminate(&self, rounds: &mut GameRounds, overkill: HashMap<String, i32>) {
+        let mut dead: Vec<(String, i32)> = overkill.into_iter().collect();
+        dead.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        for (player_id, _) in dead {
+            let placement = rounds.living.len() as u32;
+            rounds.living.retain(|p| *p != player_id);
+            let _ = self.player_manager.eliminate(&player_id);
+            self.sync.publish(&player_id);
+            self.games.add_standing(&rounds.game_id, Standing { player_id: player_id.clone(), placement });
+            info!("Player {} eliminated from game {} ({})", player_id, rounds.game_id, placement);
+            let message = WsResponse::new(
+                "PlayerEliminated",
+                json!({ "gameId": rounds.game_id, "playerId": player_id, "placement": placement }),
+            );
+            self.registry.broadcast_game(&rounds.game_id, &message, None);
+        }
+
+        if rounds.living.len() > 1 {
+            return;
+        }
+        if let Some(winner) = rounds.living.first() {
+            self.games.add_standing(&rounds.game_id, Standing { player_id: winner.clone(), placement: 1 });
+        }
+        if let Some(game) = self.games.finish(&rounds.game_id) {
+            info!("Game {} over: {:?}", game.id, game.standings);
+            for player_id in &game.players {
+                self.player_manager.end_game(player_id);
+            }
+            let message = WsResponse::new("GameOver", json!({ "gameId": game.id, "standings": game.standings }));
+            self.registry.broadcast_game(&game.id, &message, None);
+            self.registry.end_spectating(&game.id);
+        }
+    }
+}
+
+/// 輸掉戰鬥的傷害：階段基本傷害加上每個存活敵方單位的（星級 + 費用 - 1）
+pub fn player_damage<'a>(stage: u32, survivors: impl Iterator<Item = &'a Survivor>) -> i32 {
+    let base = STAGE_DAMAGE[(stage.max(1) as usize - 1).min(STAGE_DAMAGE.len() - 1)];
+    let units: i32 = survivors
+        .map(|s| {
+            let cost = find_chess(&s.chess).map_or(1, |piece| piece.cost) as i32;
+            s.level as i32 + cost - 1
+        })
+        .sum();
+    base + units
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connection::TestClients;
+    use crate::game::{GameRules, GameStatus, UnitPool};
+    use crate::types::game_state::UnitOnBench;
+    use crate::game::augments::AUGMENT_CHOICES;
+
+    struct Fixture {
+        rounds_loop: Arc<RoundLoop>,
+        games: Arc<GameRegistry>,
+        player_manager: Arc<PlayerManager>,
+        pool: Arc<UnitPool>,
+        game: GameInfo,
+        clients: TestClients,
+    }
+
+    impl Fixture {
+        fn new(ids: &[&str]) -> Self {
+            let games = Arc::new(GameRegistry::new());
+            let player_manager = Arc::new(PlayerManager::new(GameRules::default()));
+            let registry = Arc::new(ConnectionRegistry::new());
+            let game = games.create(11, ids.iter().map(|id| id.to_string()).collect());
+            games.start(&game.id);
+            let pool = Arc::new(UnitPool::new(&game.rules));
+            for &id in ids {
+                player_manager.start_game(id, Arc::new(game.rules.clone()), pool.clone());
+            }
+            let clients = TestClients::connect(&registry, ids, Some(&game.id));
+            let sync = Arc::new(StateSync::new(player_manager.clone(), registry.clone()).with_games(games.clone()));
+            let rounds_loop = Arc::new(RoundLoop::new(games.clone(), player_manager.clone(), registry, sync));
+            Self { rounds_loop, games, player_manager, pool, game, clients }
+        }
+
+        fn pushes(&mut self, player_id: &str) -> Vec<(String, serde_json::Value)> {
+            self.clients
+                .pushes(player_id)
+                .into_iter()
+                .map(|response| (response.type_, response.payload.unwrap_or_default()))
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_planning_pushes_upcoming_opponent() {
+        let mut fixture = Fixture::new(&["a", "b", "c"]);
+        let mut rounds = GameRounds::new(&fixture.game);
+        rounds.round = 3;
+        fixture.rounds_loop.begin_round(&mut rounds);
+        assert_eq!(fixture.games.get(&fixture.game.id).unwrap().round, 4);
+
+        let mut kinds = Vec::new();
+        for id in ["a", "b", "c"] {
+            let (kind, payload) = fixture.pushes(id).remove(0);
+            assert_eq!(kind, "RoundStarted");
+            assert_eq!((payload["stage"].clone(), payload["stageRound"].clone()), (json!(2), json!(1)));
+            kinds.push(payload["opponent"]["kind"].as_str().unwrap().to_string());
+        }
+        kinds.sort();
+        assert_eq!(kinds, vec!["ghost", "player", "player"]);
+
+        fixture.rounds_loop.resolve_round(&mut rounds);
+        let replay = fixture.games.replay(&fixture.game.id).unwrap();
+        let combat = replay.events.last().unwrap();
+        assert_eq!(combat.kind, "Combat");
+        // 兩名玩家的對戰只模擬一次，加上一場幽靈對戰
+        assert_eq!(combat.payload["battles"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_loser_takes_damage_and_game_ends() {
+        let mut fixture = Fixture::new(&["a", "b"]);
+        let mut strong = fixture.player_manager.get_player("a").unwrap();
+        strong.board = vec![UnitOnBoard { id: "u001".into(), chess: "Knight".into(), level: 2, position: [3, 3], items: Vec::new() }];
+        fixture.player_manager.update_player(strong);
+        let mut weak = fixture.player_manager.get_player("b").unwrap();
+        weak.hp = 5;
+        weak.bench = vec![UnitOnBench { id: "u001".into(), chess: "Mage".into(), level: 2, items: Vec::new() }];
+        fixture.player_manager.update_player(weak);
+        let mages = fixture.pool.remaining("Mage");
+
+        let mut rounds = GameRounds::new(&fixture.game);
+        rounds.round = 3;
+        fixture.rounds_loop.begin_round(&mut rounds);
+        fixture.rounds_loop.resolve_round(&mut rounds);
+
+        // 第 2 階段基本傷害 2，存活的二星 Knight（2 費）再加 3
+        assert_eq!(player_damage(2, [Survivor { id: "u001".into(), side: Side::Home, chess: "Knight".into(), level: 2, hp: 1 }].iter()), 5);
+        let pushes = fixture.pushes("b");
+        let (_, result) = pushes.iter().find(|(kind, _)| kind == "CombatResult").unwrap();
+        assert_eq!((result["result"].as_str(), result["damage"].as_i64(), result["hp"].as_i64()), (Some("loss"), Some(5), Some(0)));
+        assert!(pushes.iter().any(|(kind, payload)| kind == "PlayerEliminated" && payload["placement"] == 2));
+        let (_, over) = pushes.iter().find(|(kind, _)| kind == "GameOver").unwrap();
+        assert_eq!(over["standings"], json!([{ "playerId": "a", "placement": 1 }, { "playerId": "b", "placement": 2 }]));
+
+        assert_eq!(fixture.games.get(&fixture.game.id).unwrap().status, GameStatus::Finished);
+        assert!(fixture.player_manager.get_player("b").unwrap().bench.is_empty());
+        assert_eq!(fixture.pool.remaining("Mage"), mages + 3);
+        assert_eq!(rounds.living, vec!["a"]);
+        // 遊戲結束後不再保留每位玩家的規則與棋子池
+        assert!(["a", "b"].iter().all(|id| fixture.player_manager.pool_of(id).is_none()));
+    }
+
+    #[test]
+    fn test_afk_player_is_auto_played() {
+        let fixture = Fixture::new(&["a", "b"]);
+        let mut afk = fixture.player_manager.get_player("a").unwrap();
+        afk.bench = vec![UnitOnBench { id: "u101".into(), chess: "Knight".into(), level: 1, items: Vec::new() }];
+        fixture.player_manager.update_player(afk);
+        fixture.player_manager.set_afk("a", true);
+
+        let mut rounds = GameRounds::new(&fixture.game);
+        fixture.rounds_loop.begin_round(&mut rounds);
+
+        // 離線的玩家由策略代為上場（商店隨機，上場的不一定是原本那一隻）；連線中的玩家不會被代操作
+        let afk = fixture.player_manager.get_player("a").unwrap();
+        assert!(!afk.board.is_empty());
+        assert!(fixture.player_manager.get_player("b").unwrap().board.is_empty());
+    }
+
+    #[test]
+    fn test_income_and_augment_offer_with_auto_pick() {
+        let mut fixture = Fixture::new(&["a", "b"]);
+        let money = fixture.player_manager.get_player("a").unwrap().money;
+        let mut rounds = GameRounds::new(&fixture.game);
+        rounds.round = 3;
+        fixture.rounds_loop.begin_round(&mut rounds);
+
+        // 第 2-1 回合：發放收入（100 金有 5 金利息）並提供符文選擇
+        assert_eq!(fixture.player_manager.get_player("a").unwrap().money, money + 10);
+        let offers: Vec<_> = ["a", "b"]
+            .into_iter()
+            .map(|id| fixture.pushes(id).into_iter().find(|(kind, _)| kind == "AugmentOffered").unwrap().1)
+            .collect();
+        assert_eq!(offers[0]["tier"], offers[1]["tier"]);
+        assert_eq!(offers[0]["options"].as_array().unwrap().len(), AUGMENT_CHOICES);
+        let chosen = offers[1]["options"][1]["id"].as_str().unwrap();
+        fixture.player_manager.pick_augment("b", chosen).unwrap();
+
+        fixture.rounds_loop.resolve_round(&mut rounds);
+        let pushes = fixture.pushes("a");
+        let (_, picked) = pushes.iter().find(|(kind, _)| kind == "AugmentPicked").unwrap();
+        assert_eq!((picked["augment"]["id"].clone(), picked["auto"].as_bool()), (offers[0]["options"][0]["id"].clone(), Some(true)));
+        assert!(!fixture.pushes("b").iter().any(|(kind, _)| kind == "AugmentPicked"));
+        assert_eq!(fixture.player_manager.get_player("b").unwrap().augments, vec![chosen]);
+    }
+
+    #[tokio::test]
+    async fn test_carousel_picks_and_auto_picks() {
+        let mut fixture = Fixture::new(&["a", "b", "c"]);
+        for (id, hp) in [("b", 20), ("c", 10)] {
+            let mut weak = fixture.player_manager.get_player(id).unwrap();
+            weak.hp = hp;
+            fixture.player_manager.update_player(weak);
+        }
+        let rounds_loop = fixture.rounds_loop.clone();
+        let mut rounds = GameRounds::new(&fixture.game);
+        rounds.carousel_wave_secs = 1;
+
+        let task = {
+            let rounds_loop = rounds_loop.clone();
+            tokio::spawn(async move {
+                rounds_loop.run_carousel(&mut rounds).await;
+            })
+        };
+        // 生命較低的 c、b 在第一波，a 不能搶先挑
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(rounds_loop.pick_carousel("a", 0).unwrap_err(), CarouselError::NotYourTurn);
+        let slot = rounds_loop.pick_carousel("c", 2).unwrap();
+        task.await.unwrap();
+
+        let bench = fixture.player_manager.get_player("c").unwrap().bench;
+        assert_eq!((bench[0].chess.clone(), bench[0].items.clone()), (slot.chess, vec![slot.item]));
+        let pushes = fixture.pushes("a");
+        let picks: Vec<_> = pushes.iter().filter(|(kind, _)| kind == "CarouselPick").map(|(_, p)| p).collect();
+        assert_eq!(picks.len(), 3);
+        assert_eq!((picks[0]["playerId"].as_str(), picks[0]["auto"].as_bool()), (Some("c"), Some(false)));
+        assert!(picks[1..].iter().all(|p| p["auto"] == true));
+        assert_eq!(pushes.last().unwrap().0, "CarouselEnded");
+        assert!(fixture.player_manager.get_player("a").unwrap().bench.len() == 1);
+    }
+
+    #[test]
+    fn test_creep_round_grants_loot_without_damage() {
+        let mut fixture = Fixture::new(&["a", "b"]);
+        let mut strong = fixture.player_manager.get_player("a").unwrap();
+        strong.board = vec![UnitOnBoard { id: "u001".into(), chess: "Knight".into(), level: 2, position: [3, 3], items: Vec::new() }];
+        fixture.player_manager.update_player(strong);
+        let money = fixture.player_manager.get_player("b").unwrap().money;
+
+        let mut rounds = GameRounds::new(&fixture.game);
+        fixture.rounds_loop.begin_round(&mut rounds);
+        assert_eq!(rounds.opponents.get("a"), Some(&Opponent::Creep));
+        fixture.rounds_loop.resolve_round(&mut rounds);
+
+        let pushes = fixture.pushes("a");
+        let (_, won) = pushes.iter().find(|(kind, _)| kind == "CombatResult").unwrap();
+        assert_eq!((won["result"].as_str(), won["damage"].as_i64()), (Some("win"), Some(0)));
+        assert!(!won["loot"].as_array().unwrap().is_empty());
+
+        // 空棋盤輸給小兵：不扣血，拿到 1 金幣安慰獎
+        let pushes = fixture.pushes("b");
+        let (_, lost) = pushes.iter().find(|(kind, _)| kind == "CombatResult").unwrap();
+        assert_eq!((lost["result"].as_str(), lost["damage"].as_i64()), (Some("loss"), Some(0)));
+        assert_eq!(lost["loot"], json!([{ "kind": "gold", "amount": 1 }]));
+        let loser = fixture.player_manager.get_player("b").unwrap();
+        assert_eq!((loser.hp, loser.money), (100, money + 1));
+    }
+}
@@ -0,0 +1,145 @@
+use crate::config::{EconomyConfig, TimingConfig};
+use crate::data::{all_chess_pieces, find_chess};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::RangeInclusive;
+
+/// 商店格數的預設值與上限
+pub const DEFAULT_SHOP_SIZE: usize = 5;
+pub const MAX_SHOP_SIZE: usize = 8;
+
+/// 準備階段長度（秒）的允許範圍，伺服器設定與自訂房間共用
+pub const PLANNING_SECS: RangeInclusive<u64> = 5..=300;
+
+/// 一場遊戲的規則；配對遊戲使用伺服器設定，自訂房間可由房主調整
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct GameRules {
+    /// 初始金幣
+    pub starting_gold: i32,
+    /// 每回合準備階段的秒數
+    pub planning_secs: u64,
+    /// 商店格數
+    pub shop_size: usize,
+    /// 重新整理商店的花費
+    pub refresh_cost: i32,
+    /// 購買經驗的花費
+    pub xp_cost: i32,
+    /// 商店會出現的棋子；未設定時為全部棋子
+    pub allowed_units: Option<Vec<String>>,
+}
+
+/// 房主調整規則時只需帶要改的欄位
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RulesPatch {
+    pub starting_gold: Option<i32>,
+    pub planning_secs: Option<u64>,
+    pub shop_size: Option<usize>,
+    pub refresh_cost: Option<i32>,
+    pub xp_cost: Option<i32>,
+    /// 空陣列代表恢復為全部棋子
+    pub allowed_units: Option<Vec<String>>,
+}
+
+/// 規則不合法的原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidRules(pub String);
+
+impl fmt::Display for InvalidRules {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid rules: {}", self.0)
+    }
+}
+
+impl Default for GameRules {
+    fn default() -> Self {
+        Self::from_config(&EconomyConfig::default(), &TimingConfig::default())
+    }
+}
+
+impl GameRules {
+    /// 伺服器設定中的預設規則
+    pub fn from_config(economy: &EconomyConfig, timing: &TimingConfig) -> Self {
+        Self {
+            starting_gold: economy.starting_money,
+            planning_secs: timing.planning_secs,
+            shop_size: DEFAULT_SHOP_SIZE,
+            refresh_cost: economy.refresh_cost,
+            xp_cost: economy.xp_cost,
+            allowed_units: None,
+        }
+    }
+
+    /// 棋子是否會出現在商店
+    pub fn allows(&self, chess: &str) -> bool {
+        self.allowed_units
+            .as_ref()
+            .is_none_or(|units| units.iter().any(|unit| unit == chess))
+    }
+
+    /// 套用調整後的規則，不合法時維持原狀
+    pub fn apply(&self, patch: RulesPatch) -> Result<GameRules, InvalidRules> {
+        let mut rules = self.clone();
+        rules.starting_gold = patch.starting_gold.unwrap_or(rules.starting_gold);
+        rules.planning_secs = patch.planning_secs.unwrap_or(rules.planning_secs);
+        rules.shop_size = patch.shop_size.unwrap_or(rules.shop_size);
+        rules.refresh_cost = patch.refresh_cost.unwrap_or(rules.refresh_cost);
+        rules.xp_cost = patch.xp_cost.unwrap_or(rules.xp_cost);
+        if let Some(units) = patch.allowed_units {
+            rules.allowed_units = (!units.is_empty()).then_some(units);
+        }
+        rules.validate()?;
+        Ok(rules)
+    }
+
+    pub fn validate(&self) -> Result<(), InvalidRules> {
+        let invalid = |reason: &str| Err(InvalidRules(reason.to_string()));
+
+        if self.starting_gold < 0 || self.refresh_cost < 0 || self.xp_cost < 0 {
+            return invalid("gold values must not be negative");
+        }
+        if !PLANNING_SECS.contains(&self.planning_secs) {
+            return Err(InvalidRules(format!("planningSecs must be between {} and {}", PLANNING_SECS.start(), PLANNING_SECS.end())));
+        }
+        if !(1..=MAX_SHOP_SIZE).contains(&self.shop_size) {
+            return invalid("shopSize must be between 1 and 8");
+        }
+        if let Some(units) = &self.allowed_units {
+            if let Some(unknown) = units.iter().find(|unit| find_chess(unit).is_none()) {
+                return Err(InvalidRules(format!("unknown unit: {}", unknown)));
+            }
+        }
+        let pool = all_chess_pieces().iter().filter(|piece| self.allows(&piece.name)).count();
+        if pool < self.shop_size {
+            return invalid("allowedUnits must contain at least shopSize units");
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_patch_validates_rules() {
+        let rules = GameRules::default();
+        let custom = rules
+            .apply(RulesPatch {
+                starting_gold: Some(50),
+                shop_size: Some(3),
+                allowed_units: Some(vec!["Knight".into(), "Mage".into(), "Archer".into()]),
+                ..RulesPatch::default()
+            })
+            .unwrap();
+        assert_eq!(custom.starting_gold, 50);
+        assert!(custom.allows("Knight") && !custom.allows("Tank"));
+        assert!(custom.apply(RulesPatch { allowed_units: Some(vec![]), ..RulesPatch::default() }).unwrap().allows("Tank"));
+
+        assert!(rules.apply(RulesPatch { shop_size: Some(4), allowed_units: Some(vec!["Knight".into()]), ..RulesPatch::default() }).is_err());
+        assert!(rules.apply(RulesPatch { allowed_units: Some(vec!["Dragon".into()]), ..RulesPatch::default() }).is_err());
+        assert!(rules.apply(RulesPatch { planning_secs: Some(0), ..RulesPatch::default() }).is_err());
+    }
+}
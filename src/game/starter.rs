@@ -0,0 +1,73 @@
+use super::{GameInfo, GameRegistry, RoundLoop, UnitPool};
+use crate::connection::ConnectionRegistry;
+use crate::control::StateSync;
+use crate::player::PlayerManager;
+use crate::types::response::WsResponse;
+use log::*;
+use serde_json::json;
+use std::sync::Arc;
+
+/// 開始遊戲：配對確認完成或房主開始自訂房間時呼叫
+///
+/// 依遊戲規則重置每位玩家的狀態、把玩家加入遊戲的推播群組，並推播 `GameStarted` 與完整快照，
+/// 設定了回合流程時接著啟動回合流程。
+pub struct GameStarter {
+    games: Arc<GameRegistry>,
+    player_manager: Arc<PlayerManager>,
+    registry: Arc<ConnectionRegistry>,
+    sync: Arc<StateSync>,
+    rounds: Option<Arc<RoundLoop>>,
+}
+
+impl GameStarter {
+    pub fn new(
+        games: Arc<GameRegistry>,
+        player_manager: Arc<PlayerManager>,
+        registry: Arc<ConnectionRegistry>,
+        sync: Arc<StateSync>,
+    ) -> Self {
+        Self { games, player_manager, registry, sync, rounds: None }
+    }
+
+    /// 遊戲開始後啟動回合流程（需要 tokio runtime）
+    pub fn with_rounds(mut self, rounds: Arc<RoundLoop>) -> Self {
+        self.rounds = Some(rounds);
+        self
+    }
+
+    pub fn start(&self, game_id: &str) -> Option<GameInfo> {
+        let game = self.games.start(game_id)?;
+        info!("Game {} started with {:?}", game.id, game.players);
+
+        let rules = Arc::new(game.rules.clone());
+        let pool = Arc::new(UnitPool::new(&rules));
+        let message = WsResponse::new(
+            "GameStarted",
+            json!({ "gameId": game.id, "players": game.players, "rules": game.rules }),
+        );
+        for player_id in &game.players {
+            self.registry.join_game(player_id, &game.id);
+            self.player_manager.start_game(player_id, rules.clone(), pool.clone());
+            let _ = self.registry.push(player_id, message.clone());
+            self.sync.send_snapshot(player_id);
+        }
+        if let Some(rounds) = &self.rounds {
+            rounds.spawn(game.clone());
+        }
+        Some(game)
+    }
+
+    /// 伺服器重啟後接續進行中的遊戲：沿用保存的玩家狀態，重建棋子池並從下一回合繼續
+    pub fn resume(&self, game: GameInfo) {
+        info!("Resuming game {} after round {}", game.id, game.round);
+        let rules = Arc::new(game.rules.clone());
+        let pool = Arc::new(UnitPool::new(&rules));
+        for player_id in &game.players {
+            self.registry.join_game(player_id, &game.id);
+            self.player_manager.resume_game(player_id, rules.clone(), pool.clone());
+        }
+        if let Some(rounds) = &self.rounds {
+            rounds.spawn(game);
+        }
+    }
+}
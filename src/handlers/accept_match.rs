@@ -0,0 +1,62 @@
+use super::{acting_player, HandlerError, TypedHandler};
+use crate::connection::ConnectionContext;
+use crate::matchmaking::Matchmaker;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Instant;
+use async_trait::async_trait;
+
+/// 回應 `MatchFound` 的確認；所有人都接受後遊戲開始
+pub struct AcceptMatchHandler {
+    matchmaker: Arc<Matchmaker>,
+}
+
+impl AcceptMatchHandler {
+    pub fn new(matchmaker: Arc<Matchmaker>) -> Self {
+        Self { matchmaker }
+    }
+}
+
+fn default_accept() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AcceptMatchRequest {
+    pub game_id: String,
+    /// false 代表拒絕，會離開佇列
+    #[serde(default = "default_accept")]
+    pub accept: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AcceptMatchResponse {
+    pub game_id: String,
+    pub accepted: bool,
+    /// 是否因這次確認而開始遊戲
+    pub started: bool,
+}
+
+#[async_trait]
+impl TypedHandler for AcceptMatchHandler {
+    type Request = AcceptMatchRequest;
+    type Response = AcceptMatchResponse;
+
+    const ACTION: &'static str = "AcceptMatch";
+    const RESULT: &'static str = "AcceptMatchResult";
+
+    async fn handle(&self, ctx: &ConnectionContext, request: AcceptMatchRequest) -> Result<AcceptMatchResponse, HandlerError> {
+        let player_id = acting_player(ctx, None)?;
+        let started = self
+            .matchmaker
+            .respond(&player_id, &request.game_id, request.accept, Instant::now())?;
+        Ok(AcceptMatchResponse {
+            game_id: request.game_id,
+            accepted: request.accept,
+            started,
+        })
+    }
+}
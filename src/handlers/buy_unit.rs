@@ -0,0 +1,61 @@
+use super::{acting_player, HandlerError, TypedHandler};
+use crate::connection::ConnectionContext;
+use crate::control::StateSync;
+use crate::player::PlayerManager;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use async_trait::async_trait;
+
+/// 購買商店中的棋子
+pub struct BuyUnitHandler {
+    player_manager: Arc<PlayerManager>,
+    sync: Arc<StateSync>,
+}
+
+impl BuyUnitHandler {
+    pub fn new(player_manager: Arc<PlayerManager>, sync: Arc<StateSync>) -> Self {
+        Self { player_manager, sync }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BuyUnitRequest {
+    /// 選填，必須與登入身分一致
+    pub player_id: Option<String>,
+    /// 商店格子的索引（從 0 開始）
+    pub shop_index: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuyUnitResponse {
+    pub player_id: String,
+    pub unit_id: String,
+    pub money: i32,
+    pub version: u64,
+}
+
+#[async_trait]
+impl TypedHandler for BuyUnitHandler {
+    type Request = BuyUnitRequest;
+    type Response = BuyUnitResponse;
+
+    const ACTION: &'static str = "BuyUnit";
+    const RESULT: &'static str = "BuyUnitResult";
+
+    async fn handle(&self, ctx: &ConnectionContext, request: BuyUnitRequest) -> Result<BuyUnitResponse, HandlerError> {
+        let player_id = acting_player(ctx, request.player_id.as_deref())?;
+
+        let player = self.player_manager.buy_unit(&player_id, request.shop_index)?;
+        let version = self.sync.publish(&player_id).unwrap_or_default();
+
+        Ok(BuyUnitResponse {
+            unit_id: player.bench.last().map(|unit| unit.id.clone()).unwrap_or_default(),
+            player_id,
+            money: player.money,
+            version,
+        })
+    }
+}
@@ -1,62 +1,61 @@
-use super::MessageHandler;
-use crate::types::response::{WsRequest, WsResponse};
-use crate::player::PlayerManager;
-use serde_json::json;
+use super::{acting_player, HandlerError, TypedHandler};
+use crate::connection::ConnectionContext;
+use crate::control::StateSync;
+use crate::player::{PlayerManager, XPData};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use async_trait::async_trait;
 
 pub struct BuyXPHandler {
     player_manager: Arc<PlayerManager>,
+    sync: Arc<StateSync>,
 }
 
 impl BuyXPHandler {
-    pub fn new(player_manager: Arc<PlayerManager>) -> Self {
-        Self { player_manager }
+    pub fn new(player_manager: Arc<PlayerManager>, sync: Arc<StateSync>) -> Self {
+        Self { player_manager, sync }
     }
 }
 
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BuyXPRequest {
+    /// 選填，必須與登入身分一致
+    pub player_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuyXPResponse {
+    pub player_id: String,
+    pub money: i32,
+    pub xp: XPData,
+    pub level: u32,
+    pub version: u64,
+}
+
 #[async_trait]
-impl MessageHandler for BuyXPHandler {
-    async fn handle(&self, val: &WsRequest) -> WsResponse {
-        // 从请求中获取 playerId
-        let player_id = match val.payload.get("playerId") {
-            Some(id) => match id.as_str() {
-                Some(id_str) => id_str,
-                None => return WsResponse::error("invalid playerId format".to_string()),
-            },
-            None => return WsResponse::error("missing playerId".to_string()),
-        };
+impl TypedHandler for BuyXPHandler {
+    type Request = BuyXPRequest;
+    type Response = BuyXPResponse;
+
+    const ACTION: &'static str = "BuyXP";
+    const RESULT: &'static str = "BuyXPResult";
+
+    async fn handle(&self, ctx: &ConnectionContext, request: BuyXPRequest) -> Result<BuyXPResponse, HandlerError> {
+        let player_id = acting_player(ctx, request.player_id.as_deref())?;
 
         // 尝试购买经验值
-        match self.player_manager.buy_xp(player_id) {
-            Ok(player) => {
-                WsResponse {
-                    type_: "BuyXPResult".to_string(),
-                    payload: Some(json!({
-                        "playerId": player.id,
-                        "success": true,
-                        "money": player.money,
-                        "xp": {
-                            "current": player.xp.current,
-                            "required": player.xp.required
-                        }
-                    })),
-                }
-            }
-            Err(reason) => {
-                WsResponse {
-                    type_: "BuyXPResult".to_string(),
-                    payload: Some(json!({
-                        "playerId": player_id,
-                        "success": false,
-                        "reason": reason
-                    })),
-                }
-            }
-        }
-    }
+        let player = self.player_manager.buy_xp(&player_id)?;
+        let version = self.sync.publish(&player_id).unwrap_or_default();
 
-    fn can_handle(&self, action: &str) -> bool {
-        action == "BuyXP"
+        Ok(BuyXPResponse {
+            player_id: player.id,
+            money: player.money,
+            xp: player.xp,
+            level: player.level,
+            version,
+        })
     }
-} 
\ No newline at end of file
+}
@@ -0,0 +1,49 @@
+use super::{acting_player, HandlerError, TypedHandler};
+use crate::connection::ConnectionContext;
+use crate::matchmaking::Matchmaker;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Instant;
+use async_trait::async_trait;
+
+/// 離開配對佇列；已在確認階段時視同拒絕這場配對
+pub struct CancelQueueHandler {
+    matchmaker: Arc<Matchmaker>,
+}
+
+impl CancelQueueHandler {
+    pub fn new(matchmaker: Arc<Matchmaker>) -> Self {
+        Self { matchmaker }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelQueueRequest {
+    /// 選填，必須與登入身分一致
+    pub player_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelQueueResponse {
+    pub player_id: String,
+    /// 原本不在佇列中時為 false
+    pub cancelled: bool,
+}
+
+#[async_trait]
+impl TypedHandler for CancelQueueHandler {
+    type Request = CancelQueueRequest;
+    type Response = CancelQueueResponse;
+
+    const ACTION: &'static str = "CancelQueue";
+    const RESULT: &'static str = "CancelQueueResult";
+
+    async fn handle(&self, ctx: &ConnectionContext, request: CancelQueueRequest) -> Result<CancelQueueResponse, HandlerError> {
+        let player_id = acting_player(ctx, request.player_id.as_deref())?;
+        let cancelled = self.matchmaker.cancel(&player_id, Instant::now());
+        Ok(CancelQueueResponse { player_id, cancelled })
+    }
+}
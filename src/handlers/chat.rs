@@ -0,0 +1,68 @@
+use super::{acting_player, HandlerError, TypedHandler};
+use crate::connection::ConnectionContext;
+use crate::connection::ConnectionRegistry;
+use crate::types::response::{ErrorCode, WsResponse};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+use async_trait::async_trait;
+
+/// 聊天訊息：轉發給同一場遊戲中的其他玩家
+pub struct ChatHandler {
+    registry: Arc<ConnectionRegistry>,
+}
+
+impl ChatHandler {
+    pub fn new(registry: Arc<ConnectionRegistry>) -> Self {
+        Self { registry }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatRequest {
+    /// 選填，必須與登入身分一致
+    pub player_id: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatResponse {
+    pub player_id: String,
+    pub delivered: usize,
+}
+
+#[async_trait]
+impl TypedHandler for ChatHandler {
+    type Request = ChatRequest;
+    type Response = ChatResponse;
+
+    const ACTION: &'static str = "Chat";
+    const RESULT: &'static str = "ChatResult";
+
+    async fn handle(&self, ctx: &ConnectionContext, request: ChatRequest) -> Result<ChatResponse, HandlerError> {
+        let player_id = acting_player(ctx, request.player_id.as_deref())?;
+
+        // 只能在自己所在的遊戲中發言
+        let game_id = self
+            .registry
+            .game_of(&player_id)
+            .ok_or_else(|| HandlerError::new(ErrorCode::NotInGame, "player is not in a game"))?;
+
+        let push = WsResponse::new("ChatMessage", json!({
+            "gameId": game_id,
+            "playerId": player_id,
+            "message": request.message
+        }));
+        let delivered = self
+            .registry
+            .broadcast_game(&game_id, &push, Some(&player_id));
+
+        Ok(ChatResponse {
+            player_id,
+            delivered,
+        })
+    }
+}
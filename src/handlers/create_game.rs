@@ -1,36 +1,55 @@
-use super::MessageHandler;
-use crate::types::response::{WsRequest, WsResponse};
-use serde_json::json;
-use rand::{distributions::Alphanumeric, Rng};
+use super::{acting_player, HandlerError, TypedHandler};
+use crate::connection::{ConnectionContext, ConnectionRegistry};
+use crate::game::GameRegistry;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 use async_trait::async_trait;
 
-pub struct CreateGameHandler;
+pub struct CreateGameHandler {
+    registry: Arc<ConnectionRegistry>,
+    games: Arc<GameRegistry>,
+}
 
-#[async_trait]
-impl MessageHandler for CreateGameHandler {
-    async fn handle(&self, val: &WsRequest) -> WsResponse {
-        // 產生隨機 playerId
-        let rand_string: String = rand::thread_rng()
-            .sample_iter(&Alphanumeric)
-            .take(8)
-            .map(char::from)
-            .collect();
-        let player_id = format!("p{}", rand_string);
-
-        let seed = val.payload.get("seed").and_then(|v| v.as_i64()).unwrap_or(0);
-
-        // TODO: 這裡可以初始化遊戲狀態、建立房間等
-
-        WsResponse {
-            type_: "CreateGame".to_string(),
-            payload: Some(json!({
-                "playerId": "p1",
-                "seed": seed
-            })),
-        }
+impl CreateGameHandler {
+    pub fn new(registry: Arc<ConnectionRegistry>, games: Arc<GameRegistry>) -> Self {
+        Self { registry, games }
     }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateGameRequest {
+    #[serde(default)]
+    pub seed: i64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateGameResponse {
+    pub game_id: String,
+    pub player_id: String,
+    pub seed: i64,
+}
+
+#[async_trait]
+impl TypedHandler for CreateGameHandler {
+    type Request = CreateGameRequest;
+    type Response = CreateGameResponse;
+
+    const ACTION: &'static str = "CreateGame";
+    const RESULT: &'static str = "CreateGame";
+
+    async fn handle(&self, ctx: &ConnectionContext, request: CreateGameRequest) -> Result<CreateGameResponse, HandlerError> {
+        let player_id = acting_player(ctx, None)?;
+
+        let game_id = self.games.create(request.seed, vec![player_id.clone()]).id;
+        self.registry.bind(&player_id, Some(&game_id), ctx.handle());
 
-    fn can_handle(&self, action: &str) -> bool {
-        action == "CreateGame"
+        Ok(CreateGameResponse {
+            game_id,
+            player_id,
+            seed: request.seed,
+        })
     }
 }
@@ -0,0 +1,41 @@
+use super::{acting_player, HandlerError, TypedHandler};
+use crate::connection::ConnectionContext;
+use crate::matchmaking::LobbyManager;
+use crate::game::RulesPatch;
+use crate::matchmaking::LobbyView;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use std::sync::Arc;
+use async_trait::async_trait;
+
+/// 建立自訂房間，回傳邀請碼與房間規則
+pub struct CreateLobbyHandler {
+    lobbies: Arc<LobbyManager>,
+}
+
+impl CreateLobbyHandler {
+    pub fn new(lobbies: Arc<LobbyManager>) -> Self {
+        Self { lobbies }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateLobbyRequest {
+    /// 選填，相對於伺服器預設規則的調整
+    pub rules: Option<RulesPatch>,
+}
+
+#[async_trait]
+impl TypedHandler for CreateLobbyHandler {
+    type Request = CreateLobbyRequest;
+    type Response = LobbyView;
+
+    const ACTION: &'static str = "CreateLobby";
+    const RESULT: &'static str = "CreateLobbyResult";
+
+    async fn handle(&self, ctx: &ConnectionContext, request: CreateLobbyRequest) -> Result<LobbyView, HandlerError> {
+        let player_id = acting_player(ctx, None)?;
+        Ok(self.lobbies.create(&player_id, request.rules)?)
+    }
+}
@@ -1,4 +1,5 @@
 use super::MessageHandler;
+use crate::connection::ConnectionContext;
 use crate::types::response::{WsRequest, WsResponse};
 use async_trait::async_trait;
 
@@ -6,11 +7,19 @@ pub struct EchoHandler;
 
 #[async_trait]
 impl MessageHandler for EchoHandler {
-    async fn handle(&self, val: &WsRequest) -> WsResponse {
+    async fn handle(&self, _ctx: &ConnectionContext, val: &WsRequest) -> WsResponse {
         WsResponse::ok(Some(val.payload.clone()))
     }
 
-    fn can_handle(&self, action: &str) -> bool {
-        action == "echo"
+    fn action(&self) -> &'static str {
+        "echo"
+    }
+
+    fn public(&self) -> bool {
+        true
+    }
+
+    fn read_only(&self) -> bool {
+        true
     }
 }
@@ -0,0 +1,69 @@
+use super::{acting_player, HandlerError, TypedHandler};
+use crate::connection::ConnectionContext;
+use crate::control::StateSync;
+use crate::player::PlayerManager;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use async_trait::async_trait;
+
+/// 將道具欄的道具裝備到單位上（最多 3 個，兩個基礎道具自動合成）
+pub struct EquipItemHandler {
+    player_manager: Arc<PlayerManager>,
+    sync: Arc<StateSync>,
+}
+
+impl EquipItemHandler {
+    pub fn new(player_manager: Arc<PlayerManager>, sync: Arc<StateSync>) -> Self {
+        Self { player_manager, sync }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EquipItemRequest {
+    /// 選填，必須與登入身分一致
+    pub player_id: Option<String>,
+    pub item_id: String,
+    pub unit_id: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EquipItemResponse {
+    pub player_id: String,
+    pub unit_id: String,
+    /// 裝備後單位身上的道具
+    pub items: Vec<String>,
+    pub version: u64,
+}
+
+#[async_trait]
+impl TypedHandler for EquipItemHandler {
+    type Request = EquipItemRequest;
+    type Response = EquipItemResponse;
+
+    const ACTION: &'static str = "EquipItem";
+    const RESULT: &'static str = "EquipItemResult";
+
+    async fn handle(&self, ctx: &ConnectionContext, request: EquipItemRequest) -> Result<EquipItemResponse, HandlerError> {
+        let player_id = acting_player(ctx, request.player_id.as_deref())?;
+
+        let player = self.player_manager.equip_item(&player_id, &request.item_id, &request.unit_id)?;
+        let version = self.sync.publish(&player_id).unwrap_or_default();
+
+        let items = player
+            .bench
+            .iter()
+            .find(|u| u.id == request.unit_id)
+            .map(|u| u.items.clone())
+            .or_else(|| player.board.iter().find(|u| u.id == request.unit_id).map(|u| u.items.clone()))
+            .unwrap_or_default();
+        Ok(EquipItemResponse {
+            player_id,
+            unit_id: request.unit_id,
+            items,
+            version,
+        })
+    }
+}
@@ -1,64 +1,62 @@
 // GameStateMessageHandler 處理 WebSocket 訊息
 
-use super::MessageHandler;
-use crate::types::response::{WsRequest, WsResponse};
-use crate::control::game_state_control::{GameStateControl};
-use crate::player::PlayerManager;
+use super::{acting_player, HandlerError, TypedHandler};
+use crate::connection::{ConnectionContext, ConnectionRegistry};
+use crate::control::StateSync;
+use crate::player::PlayerError;
+use crate::types::game_state::GameState;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use serde_json::json;
 use async_trait::async_trait;
-use tokio::net::TcpStream;
 
 
 pub struct GameStateMessageHandler {
-    player_manager: Arc<PlayerManager>,
+    sync: Arc<StateSync>,
+    registry: Arc<ConnectionRegistry>,
 }
 
 impl GameStateMessageHandler {
-    pub fn new(player_manager: Arc<PlayerManager>) -> Self {
-        Self { player_manager }
+    pub fn new(sync: Arc<StateSync>, registry: Arc<ConnectionRegistry>) -> Self {
+        Self { sync, registry }
     }
 }
 
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct GetGameStateRequest {
+    /// 選填，必須與登入身分一致
+    pub player_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetGameStateResponse {
+    pub game_id: Option<String>,
+    pub player_id: String,
+    pub version: u64,
+    pub state: GameState,
+}
+
 #[async_trait]
-impl MessageHandler for GameStateMessageHandler {
-    async fn handle(
-        &self,
-        message: &WsRequest,
-    ) -> WsResponse {
-		//println!("進入MessageHandler");
-        if message.type_ == "GetGameState" {
-            // 步驟1. 從 websocket 拿到 playerId
-            let player_id = message.payload.get("playerId").and_then(|v| v.as_str()).unwrap_or("");
+impl TypedHandler for GameStateMessageHandler {
+    type Request = GetGameStateRequest;
+    type Response = GetGameStateResponse;
+
+    const ACTION: &'static str = "GetGameState";
+    const RESULT: &'static str = "GetGameStateResult";
+
+    async fn handle(&self, ctx: &ConnectionContext, request: GetGameStateRequest) -> Result<GetGameStateResponse, HandlerError> {
+        let player_id = acting_player(ctx, request.player_id.as_deref())?;
 
-            // 2. 呼叫 GameStateControl
-            // 3. 包成 JSON 回傳
-			let game_state = GameStateControl::handle(player_id).await;
-			println!("後端送出 WebSocket 訊息: {}", game_state);
-			return WsResponse {
-				type_: "GetGameStateResult".to_string(),
-				payload: Some(json!({
-					"success": true,
-					"gameId": "待更新",
-                    "playerId": player_id,
-                    "state": game_state
-				})),
-			}
-        }
-		else {
-			let player_id = message.payload.get("playerId").and_then(|v| v.as_str()).unwrap_or("");
-			return WsResponse {
-				type_: "GetGameStateResult".to_string(),
-				payload: Some(json!({
-					"success": false,
-					"playerId": player_id,
-					"reason":"後端message型別不正確"
-				})),
-    	}
-		}
-	}
+        // 取得目前狀態與版本（之後的變更會以 StateDelta 推播）
+        let (version, state) = self.sync.current(&player_id).ok_or(PlayerError::NotFound)?;
 
-    fn can_handle(&self, action: &str) -> bool {
-        action == "GetGameState"
+        Ok(GetGameStateResponse {
+            game_id: self.registry.game_of(&player_id),
+            player_id,
+            version,
+            state,
+        })
     }
 }
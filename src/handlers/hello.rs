@@ -0,0 +1,80 @@
+use super::{HandlerError, TypedHandler};
+use crate::connection::{ConnectionContext, Handshake};
+use crate::types::codec::Encoding;
+use crate::types::protocol::{self, Feature, MIN_PROTOCOL_VERSION, PROTOCOL_VERSION};
+use crate::types::response::ErrorCode;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use async_trait::async_trait;
+
+/// 協定版本不相容時關閉連線使用的 close code
+pub const UPGRADE_REQUIRED_CLOSE_CODE: CloseCode = CloseCode::Library(4001);
+
+/// 連線建立後的第一則訊息：交換協定版本與功能，並協商之後使用的編碼
+/// 版本不相容時回傳 `UPGRADE_REQUIRED` 並關閉連線
+pub struct HelloHandler;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct HelloRequest {
+    /// 客戶端實作的協定版本
+    pub protocol_version: u32,
+    /// 客戶端支援的功能，未提供時視為全部接受
+    pub features: Option<Vec<String>>,
+    /// 之後的訊息要使用的編碼，未指定則維持目前的編碼
+    pub encoding: Option<Encoding>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HelloResponse {
+    pub protocol_version: u32,
+    pub features: Vec<Feature>,
+    pub server_time: u64,
+    pub encoding: Encoding,
+}
+
+#[async_trait]
+impl TypedHandler for HelloHandler {
+    type Request = HelloRequest;
+    type Response = HelloResponse;
+
+    const ACTION: &'static str = "Hello";
+    const RESULT: &'static str = "HelloResult";
+    const PUBLIC: bool = true;
+    const READ_ONLY: bool = true;
+
+    async fn handle(&self, ctx: &ConnectionContext, request: HelloRequest) -> Result<HelloResponse, HandlerError> {
+        if !protocol::is_supported(request.protocol_version) {
+            ctx.close_after_reply(UPGRADE_REQUIRED_CLOSE_CODE, "upgrade required");
+            return Err(HandlerError::new(
+                ErrorCode::UpgradeRequired,
+                format!(
+                    "protocol version {} is not supported, server accepts {}..={}",
+                    request.protocol_version, MIN_PROTOCOL_VERSION, PROTOCOL_VERSION
+                ),
+            ));
+        }
+        if ctx.handshake().is_some() {
+            return Err(HandlerError::new(ErrorCode::Forbidden, "handshake already completed"));
+        }
+
+        let features = protocol::negotiate_features(request.features.as_deref());
+        ctx.complete_handshake(Handshake {
+            protocol_version: request.protocol_version,
+            features: features.clone(),
+        });
+
+        // HelloResult 仍以原本的編碼送出，之後才切換
+        let encoding = request.encoding.unwrap_or(ctx.encoding());
+        ctx.request_encoding(encoding);
+
+        Ok(HelloResponse {
+            protocol_version: PROTOCOL_VERSION,
+            features,
+            server_time: protocol::server_time_millis(),
+            encoding,
+        })
+    }
+}
@@ -0,0 +1,40 @@
+use super::{acting_player, HandlerError, TypedHandler};
+use crate::connection::ConnectionContext;
+use crate::matchmaking::LobbyManager;
+use crate::matchmaking::LobbyView;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use std::sync::Arc;
+use async_trait::async_trait;
+
+/// 以邀請碼加入自訂房間
+pub struct JoinLobbyHandler {
+    lobbies: Arc<LobbyManager>,
+}
+
+impl JoinLobbyHandler {
+    pub fn new(lobbies: Arc<LobbyManager>) -> Self {
+        Self { lobbies }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct JoinLobbyRequest {
+    /// 邀請碼，不分大小寫
+    pub code: String,
+}
+
+#[async_trait]
+impl TypedHandler for JoinLobbyHandler {
+    type Request = JoinLobbyRequest;
+    type Response = LobbyView;
+
+    const ACTION: &'static str = "JoinLobby";
+    const RESULT: &'static str = "JoinLobbyResult";
+
+    async fn handle(&self, ctx: &ConnectionContext, request: JoinLobbyRequest) -> Result<LobbyView, HandlerError> {
+        let player_id = acting_player(ctx, None)?;
+        Ok(self.lobbies.join(&player_id, &request.code)?)
+    }
+}
@@ -0,0 +1,40 @@
+use super::{acting_player, HandlerError, TypedHandler};
+use crate::connection::ConnectionContext;
+use crate::matchmaking::LobbyManager;
+use crate::matchmaking::LobbyView;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use std::sync::Arc;
+use async_trait::async_trait;
+
+/// 房主把成員踢出自訂房間
+pub struct KickFromLobbyHandler {
+    lobbies: Arc<LobbyManager>,
+}
+
+impl KickFromLobbyHandler {
+    pub fn new(lobbies: Arc<LobbyManager>) -> Self {
+        Self { lobbies }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct KickFromLobbyRequest {
+    /// 要踢出的成員
+    pub player_id: String,
+}
+
+#[async_trait]
+impl TypedHandler for KickFromLobbyHandler {
+    type Request = KickFromLobbyRequest;
+    type Response = LobbyView;
+
+    const ACTION: &'static str = "KickFromLobby";
+    const RESULT: &'static str = "KickFromLobbyResult";
+
+    async fn handle(&self, ctx: &ConnectionContext, request: KickFromLobbyRequest) -> Result<LobbyView, HandlerError> {
+        let host = acting_player(ctx, None)?;
+        Ok(self.lobbies.kick(&host, &request.player_id)?)
+    }
+}
@@ -0,0 +1,43 @@
+use super::{acting_player, HandlerError, TypedHandler};
+use crate::connection::ConnectionContext;
+use crate::matchmaking::LobbyManager;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use async_trait::async_trait;
+
+/// 離開目前的自訂房間；房主離開時由下一位成員接手
+pub struct LeaveLobbyHandler {
+    lobbies: Arc<LobbyManager>,
+}
+
+impl LeaveLobbyHandler {
+    pub fn new(lobbies: Arc<LobbyManager>) -> Self {
+        Self { lobbies }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaveLobbyRequest {}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaveLobbyResponse {
+    pub code: String,
+}
+
+#[async_trait]
+impl TypedHandler for LeaveLobbyHandler {
+    type Request = LeaveLobbyRequest;
+    type Response = LeaveLobbyResponse;
+
+    const ACTION: &'static str = "LeaveLobby";
+    const RESULT: &'static str = "LeaveLobbyResult";
+
+    async fn handle(&self, ctx: &ConnectionContext, _request: LeaveLobbyRequest) -> Result<LeaveLobbyResponse, HandlerError> {
+        let player_id = acting_player(ctx, None)?;
+        let code = self.lobbies.leave(&player_id)?;
+        Ok(LeaveLobbyResponse { code })
+    }
+}
@@ -0,0 +1,95 @@
+use super::{HandlerError, TypedHandler};
+use crate::connection::{ConnectionContext, PresenceMonitor, SessionSigner};
+use crate::control::StateSync;
+use crate::player::PlayerManager;
+use crate::types::response::ErrorCode;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use rand::{distributions::Alphanumeric, Rng};
+use std::sync::Arc;
+use async_trait::async_trait;
+
+/// 登入：將此連線綁定到玩家並簽發 session token
+/// 帶 sessionToken 時沿用原本的玩家，否則建立新玩家
+pub struct LoginHandler {
+    player_manager: Arc<PlayerManager>,
+    presence: Arc<PresenceMonitor>,
+    signer: Arc<SessionSigner>,
+    sync: Arc<StateSync>,
+}
+
+impl LoginHandler {
+    pub fn new(
+        player_manager: Arc<PlayerManager>,
+        presence: Arc<PresenceMonitor>,
+        signer: Arc<SessionSigner>,
+        sync: Arc<StateSync>,
+    ) -> Self {
+        Self { player_manager, presence, signer, sync }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LoginRequest {
+    /// 先前登入取得的 token
+    pub session_token: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoginResponse {
+    pub player_id: String,
+    pub session_token: String,
+}
+
+#[async_trait]
+impl TypedHandler for LoginHandler {
+    type Request = LoginRequest;
+    type Response = LoginResponse;
+
+    const ACTION: &'static str = "Login";
+    const RESULT: &'static str = "LoginResult";
+    const PUBLIC: bool = true;
+
+    async fn handle(&self, ctx: &ConnectionContext, request: LoginRequest) -> Result<LoginResponse, HandlerError> {
+        let player_id = match &request.session_token {
+            Some(token) => self
+                .signer
+                .verify(token)
+                .ok_or_else(|| HandlerError::new(ErrorCode::InvalidToken, "invalid or expired session token"))?,
+            None => {
+                // 產生隨機 playerId
+                let rand_string: String = rand::thread_rng()
+                    .sample_iter(&Alphanumeric)
+                    .take(8)
+                    .map(char::from)
+                    .collect();
+                format!("p{}", rand_string)
+            }
+        };
+
+        // 同一條連線不能切換成另一個玩家
+        if let Some(current) = ctx.player_id() {
+            if current != player_id {
+                return Err(HandlerError::new(
+                    ErrorCode::Forbidden,
+                    format!("connection is already logged in as {}", current),
+                ));
+            }
+        }
+
+        if self.player_manager.get_player(&player_id).is_none() {
+            self.player_manager.create_player(&player_id);
+        }
+        ctx.authenticate(&player_id);
+        self.presence.attach(ctx);
+        // 加入時送一次完整快照，之後只推播差異
+        self.sync.send_snapshot(&player_id);
+
+        Ok(LoginResponse {
+            session_token: self.signer.issue(&player_id),
+            player_id,
+        })
+    }
+}
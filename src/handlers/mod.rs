@@ -1,5 +1,7 @@
+use crate::connection::ConnectionContext;
 use crate::types::response::{WsRequest, WsResponse};
 use async_trait::async_trait;
+use serde_json::{json, Value};
 
 
 // // 同步
@@ -11,8 +13,25 @@ use async_trait::async_trait;
 // 非同步
 #[async_trait]
 pub trait MessageHandler: Send + Sync {
-    async fn handle(&self, val: &WsRequest) -> WsResponse;
-    fn can_handle(&self, action: &str) -> bool;
+    async fn handle(&self, ctx: &ConnectionContext, val: &WsRequest) -> WsResponse;
+
+    /// 對應的 action 名稱，Router 以此為索引
+    fn action(&self) -> &'static str;
+
+    /// payload 的 JSON Schema，供 ListActions 回傳給客戶端開發者
+    fn payload_schema(&self) -> Value {
+        json!({ "type": "object" })
+    }
+
+    /// 未登入的連線也能使用
+    fn public(&self) -> bool {
+        false
+    }
+
+    /// 不會改變遊戲狀態，觀戰中的連線也能使用
+    fn read_only(&self) -> bool {
+        false
+    }
 }
 
 pub mod echo;
@@ -22,6 +41,30 @@ pub mod buy_xp;
 pub mod shop;
 pub mod create_game;
 pub mod game_state_message_handler;
+pub mod chat;
+pub mod typed;
+pub mod login;
+pub mod resume;
+pub mod hello;
+pub mod buy_unit;
+pub mod sell_unit;
+pub mod equip_item;
+pub mod move_unit;
+pub mod resync;
+pub mod queue_for_match;
+pub mod cancel_queue;
+pub mod accept_match;
+pub mod create_lobby;
+pub mod join_lobby;
+pub mod leave_lobby;
+pub mod kick_from_lobby;
+pub mod set_lobby_rules;
+pub mod start_lobby;
+pub mod pick_carousel;
+pub mod pick_augment;
+pub mod reroll_augments;
+pub mod spectate;
+pub mod stop_spectating;
 
 
 pub use echo::EchoHandler;
@@ -30,4 +73,28 @@ pub use unknown::UnknownHandler;
 pub use buy_xp::BuyXPHandler;
 pub use shop::ShopHandler;
 pub use create_game::CreateGameHandler;
-pub use game_state_message_handler::GameStateMessageHandler;
\ No newline at end of file
+pub use game_state_message_handler::GameStateMessageHandler;
+pub use chat::ChatHandler;
+pub use typed::{acting_player, HandlerError, Typed, TypedHandler};
+pub use login::LoginHandler;
+pub use resume::ResumeHandler;
+pub use hello::HelloHandler;
+pub use buy_unit::BuyUnitHandler;
+pub use sell_unit::SellUnitHandler;
+pub use equip_item::EquipItemHandler;
+pub use move_unit::MoveUnitHandler;
+pub use resync::ResyncHandler;
+pub use queue_for_match::QueueForMatchHandler;
+pub use cancel_queue::CancelQueueHandler;
+pub use accept_match::AcceptMatchHandler;
+pub use create_lobby::CreateLobbyHandler;
+pub use join_lobby::JoinLobbyHandler;
+pub use leave_lobby::LeaveLobbyHandler;
+pub use kick_from_lobby::KickFromLobbyHandler;
+pub use set_lobby_rules::SetLobbyRulesHandler;
+pub use start_lobby::StartLobbyHandler;
+pub use pick_carousel::PickCarouselHandler;
+pub use pick_augment::PickAugmentHandler;
+pub use reroll_augments::RerollAugmentsHandler;
+pub use spectate::SpectateHandler;
+pub use stop_spectating::StopSpectatingHandler;
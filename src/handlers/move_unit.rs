@@ -0,0 +1,55 @@
+use super::{acting_player, HandlerError, TypedHandler};
+use crate::connection::ConnectionContext;
+use crate::control::StateSync;
+use crate::player::PlayerManager;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use async_trait::async_trait;
+
+/// 在備戰區與棋盤之間移動單位
+pub struct MoveUnitHandler {
+    player_manager: Arc<PlayerManager>,
+    sync: Arc<StateSync>,
+}
+
+impl MoveUnitHandler {
+    pub fn new(player_manager: Arc<PlayerManager>, sync: Arc<StateSync>) -> Self {
+        Self { player_manager, sync }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MoveUnitRequest {
+    /// 選填，必須與登入身分一致
+    pub player_id: Option<String>,
+    pub unit_id: String,
+    /// 棋盤座標 [x, y]；省略時收回備戰區
+    pub position: Option<[u32; 2]>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MoveUnitResponse {
+    pub player_id: String,
+    pub version: u64,
+}
+
+#[async_trait]
+impl TypedHandler for MoveUnitHandler {
+    type Request = MoveUnitRequest;
+    type Response = MoveUnitResponse;
+
+    const ACTION: &'static str = "MoveUnit";
+    const RESULT: &'static str = "MoveUnitResult";
+
+    async fn handle(&self, ctx: &ConnectionContext, request: MoveUnitRequest) -> Result<MoveUnitResponse, HandlerError> {
+        let player_id = acting_player(ctx, request.player_id.as_deref())?;
+
+        self.player_manager.move_unit(&player_id, &request.unit_id, request.position)?;
+        let version = self.sync.publish(&player_id).unwrap_or_default();
+
+        Ok(MoveUnitResponse { player_id, version })
+    }
+}
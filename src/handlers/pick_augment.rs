@@ -0,0 +1,56 @@
+use super::{acting_player, HandlerError, TypedHandler};
+use crate::connection::ConnectionContext;
+use crate::control::StateSync;
+use crate::game::augments::{find_augment, Augment};
+use crate::player::PlayerManager;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use async_trait::async_trait;
+
+/// 從 `AugmentOffered` 的選項中選擇一個強化符文
+pub struct PickAugmentHandler {
+    player_manager: Arc<PlayerManager>,
+    sync: Arc<StateSync>,
+}
+
+impl PickAugmentHandler {
+    pub fn new(player_manager: Arc<PlayerManager>, sync: Arc<StateSync>) -> Self {
+        Self { player_manager, sync }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PickAugmentRequest {
+    pub augment_id: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PickAugmentResponse {
+    pub augment: Option<Augment>,
+    /// 選擇後擁有的所有符文
+    pub augments: Vec<String>,
+    pub version: u64,
+}
+
+#[async_trait]
+impl TypedHandler for PickAugmentHandler {
+    type Request = PickAugmentRequest;
+    type Response = PickAugmentResponse;
+
+    const ACTION: &'static str = "PickAugment";
+    const RESULT: &'static str = "PickAugmentResult";
+
+    async fn handle(&self, ctx: &ConnectionContext, request: PickAugmentRequest) -> Result<PickAugmentResponse, HandlerError> {
+        let player_id = acting_player(ctx, None)?;
+        let player = self.player_manager.pick_augment(&player_id, &request.augment_id)?;
+        let version = self.sync.publish(&player_id).unwrap_or_default();
+        Ok(PickAugmentResponse {
+            augment: find_augment(&request.augment_id),
+            augments: player.augments,
+            version,
+        })
+    }
+}
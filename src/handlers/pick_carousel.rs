@@ -0,0 +1,52 @@
+use super::{acting_player, HandlerError, TypedHandler};
+use crate::connection::ConnectionContext;
+use crate::game::RoundLoop;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use async_trait::async_trait;
+
+/// 選秀時挑選一組棋子與道具；只有輪到的那一波玩家可以挑
+pub struct PickCarouselHandler {
+    rounds: Arc<RoundLoop>,
+}
+
+impl PickCarouselHandler {
+    pub fn new(rounds: Arc<RoundLoop>) -> Self {
+        Self { rounds }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PickCarouselRequest {
+    /// `CarouselStarted.slots` 中的索引
+    pub slot_index: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PickCarouselResponse {
+    pub slot_index: usize,
+    pub chess: String,
+    pub item: String,
+}
+
+#[async_trait]
+impl TypedHandler for PickCarouselHandler {
+    type Request = PickCarouselRequest;
+    type Response = PickCarouselResponse;
+
+    const ACTION: &'static str = "PickCarousel";
+    const RESULT: &'static str = "PickCarouselResult";
+
+    async fn handle(&self, ctx: &ConnectionContext, request: PickCarouselRequest) -> Result<PickCarouselResponse, HandlerError> {
+        let player_id = acting_player(ctx, None)?;
+        let slot = self.rounds.pick_carousel(&player_id, request.slot_index)?;
+        Ok(PickCarouselResponse {
+            slot_index: request.slot_index,
+            chess: slot.chess,
+            item: slot.item,
+        })
+    }
+}
@@ -1,4 +1,5 @@
 use super::MessageHandler;
+use crate::connection::ConnectionContext;
 use crate::types::response::{WsRequest, WsResponse};
 use async_trait::async_trait;
 
@@ -6,11 +7,19 @@ pub struct PingHandler;
 
 #[async_trait]
 impl MessageHandler for PingHandler {
-    async fn handle(&self, _val: &WsRequest) -> WsResponse {
+    async fn handle(&self, _ctx: &ConnectionContext, _val: &WsRequest) -> WsResponse {
         WsResponse::ok(Some(serde_json::json!({ "pong": true })))
     }
 
-    fn can_handle(&self, action: &str) -> bool {
-        action == "ping"
+    fn action(&self) -> &'static str {
+        "ping"
+    }
+
+    fn public(&self) -> bool {
+        true
+    }
+
+    fn read_only(&self) -> bool {
+        true
     }
 }
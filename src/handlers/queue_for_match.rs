@@ -0,0 +1,49 @@
+use super::{acting_player, HandlerError, TypedHandler};
+use crate::connection::ConnectionContext;
+use crate::matchmaking::Matchmaker;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Instant;
+use async_trait::async_trait;
+
+/// 加入配對佇列，湊滿一桌後推播 `MatchFound`
+pub struct QueueForMatchHandler {
+    matchmaker: Arc<Matchmaker>,
+}
+
+impl QueueForMatchHandler {
+    pub fn new(matchmaker: Arc<Matchmaker>) -> Self {
+        Self { matchmaker }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueForMatchRequest {
+    /// 選填，必須與登入身分一致
+    pub player_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueForMatchResponse {
+    pub player_id: String,
+    /// 加入後的排隊人數
+    pub queue_size: usize,
+}
+
+#[async_trait]
+impl TypedHandler for QueueForMatchHandler {
+    type Request = QueueForMatchRequest;
+    type Response = QueueForMatchResponse;
+
+    const ACTION: &'static str = "QueueForMatch";
+    const RESULT: &'static str = "QueueForMatchResult";
+
+    async fn handle(&self, ctx: &ConnectionContext, request: QueueForMatchRequest) -> Result<QueueForMatchResponse, HandlerError> {
+        let player_id = acting_player(ctx, request.player_id.as_deref())?;
+        let queue_size = self.matchmaker.enqueue(&player_id, Instant::now())?;
+        Ok(QueueForMatchResponse { player_id, queue_size })
+    }
+}
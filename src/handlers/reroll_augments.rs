@@ -0,0 +1,54 @@
+use super::{acting_player, HandlerError, TypedHandler};
+use crate::connection::ConnectionContext;
+use crate::control::StateSync;
+use crate::game::augments::{find_augment, Augment};
+use crate::player::PlayerManager;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use async_trait::async_trait;
+
+/// 以同一階級重抽符文選項（每次選擇限定次數）
+pub struct RerollAugmentsHandler {
+    player_manager: Arc<PlayerManager>,
+    sync: Arc<StateSync>,
+}
+
+impl RerollAugmentsHandler {
+    pub fn new(player_manager: Arc<PlayerManager>, sync: Arc<StateSync>) -> Self {
+        Self { player_manager, sync }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RerollAugmentsRequest {}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RerollAugmentsResponse {
+    pub options: Vec<Augment>,
+    /// 剩餘的重抽次數
+    pub rerolls: u32,
+    pub version: u64,
+}
+
+#[async_trait]
+impl TypedHandler for RerollAugmentsHandler {
+    type Request = RerollAugmentsRequest;
+    type Response = RerollAugmentsResponse;
+
+    const ACTION: &'static str = "RerollAugments";
+    const RESULT: &'static str = "RerollAugmentsResult";
+
+    async fn handle(&self, ctx: &ConnectionContext, _request: RerollAugmentsRequest) -> Result<RerollAugmentsResponse, HandlerError> {
+        let player_id = acting_player(ctx, None)?;
+        let offer = self.player_manager.reroll_augments(&player_id)?;
+        let version = self.sync.publish(&player_id).unwrap_or_default();
+        Ok(RerollAugmentsResponse {
+            options: offer.options.iter().filter_map(|id| find_augment(id)).collect(),
+            rerolls: offer.rerolls,
+            version,
+        })
+    }
+}
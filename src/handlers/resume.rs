@@ -0,0 +1,87 @@
+use super::{HandlerError, TypedHandler};
+use crate::connection::{ConnectionContext, PresenceMonitor, SessionSigner};
+use crate::player::{PlayerError, PlayerManager};
+use crate::types::response::{ErrorCode, WsResponse};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use async_trait::async_trait;
+
+/// 斷線重連：以 session token 將新的 socket 接回原本的玩家，並補送遺漏的推播
+pub struct ResumeHandler {
+    player_manager: Arc<PlayerManager>,
+    presence: Arc<PresenceMonitor>,
+    signer: Arc<SessionSigner>,
+}
+
+impl ResumeHandler {
+    pub fn new(
+        player_manager: Arc<PlayerManager>,
+        presence: Arc<PresenceMonitor>,
+        signer: Arc<SessionSigner>,
+    ) -> Self {
+        Self { player_manager, presence, signer }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ResumeRequest {
+    pub session_token: String,
+    /// 客戶端最後收到的推播序號
+    #[serde(default)]
+    pub last_seq: u64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResumeResponse {
+    pub player_id: String,
+    pub session_token: String,
+    /// lastSeq 之後遺漏的推播（依 seq 排序）
+    pub missed: Vec<WsResponse>,
+    /// 為 false 時代表部分推播已不在緩衝區，客戶端應重新取得完整狀態
+    pub complete: bool,
+}
+
+#[async_trait]
+impl TypedHandler for ResumeHandler {
+    type Request = ResumeRequest;
+    type Response = ResumeResponse;
+
+    const ACTION: &'static str = "Resume";
+    const RESULT: &'static str = "ResumeResult";
+    const PUBLIC: bool = true;
+
+    async fn handle(&self, ctx: &ConnectionContext, request: ResumeRequest) -> Result<ResumeResponse, HandlerError> {
+        let player_id = self
+            .signer
+            .verify(&request.session_token)
+            .ok_or_else(|| HandlerError::new(ErrorCode::InvalidToken, "invalid or expired session token"))?;
+
+        if let Some(current) = ctx.player_id() {
+            if current != player_id {
+                return Err(HandlerError::new(
+                    ErrorCode::Forbidden,
+                    format!("connection is already logged in as {}", current),
+                ));
+            }
+        }
+        if self.player_manager.get_player(&player_id).is_none() {
+            return Err(PlayerError::NotFound.into());
+        }
+
+        ctx.authenticate(&player_id);
+        let replay = self
+            .presence
+            .resume(ctx, request.last_seq)
+            .ok_or_else(|| HandlerError::new(ErrorCode::Unauthenticated, "login required"))?;
+
+        Ok(ResumeResponse {
+            session_token: self.signer.issue(&player_id),
+            player_id,
+            missed: replay.missed,
+            complete: replay.complete,
+        })
+    }
+}
@@ -0,0 +1,56 @@
+use super::{acting_player, HandlerError, TypedHandler};
+use crate::connection::ConnectionContext;
+use crate::control::state_sync::{Resync, StateSync};
+use crate::player::PlayerError;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use async_trait::async_trait;
+
+/// 客戶端發現 StateDelta 版本不連續時，補回缺少的差異
+pub struct ResyncHandler {
+    sync: Arc<StateSync>,
+}
+
+impl ResyncHandler {
+    pub fn new(sync: Arc<StateSync>) -> Self {
+        Self { sync }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ResyncRequest {
+    /// 選填，必須與登入身分一致
+    pub player_id: Option<String>,
+    /// 客戶端目前套用到的版本
+    pub since_version: u64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResyncResponse {
+    pub player_id: String,
+    #[serde(flatten)]
+    pub resync: Resync,
+}
+
+#[async_trait]
+impl TypedHandler for ResyncHandler {
+    type Request = ResyncRequest;
+    type Response = ResyncResponse;
+
+    const ACTION: &'static str = "Resync";
+    const RESULT: &'static str = "ResyncResult";
+
+    async fn handle(&self, ctx: &ConnectionContext, request: ResyncRequest) -> Result<ResyncResponse, HandlerError> {
+        let player_id = acting_player(ctx, request.player_id.as_deref())?;
+
+        let resync = self
+            .sync
+            .resync(&player_id, request.since_version)
+            .ok_or(PlayerError::NotFound)?;
+
+        Ok(ResyncResponse { player_id, resync })
+    }
+}
@@ -0,0 +1,58 @@
+use super::{acting_player, HandlerError, TypedHandler};
+use crate::connection::ConnectionContext;
+use crate::control::StateSync;
+use crate::player::PlayerManager;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use async_trait::async_trait;
+
+/// 賣出備戰區或棋盤上的單位
+pub struct SellUnitHandler {
+    player_manager: Arc<PlayerManager>,
+    sync: Arc<StateSync>,
+}
+
+impl SellUnitHandler {
+    pub fn new(player_manager: Arc<PlayerManager>, sync: Arc<StateSync>) -> Self {
+        Self { player_manager, sync }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SellUnitRequest {
+    /// 選填，必須與登入身分一致
+    pub player_id: Option<String>,
+    pub unit_id: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SellUnitResponse {
+    pub player_id: String,
+    pub money: i32,
+    pub version: u64,
+}
+
+#[async_trait]
+impl TypedHandler for SellUnitHandler {
+    type Request = SellUnitRequest;
+    type Response = SellUnitResponse;
+
+    const ACTION: &'static str = "SellUnit";
+    const RESULT: &'static str = "SellUnitResult";
+
+    async fn handle(&self, ctx: &ConnectionContext, request: SellUnitRequest) -> Result<SellUnitResponse, HandlerError> {
+        let player_id = acting_player(ctx, request.player_id.as_deref())?;
+
+        let player = self.player_manager.sell_unit(&player_id, &request.unit_id)?;
+        let version = self.sync.publish(&player_id).unwrap_or_default();
+
+        Ok(SellUnitResponse {
+            player_id,
+            money: player.money,
+            version,
+        })
+    }
+}
@@ -0,0 +1,32 @@
+use super::{acting_player, HandlerError, TypedHandler};
+use crate::connection::ConnectionContext;
+use crate::matchmaking::LobbyManager;
+use crate::game::RulesPatch;
+use crate::matchmaking::LobbyView;
+use std::sync::Arc;
+use async_trait::async_trait;
+
+/// 房主調整自訂房間的規則，只需帶要改的欄位
+pub struct SetLobbyRulesHandler {
+    lobbies: Arc<LobbyManager>,
+}
+
+impl SetLobbyRulesHandler {
+    pub fn new(lobbies: Arc<LobbyManager>) -> Self {
+        Self { lobbies }
+    }
+}
+
+#[async_trait]
+impl TypedHandler for SetLobbyRulesHandler {
+    type Request = RulesPatch;
+    type Response = LobbyView;
+
+    const ACTION: &'static str = "SetLobbyRules";
+    const RESULT: &'static str = "SetLobbyRulesResult";
+
+    async fn handle(&self, ctx: &ConnectionContext, request: RulesPatch) -> Result<LobbyView, HandlerError> {
+        let host = acting_player(ctx, None)?;
+        Ok(self.lobbies.set_rules(&host, request)?)
+    }
+}
@@ -1,73 +1,60 @@
-use super::MessageHandler;
-use crate::types::response::{WsRequest, WsResponse};
+use super::{acting_player, HandlerError, TypedHandler};
+use crate::connection::ConnectionContext;
+use crate::control::StateSync;
 use crate::player::PlayerManager;
-use serde_json::json;
-use rand::seq::SliceRandom;
-use rand::thread_rng;
+use crate::types::game_state::ShopUnit;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use async_trait::async_trait;
 
 pub struct ShopHandler {
     player_manager: Arc<PlayerManager>,
+    sync: Arc<StateSync>,
 }
 
 impl ShopHandler {
-    pub fn new(player_manager: Arc<PlayerManager>) -> Self {
-        Self { player_manager }
+    pub fn new(player_manager: Arc<PlayerManager>, sync: Arc<StateSync>) -> Self {
+        Self { player_manager, sync }
     }
 }
 
-const ALL_CHESS: &[&str] = &[
-    "Mage", "Tank", "Knight", "Priest", "Hunter", "Assassin", "Archer", "Berserker",
-    "Paladin", "Warlock", "Necromancer", "Druid", "Shaman", "Blademaster", "Sniper",
-    "Engineer", "Beastmaster", "Phantom", "Guardian", "Elemental"
-];
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RefreshShopRequest {
+    /// 選填，必須與登入身分一致
+    pub player_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RefreshShopResponse {
+    pub player_id: String,
+    pub shop: Vec<Option<ShopUnit>>,
+    pub money: i32,
+    pub version: u64,
+}
 
 #[async_trait]
-impl MessageHandler for ShopHandler {
-    async fn handle(&self, val: &WsRequest) -> WsResponse {
-        // 解析 playerId
-        let player_id = match val.payload.get("playerId") {
-            Some(id) => match id.as_str() {
-                Some(id_str) => id_str,
-                None => return WsResponse::error("invalid playerId format".to_string()),
-            },
-            None => return WsResponse::error("missing playerId".to_string()),
-        };
+impl TypedHandler for ShopHandler {
+    type Request = RefreshShopRequest;
+    type Response = RefreshShopResponse;
 
-        // 嘗試扣除金錢
-        match self.player_manager.refresh_shop(player_id) {
-            Ok(new_money) => {
-                let mut rng = thread_rng();
-                let selected_chess: Vec<_> = ALL_CHESS
-                    .choose_multiple(&mut rng, 5)
-                    .map(|&chess| json!({ "chess": chess, "level": 1 }))
-                    .collect();
+    const ACTION: &'static str = "RefreshShop";
+    const RESULT: &'static str = "RefreshShopResult";
 
-                WsResponse {
-                    type_: "RefreshShopResult".to_string(),
-                    payload: Some(json!({
-                        "playerId": player_id,
-                        "success": true,
-                        "shop": selected_chess,
-                        "money": new_money
-                    })),
-                }
-            }
-            Err(reason) => {
-                WsResponse {
-                    type_: "RefreshShopResult".to_string(),
-                    payload: Some(json!({
-                        "playerId": player_id,
-                        "success": false,
-                        "reason": reason
-                    })),
-                }
-            }
-        }
-    }
+    async fn handle(&self, ctx: &ConnectionContext, request: RefreshShopRequest) -> Result<RefreshShopResponse, HandlerError> {
+        let player_id = acting_player(ctx, request.player_id.as_deref())?;
+
+        // 扣除金錢並重新產生商店
+        let player = self.player_manager.refresh_shop(&player_id)?;
+        let version = self.sync.publish(&player_id).unwrap_or_default();
 
-    fn can_handle(&self, action: &str) -> bool {
-        action == "RefreshShop"
+        Ok(RefreshShopResponse {
+            player_id,
+            shop: player.shop,
+            money: player.money,
+            version,
+        })
     }
 }
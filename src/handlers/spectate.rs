@@ -0,0 +1,101 @@
+use super::{acting_player, HandlerError, TypedHandler};
+use crate::connection::{ConnectionContext, ConnectionRegistry};
+use crate::control::StateSync;
+use crate::game::{GameRegistry, GameStatus};
+use crate::types::response::{ErrorCode, WsResponse};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+use async_trait::async_trait;
+
+/// 觀戰進行中的遊戲：之後收到被觀戰玩家的推播（狀態差異、回合、戰鬥）與整場遊戲的廣播，
+/// 並先收到每位被觀戰玩家的 `StateSnapshot`；所有觀戰推播都延遲 `delay` 送出
+pub struct SpectateHandler {
+    registry: Arc<ConnectionRegistry>,
+    games: Arc<GameRegistry>,
+    sync: Arc<StateSync>,
+    delay: Duration,
+}
+
+impl SpectateHandler {
+    pub fn new(registry: Arc<ConnectionRegistry>, games: Arc<GameRegistry>, sync: Arc<StateSync>, delay: Duration) -> Self {
+        Self { registry, games, sync, delay }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SpectateRequest {
+    pub game_id: String,
+    /// 只看這位玩家；省略時看所有玩家
+    pub player_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpectateResponse {
+    pub game_id: String,
+    pub player_id: Option<String>,
+    pub players: Vec<String>,
+    pub round: u32,
+    pub delay_secs: u64,
+}
+
+#[async_trait]
+impl TypedHandler for SpectateHandler {
+    type Request = SpectateRequest;
+    type Response = SpectateResponse;
+
+    const ACTION: &'static str = "Spectate";
+    const RESULT: &'static str = "SpectateResult";
+    const READ_ONLY: bool = true;
+
+    async fn handle(&self, ctx: &ConnectionContext, request: SpectateRequest) -> Result<SpectateResponse, HandlerError> {
+        let viewer = acting_player(ctx, None)?;
+        let game = self
+            .games
+            .get(&request.game_id)
+            .filter(|game| game.status == GameStatus::InProgress)
+            .ok_or_else(|| HandlerError::new(ErrorCode::GameNotFound, "no game in progress with this gameId"))?;
+        if request.player_id.as_ref().is_some_and(|player_id| !game.players.contains(player_id)) {
+            return Err(HandlerError::new(ErrorCode::PlayerNotFound, "player is not in this game"));
+        }
+
+        // 還沒被淘汰的玩家不能觀戰，避免偷看其他玩家的商店與棋盤
+        let playing = self
+            .registry
+            .game_of(&viewer)
+            .and_then(|game_id| self.games.get(&game_id))
+            .is_some_and(|current| {
+                current.status == GameStatus::InProgress
+                    && current.players.contains(&viewer)
+                    && !current.standings.iter().any(|standing| standing.player_id == viewer)
+            });
+        if playing {
+            return Err(HandlerError::new(ErrorCode::AlreadyInGame, "players cannot spectate while in a game"));
+        }
+
+        self.registry.spectate(ctx.handle(), &game.id, request.player_id.as_deref(), self.delay);
+        let watched = request.player_id.clone().map_or_else(|| game.players.clone(), |player_id| vec![player_id]);
+        for player_id in &watched {
+            if let Some((version, state)) = self.sync.current(player_id) {
+                let snapshot = WsResponse::new("StateSnapshot", json!({
+                    "playerId": player_id,
+                    "version": version,
+                    "state": state,
+                }));
+                self.registry.push_spectator(ctx.id(), snapshot);
+            }
+        }
+
+        Ok(SpectateResponse {
+            game_id: game.id,
+            player_id: request.player_id,
+            players: game.players,
+            round: game.round,
+            delay_secs: self.delay.as_secs(),
+        })
+    }
+}
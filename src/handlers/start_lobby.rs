@@ -0,0 +1,47 @@
+use super::{acting_player, HandlerError, TypedHandler};
+use crate::connection::ConnectionContext;
+use crate::matchmaking::LobbyManager;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use async_trait::async_trait;
+
+/// 房主以房間規則開始遊戲，房間隨即解散
+pub struct StartLobbyHandler {
+    lobbies: Arc<LobbyManager>,
+}
+
+impl StartLobbyHandler {
+    pub fn new(lobbies: Arc<LobbyManager>) -> Self {
+        Self { lobbies }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct StartLobbyRequest {}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartLobbyResponse {
+    pub game_id: String,
+    pub players: Vec<String>,
+}
+
+#[async_trait]
+impl TypedHandler for StartLobbyHandler {
+    type Request = StartLobbyRequest;
+    type Response = StartLobbyResponse;
+
+    const ACTION: &'static str = "StartLobby";
+    const RESULT: &'static str = "StartLobbyResult";
+
+    async fn handle(&self, ctx: &ConnectionContext, _request: StartLobbyRequest) -> Result<StartLobbyResponse, HandlerError> {
+        let host = acting_player(ctx, None)?;
+        let game = self.lobbies.start(&host)?;
+        Ok(StartLobbyResponse {
+            game_id: game.id,
+            players: game.players,
+        })
+    }
+}
@@ -0,0 +1,46 @@
+use super::{HandlerError, TypedHandler};
+use crate::connection::{ConnectionContext, ConnectionRegistry};
+use crate::types::response::ErrorCode;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use async_trait::async_trait;
+
+/// 停止觀戰，之後這條連線才能再操作遊戲
+pub struct StopSpectatingHandler {
+    registry: Arc<ConnectionRegistry>,
+}
+
+impl StopSpectatingHandler {
+    pub fn new(registry: Arc<ConnectionRegistry>) -> Self {
+        Self { registry }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct StopSpectatingRequest {}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StopSpectatingResponse {
+    pub game_id: String,
+}
+
+#[async_trait]
+impl TypedHandler for StopSpectatingHandler {
+    type Request = StopSpectatingRequest;
+    type Response = StopSpectatingResponse;
+
+    const ACTION: &'static str = "StopSpectating";
+    const RESULT: &'static str = "StopSpectatingResult";
+    const READ_ONLY: bool = true;
+
+    async fn handle(&self, ctx: &ConnectionContext, _request: StopSpectatingRequest) -> Result<StopSpectatingResponse, HandlerError> {
+        let game_id = self
+            .registry
+            .stop_spectating(ctx.id())
+            .ok_or_else(|| HandlerError::new(ErrorCode::NotSpectating, "this connection is not spectating"))?;
+        Ok(StopSpectatingResponse { game_id })
+    }
+}
@@ -0,0 +1,220 @@
+// 型別化處理器：由包裝層負責 payload 的反序列化與驗證，handler 只需處理遊戲邏輯
+
+use super::MessageHandler;
+use crate::connection::ConnectionContext;
+use crate::game::carousel::CarouselError;
+use crate::matchmaking::{LobbyError, MatchmakingError};
+use crate::player::PlayerError;
+use crate::types::response::{ErrorCode, WsRequest, WsResponse};
+use async_trait::async_trait;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+/// 業務邏輯失敗，會包成 `{ success: false, code, reason }` 回傳
+#[derive(Debug, Clone)]
+pub struct HandlerError {
+    pub code: ErrorCode,
+    pub reason: String,
+}
+
+impl HandlerError {
+    pub fn new(code: ErrorCode, reason: impl Into<String>) -> Self {
+        Self {
+            code,
+            reason: reason.into(),
+        }
+    }
+}
+
+impl From<PlayerError> for HandlerError {
+    fn from(err: PlayerError) -> Self {
+        Self::new(err.code(), err.to_string())
+    }
+}
+
+impl From<MatchmakingError> for HandlerError {
+    fn from(err: MatchmakingError) -> Self {
+        Self::new(err.code(), err.to_string())
+    }
+}
+
+impl From<LobbyError> for HandlerError {
+    fn from(err: LobbyError) -> Self {
+        Self::new(err.code(), err.to_string())
+    }
+}
+
+impl From<CarouselError> for HandlerError {
+    fn from(err: CarouselError) -> Self {
+        Self::new(err.code(), err.to_string())
+    }
+}
+
+/// 宣告請求與回應結構的處理器
+#[async_trait]
+pub trait TypedHandler: Send + Sync {
+    type Request: DeserializeOwned + JsonSchema + Send;
+    type Response: Serialize;
+
+    /// 對應的 action 名稱（請求的 `type`）
+    const ACTION: &'static str;
+    /// 回應的訊息型別，例如 `BuyXPResult`
+    const RESULT: &'static str;
+    /// 未登入的連線也能使用
+    const PUBLIC: bool = false;
+    /// 不會改變遊戲狀態，觀戰中的連線也能使用
+    const READ_ONLY: bool = false;
+
+    async fn handle(&self, ctx: &ConnectionContext, request: Self::Request) -> Result<Self::Response, HandlerError>;
+}
+
+/// 取得執行動作的玩家
+/// 以連線登入的身分為準；payload 若帶了 playerId 則必須與登入身分一致
+pub fn acting_player(ctx: &ConnectionContext, claimed: Option<&str>) -> Result<String, HandlerError> {
+    let player_id = ctx
+        .player_id()
+        .ok_or_else(|| HandlerError::new(ErrorCode::Unauthenticated, "login required"))?;
+
+    match claimed {
+        Some(claimed) if claimed != player_id => Err(HandlerError::new(
+            ErrorCode::Forbidden,
+            format!("cannot act as player {}", claimed),
+        )),
+        _ => Ok(player_id),
+    }
+}
+
+/// 將 `TypedHandler` 包裝成一般的 `MessageHandler`
+pub struct Typed<H>(pub H);
+
+#[async_trait]
+impl<H: TypedHandler> MessageHandler for Typed<H> {
+    async fn handle(&self, ctx: &ConnectionContext, val: &WsRequest) -> WsResponse {
+        let request = match parse_payload::<H::Request>(&val.payload) {
+            Ok(request) => request,
+            Err(response) => return response,
+        };
+
+        let payload = match self.0.handle(ctx, request).await {
+            Ok(response) => match serde_json::to_value(&response) {
+                Ok(Value::Object(mut fields)) => {
+                    fields.insert("success".to_string(), Value::Bool(true));
+                    Value::Object(fields)
+                }
+                Ok(other) => serde_json::json!({ "success": true, "data": other }),
+                Err(_) => return WsResponse::internal_server_error(),
+            },
+            Err(err) => {
+                let mut fields = serde_json::Map::new();
+                // 失敗時帶上登入的 playerId，方便客戶端辨識
+                if let Some(player_id) = ctx.player_id() {
+                    fields.insert("playerId".to_string(), Value::String(player_id));
+                }
+                fields.insert("success".to_string(), Value::Bool(false));
+                fields.insert("code".to_string(), serde_json::json!(err.code));
+                fields.insert("reason".to_string(), Value::String(err.reason));
+                Value::Object(fields)
+            }
+        };
+
+        WsResponse::new(H::RESULT, payload)
+    }
+
+    fn action(&self) -> &'static str {
+        H::ACTION
+    }
+
+    fn payload_schema(&self) -> Value {
+        serde_json::to_value(schemars::schema_for!(H::Request))
+            .unwrap_or_else(|_| serde_json::json!({ "type": "object" }))
+    }
+
+    fn public(&self) -> bool {
+        H::PUBLIC
+    }
+
+    fn read_only(&self) -> bool {
+        H::READ_ONLY
+    }
+}
+
+/// 反序列化 payload，失敗時回傳帶有欄位名稱的驗證錯誤
+pub fn parse_payload<T: DeserializeOwned>(payload: &Value) -> Result<T, WsResponse> {
+    serde_path_to_error::deserialize(payload).map_err(|err| {
+        let path = err.path().to_string();
+        let message = err.inner().to_string();
+
+        // 缺少欄位時 serde 只回報外層路徑，欄位名稱藏在訊息裡
+        match missing_field_name(&message) {
+            Some(field) => {
+                let field = if path == "." {
+                    field
+                } else {
+                    format!("{}.{}", path, field)
+                };
+                WsResponse::validation_error(ErrorCode::MissingField, &field, &message)
+            }
+            None => WsResponse::validation_error(ErrorCode::InvalidField, &path, &message),
+        }
+    })
+}
+
+fn missing_field_name(message: &str) -> Option<String> {
+    let rest = message.strip_prefix("missing field `")?;
+    rest.split('`').next().map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connection::ConnectionRegistry;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Sample {
+        player_id: String,
+        #[allow(dead_code)]
+        count: Option<u32>,
+    }
+
+    #[test]
+    fn test_parse_payload_ok() {
+        let sample: Sample = parse_payload(&serde_json::json!({ "playerId": "p1" })).unwrap();
+        assert_eq!(sample.player_id, "p1");
+    }
+
+    #[test]
+    fn test_missing_field_reports_field() {
+        let err = parse_payload::<Sample>(&serde_json::json!({})).unwrap_err();
+        let payload = err.payload.unwrap();
+        assert_eq!(payload["code"], "MISSING_FIELD");
+        assert_eq!(payload["field"], "playerId");
+    }
+
+    #[test]
+    fn test_invalid_field_reports_path() {
+        let err = parse_payload::<Sample>(&serde_json::json!({ "playerId": "p1", "count": "x" }))
+            .unwrap_err();
+        let payload = err.payload.unwrap();
+        assert_eq!(payload["code"], "INVALID_FIELD");
+        assert_eq!(payload["field"], "count");
+    }
+
+    #[test]
+    fn test_acting_player_uses_session_identity() {
+        let registry = ConnectionRegistry::new();
+        let (handle, _receiver) = registry.open();
+        let ctx = ConnectionContext::new(handle, "127.0.0.1:9000".parse().unwrap());
+
+        let err = acting_player(&ctx, None).unwrap_err();
+        assert_eq!(err.code, ErrorCode::Unauthenticated);
+
+        ctx.authenticate("p1");
+        assert_eq!(acting_player(&ctx, None).unwrap(), "p1");
+        assert_eq!(acting_player(&ctx, Some("p1")).unwrap(), "p1");
+        assert_eq!(acting_player(&ctx, Some("p2")).unwrap_err().code, ErrorCode::Forbidden);
+    }
+}
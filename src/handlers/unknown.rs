@@ -1,4 +1,5 @@
 use super::MessageHandler;
+use crate::connection::ConnectionContext;
 use crate::types::response::{WsRequest, WsResponse};
 use async_trait::async_trait;
 
@@ -6,11 +7,11 @@ pub struct UnknownHandler;
 
 #[async_trait]
 impl MessageHandler for UnknownHandler {
-    async fn handle(&self, val: &WsRequest) -> WsResponse {
-        WsResponse::error(format!("unknown action: {}", val.type_))
+    async fn handle(&self, _ctx: &ConnectionContext, val: &WsRequest) -> WsResponse {
+        WsResponse::unknown_action(&val.type_)
     }
 
-    fn can_handle(&self, _action: &str) -> bool {
-        true // 作为默认处理器
+    fn action(&self) -> &'static str {
+        "Unknown" // 作为默认处理器，透過 Router::set_fallback 註冊
     }
 }
@@ -10,40 +10,178 @@ mod websocket;
 mod player;
 mod control;
 mod data;
+mod connection;
+mod middleware;
+mod config;
+mod shutdown;
+mod api;
+mod chesses;
+mod game;
+mod matchmaking;
+mod bots;
 
-use handlers::{EchoHandler, PingHandler, UnknownHandler, BuyXPHandler, ShopHandler, CreateGameHandler, GameStateMessageHandler};
+use handlers::{EchoHandler, PingHandler, UnknownHandler, BuyXPHandler, ShopHandler, CreateGameHandler, GameStateMessageHandler, ChatHandler, LoginHandler, ResumeHandler, HelloHandler, BuyUnitHandler, SellUnitHandler, EquipItemHandler, MoveUnitHandler, ResyncHandler, QueueForMatchHandler, CancelQueueHandler, AcceptMatchHandler, CreateLobbyHandler, JoinLobbyHandler, LeaveLobbyHandler, KickFromLobbyHandler, SetLobbyRulesHandler, StartLobbyHandler, PickCarouselHandler, PickAugmentHandler, RerollAugmentsHandler, SpectateHandler, StopSpectatingHandler, Typed};
 use router::Router;
-use websocket::handle_client;
+use websocket::{handle_client, ServerStats};
 use player::PlayerManager;
-use crate::control::game_state_control::GameStateControl;
-use data::{all_chess_pieces,initial_money,initial_experience};
+use control::StateSync;
+use connection::{ConnectionRegistry, PresenceMonitor};
+use config::Config;
+use game::{GameRegistry, GameRules, GameStarter, RoundLoop};
+use matchmaking::{LobbyManager, Matchmaker};
+use bots::BotManager;
+use middleware::{AuthLayer, CatchPanicLayer, HandshakeLayer, RateLimitLayer, SpectatorLayer, TimingLayer};
+use tokio::task::JoinSet;
+use tokio::time::Duration;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     env_logger::init();
 
-    let addr = "127.0.0.1:9002";
+    // 設定有誤時直接結束，不要帶著錯誤的數值啟動
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+    info!("Loaded config: {:?}", config);
+
+    let addr = config.network.bind.as_str();
     let listener = TcpListener::bind(addr).await?;
     info!("WebSocket server running on ws://{}", addr);
+    let http_listener = TcpListener::bind(&config.network.http_bind).await?;
+    info!("HTTP API running on http://{}", config.network.http_bind);
 
     let mut router = Router::new();
-    let player_manager = Arc::new(PlayerManager::new());
+    let default_rules = GameRules::from_config(&config.economy, &config.timing);
+    let player_manager = Arc::new(PlayerManager::new(default_rules.clone()));
+    if let Some(path) = &config.persistence.state_file {
+        let restored = player_manager.load(path)?;
+        info!("Restored {} players from {}", restored, path.display());
+    }
+    let registry = Arc::new(ConnectionRegistry::new());
+    if config.session.secret.is_none() {
+        warn!("session.secret is not set, session tokens will not survive a restart");
+    }
+    let signer = Arc::new(config.session.signer());
+    let presence = Arc::new(PresenceMonitor::new(registry.clone(), player_manager.clone(), config.timing.reconnect_grace()));
+    let games = Arc::new(GameRegistry::with_default_rules(default_rules));
+    let sync = Arc::new(StateSync::new(player_manager.clone(), registry.clone()).with_games(games.clone()));
+    let rounds = Arc::new(RoundLoop::new(games.clone(), player_manager.clone(), registry.clone(), sync.clone()));
+    let starter = Arc::new(GameStarter::new(games.clone(), player_manager.clone(), registry.clone(), sync.clone()).with_rounds(rounds.clone()));
+    if let Some(path) = &config.persistence.games_file {
+        let in_progress = games.load(path)?;
+        info!("Restored {} games from {}, resuming {} in progress", games.list().len(), path.display(), in_progress.len());
+        for game in in_progress {
+            starter.resume(game);
+        }
+    }
+    let matchmaker = Arc::new(Matchmaker::new(config.matchmaking.clone(), player_manager.clone(), games.clone(), registry.clone(), starter.clone()));
+    let lobbies = Arc::new(LobbyManager::new(player_manager.clone(), games.clone(), registry.clone(), starter.clone()));
+    tokio::spawn(matchmaker.clone().run());
 
     // 註冊處理器
-    router.add_handler(Arc::new(EchoHandler));
-    router.add_handler(Arc::new(PingHandler));
-    router.add_handler(Arc::new(BuyXPHandler::new(player_manager.clone())));
-    router.add_handler(Arc::new(ShopHandler::new(player_manager.clone())));
-    router.add_handler(Arc::new(CreateGameHandler));
-    router.add_handler(Arc::new(GameStateMessageHandler::new(player_manager.clone())));
-    router.add_handler(Arc::new(UnknownHandler));
-    
-    while let Ok((stream, addr)) = listener.accept().await {
+    router.add_handler(Arc::new(Typed(HelloHandler)))?;
+    router.add_handler(Arc::new(Typed(LoginHandler::new(player_manager.clone(), presence.clone(), signer.clone(), sync.clone()))))?;
+    router.add_handler(Arc::new(Typed(ResumeHandler::new(player_manager.clone(), presence.clone(), signer.clone()))))?;
+    router.add_handler(Arc::new(EchoHandler))?;
+    router.add_handler(Arc::new(PingHandler))?;
+    router.add_handler(Arc::new(Typed(BuyXPHandler::new(player_manager.clone(), sync.clone()))))?;
+    router.add_handler(Arc::new(Typed(ShopHandler::new(player_manager.clone(), sync.clone()))))?;
+    router.add_handler(Arc::new(Typed(BuyUnitHandler::new(player_manager.clone(), sync.clone()))))?;
+    router.add_handler(Arc::new(Typed(SellUnitHandler::new(player_manager.clone(), sync.clone()))))?;
+    router.add_handler(Arc::new(Typed(EquipItemHandler::new(player_manager.clone(), sync.clone()))))?;
+    router.add_handler(Arc::new(Typed(MoveUnitHandler::new(player_manager.clone(), sync.clone()))))?;
+    router.add_handler(Arc::new(Typed(ResyncHandler::new(sync.clone()))))?;
+    router.add_handler(Arc::new(Typed(CreateGameHandler::new(registry.clone(), games.clone()))))?;
+    router.add_handler(Arc::new(Typed(QueueForMatchHandler::new(matchmaker.clone()))))?;
+    router.add_handler(Arc::new(Typed(CancelQueueHandler::new(matchmaker.clone()))))?;
+    router.add_handler(Arc::new(Typed(AcceptMatchHandler::new(matchmaker.clone()))))?;
+    router.add_handler(Arc::new(Typed(CreateLobbyHandler::new(lobbies.clone()))))?;
+    router.add_handler(Arc::new(Typed(JoinLobbyHandler::new(lobbies.clone()))))?;
+    router.add_handler(Arc::new(Typed(LeaveLobbyHandler::new(lobbies.clone()))))?;
+    router.add_handler(Arc::new(Typed(KickFromLobbyHandler::new(lobbies.clone()))))?;
+    router.add_handler(Arc::new(Typed(SetLobbyRulesHandler::new(lobbies.clone()))))?;
+    router.add_handler(Arc::new(Typed(StartLobbyHandler::new(lobbies.clone()))))?;
+    router.add_handler(Arc::new(Typed(PickCarouselHandler::new(rounds.clone()))))?;
+    router.add_handler(Arc::new(Typed(PickAugmentHandler::new(player_manager.clone(), sync.clone()))))?;
+    router.add_handler(Arc::new(Typed(RerollAugmentsHandler::new(player_manager.clone(), sync.clone()))))?;
+    router.add_handler(Arc::new(Typed(GameStateMessageHandler::new(sync.clone(), registry.clone()))))?;
+    router.add_handler(Arc::new(Typed(ChatHandler::new(registry.clone()))))?;
+    router.add_handler(Arc::new(Typed(SpectateHandler::new(registry.clone(), games.clone(), sync.clone(), config.timing.spectator_delay()))))?;
+    router.add_handler(Arc::new(Typed(StopSpectatingHandler::new(registry.clone()))))?;
+    router.set_fallback(Arc::new(UnknownHandler));
+
+    // 中介層（先加入的在最外層）
+    router.layer(Arc::new(CatchPanicLayer));
+    // 公開與唯讀的 action 由各處理器宣告
+    let public_actions = router.public_actions();
+    let read_only_actions = router.read_only_actions();
+    router.layer(Arc::new(TimingLayer::new(config.requests.slow_request())));
+    router.layer(Arc::new(RateLimitLayer::new(config.requests.rate_per_second, config.requests.rate_burst)));
+    router.layer(Arc::new(HandshakeLayer));
+    router.layer(Arc::new(AuthLayer::new(&public_actions)));
+    router.layer(Arc::new(SpectatorLayer::new(registry.clone(), &read_only_actions)));
+    let router = Arc::new(router);
+
+    // 機器人與客戶端走同一個 router
+    if config.bots.count > 0 {
+        let bots = BotManager::new(router.clone(), registry.clone()).with_think_time(Duration::from_millis(config.bots.think_millis));
+        for _ in 0..config.bots.count {
+            bots.spawn(config.bots.difficulty).await;
+        }
+        info!("Started {} {:?} bots", config.bots.count, config.bots.difficulty);
+    }
+    let heartbeat = config.timing.heartbeat();
+    let stats = Arc::new(ServerStats::new());
+
+    // HTTP API 與 WebSocket 共用玩家與遊戲資料，關機時一起停止
+    let (stop_http, http_stopped) = tokio::sync::oneshot::channel::<()>();
+    let api_state = api::ApiState {
+        player_manager: player_manager.clone(),
+        games: games.clone(),
+        registry: registry.clone(),
+        spectator_delay: config.timing.spectator_delay(),
+    };
+    let http_server = tokio::spawn(api::serve(http_listener, api_state, async {
+        let _ = http_stopped.await;
+    }));
+
+    // 接受連線直到收到關機訊號
+    let mut clients = JoinSet::new();
+    let shutdown_signal = shutdown::signal();
+    tokio::pin!(shutdown_signal);
+    loop {
+        let (stream, addr) = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    error!("Failed to accept connection: {}", e);
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    continue;
+                }
+            },
+            // 回收已結束的連線任務；連線任務不應 panic，若發生則留下紀錄
+            Some(joined) = clients.join_next(), if !clients.is_empty() => {
+                if let Err(e) = joined {
+                    if e.is_panic() {
+                        error!("Connection task panicked: {}", e);
+                    }
+                }
+                continue;
+            }
+            _ = &mut shutdown_signal => break,
+        };
         info!("New connection from: {}", addr);
 
         let router = router.clone();
-        tokio::spawn(async move {
-            if let Err(err) = handle_client(stream, router).await {
+        let registry = registry.clone();
+        let presence = presence.clone();
+        let stats = stats.clone();
+        clients.spawn(async move {
+            if let Err(err) = handle_client(stream, router, registry, presence, heartbeat, stats).await {
                 match err {
                     Error::ConnectionClosed | Error::Protocol(_) | Error::Utf8 => (),
                     e => error!("WebSocket error: {}", e),
@@ -52,5 +190,40 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         });
     }
 
+    // 停止接受新連線，通知客戶端並等待連線在期限內結束
+    drop(listener);
+    let _ = stop_http.send(());
+    let notified = shutdown::notify_all(&registry, config.timing.reconnect_after());
+    info!("Shutting down, closing {} connections", notified);
+    let drained = tokio::time::timeout(config.timing.shutdown_deadline(), async {
+        while clients.join_next().await.is_some() {}
+    })
+    .await;
+    if drained.is_err() {
+        warn!("{} connections did not close before the deadline, aborting them", clients.len());
+        clients.shutdown().await;
+    }
+
+    match http_server.await {
+        Ok(Err(e)) => error!("HTTP API failed: {}", e),
+        Err(e) => error!("HTTP API task failed: {}", e),
+        Ok(Ok(())) => (),
+    }
+
+    // 所有連線結束後才寫回狀態，確保最後的操作都已套用
+    if let Some(path) = &config.persistence.state_file {
+        match player_manager.save(path) {
+            Ok(saved) => info!("Saved {} players to {}", saved, path.display()),
+            Err(e) => error!("Failed to save players to {}: {}", path.display(), e),
+        }
+    }
+    if let Some(path) = &config.persistence.games_file {
+        match games.save(path) {
+            Ok(saved) => info!("Saved {} games to {}", saved, path.display()),
+            Err(e) => error!("Failed to save games to {}: {}", path.display(), e),
+        }
+    }
+    info!("Server stopped");
+
     Ok(())
 }
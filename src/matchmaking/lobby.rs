@@ -0,0 +1,373 @@
+// 自訂房間：房主建立房間取得邀請碼，朋友以邀請碼加入，房主調整規則後開始遊戲
+//
+// 房主離開時由最早加入的成員接手；最後一人離開後房間即解散。
+// 房間成員有變動或規則被調整時，推播 `LobbyUpdated` 給其他成員。
+
+use crate::connection::ConnectionRegistry;
+use crate::game::{GameInfo, GameRegistry, GameRules, GameStarter, RulesPatch};
+use crate::player::PlayerManager;
+use crate::types::response::{ErrorCode, WsResponse};
+use log::*;
+use rand::Rng;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+/// 邀請碼長度與字元（排除容易看錯的 0/O、1/I/L）
+const CODE_LEN: usize = 6;
+const CODE_CHARS: &[u8] = b"ABCDEFGHJKMNPQRSTUVWXYZ23456789";
+
+/// 房間人數上限與開始遊戲所需的最少人數
+pub const MAX_LOBBY_MEMBERS: usize = 8;
+pub const MIN_LOBBY_MEMBERS: usize = 2;
+
+/// 房間操作失敗原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LobbyError {
+    PlayerNotFound,
+    LobbyNotFound,
+    NotInLobby,
+    LobbyFull,
+    AlreadyInLobby,
+    AlreadyInGame,
+    NotHost,
+    NotEnoughPlayers,
+    InvalidRules(String),
+}
+
+impl LobbyError {
+    /// 對應的協定錯誤代碼
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            LobbyError::PlayerNotFound => ErrorCode::PlayerNotFound,
+            LobbyError::LobbyNotFound | LobbyError::NotInLobby => ErrorCode::LobbyNotFound,
+            LobbyError::LobbyFull => ErrorCode::LobbyFull,
+            LobbyError::AlreadyInLobby => ErrorCode::AlreadyInLobby,
+            LobbyError::AlreadyInGame => ErrorCode::AlreadyInGame,
+            LobbyError::NotHost => ErrorCode::Forbidden,
+            LobbyError::NotEnoughPlayers => ErrorCode::NotEnoughPlayers,
+            LobbyError::InvalidRules(_) => ErrorCode::InvalidField,
+        }
+    }
+}
+
+impl fmt::Display for LobbyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LobbyError::PlayerNotFound => write!(f, "player not found"),
+            LobbyError::LobbyNotFound => write!(f, "no lobby with this code"),
+            LobbyError::NotInLobby => write!(f, "not in a lobby"),
+            LobbyError::LobbyFull => write!(f, "lobby is full ({} players)", MAX_LOBBY_MEMBERS),
+            LobbyError::AlreadyInLobby => write!(f, "already in a lobby"),
+            LobbyError::AlreadyInGame => write!(f, "already playing a game"),
+            LobbyError::NotHost => write!(f, "only the host can do this"),
+            LobbyError::NotEnoughPlayers => write!(f, "at least {} players are required", MIN_LOBBY_MEMBERS),
+            LobbyError::InvalidRules(reason) => write!(f, "invalid rules: {}", reason),
+        }
+    }
+}
+
+/// 房間的公開狀態，回應與 `LobbyUpdated` 推播共用
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LobbyView {
+    pub code: String,
+    pub host: String,
+    /// 依加入順序
+    pub members: Vec<String>,
+    pub rules: GameRules,
+}
+
+/// 鎖外才送出的推播
+type Outbox = Vec<(String, WsResponse)>;
+
+pub struct LobbyManager {
+    player_manager: Arc<PlayerManager>,
+    games: Arc<GameRegistry>,
+    registry: Arc<ConnectionRegistry>,
+    starter: Arc<GameStarter>,
+    /// 邀請碼 -> 房間
+    lobbies: Mutex<HashMap<String, LobbyView>>,
+}
+
+impl LobbyManager {
+    pub fn new(
+        player_manager: Arc<PlayerManager>,
+        games: Arc<GameRegistry>,
+        registry: Arc<ConnectionRegistry>,
+        starter: Arc<GameStarter>,
+    ) -> Self {
+        Self {
+            player_manager,
+            games,
+            registry,
+            starter,
+            lobbies: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 建立房間，建立者成為房主；`rules` 為相對於伺服器預設規則的調整
+    pub fn create(&self, host: &str, rules: Option<RulesPatch>) -> Result<LobbyView, LobbyError> {
+        self.check_available(host)?;
+        let defaults = self.games.default_rules();
+        let rules = match rules {
+            Some(patch) => defaults.apply(patch).map_err(|e| LobbyError::InvalidRules(e.0))?,
+            None => defaults.clone(),
+        };
+
+        let mut lobbies = self.lobbies.lock().unwrap();
+        if find_member(&lobbies, host).is_some() {
+            return Err(LobbyError::AlreadyInLobby);
+        }
+        let code = loop {
+            let code = invite_code();
+            if !lobbies.contains_key(&code) {
+                break code;
+            }
+        };
+        let lobby = LobbyView {
+            code: code.clone(),
+            host: host.to_string(),
+            members: vec![host.to_string()],
+            rules,
+        };
+        info!("Lobby {} created by {}", code, host);
+        lobbies.insert(code, lobby.clone());
+        Ok(lobby)
+    }
+
+    /// 以邀請碼加入房間（不分大小寫）
+    pub fn join(&self, player_id: &str, code: &str) -> Result<LobbyView, LobbyError> {
+        self.check_available(player_id)?;
+        let mut outbox = Outbox::new();
+        let lobby = {
+            let mut lobbies = self.lobbies.lock().unwrap();
+            if find_member(&lobbies, player_id).is_some() {
+                return Err(LobbyError::AlreadyInLobby);
+            }
+            let lobby = lobbies.get_mut(&code.trim().to_uppercase()).ok_or(LobbyError::LobbyNotFound)?;
+            if lobby.members.len() >= MAX_LOBBY_MEMBERS {
+                return Err(LobbyError::LobbyFull);
+            }
+            lobby.members.push(player_id.to_string());
+            notify_others(lobby, player_id, &mut outbox);
+            lobby.clone()
+        };
+        self.deliver(outbox);
+        Ok(lobby)
+    }
+
+    /// 離開目前的房間，回傳離開的房間邀請碼
+    pub fn leave(&self, player_id: &str) -> Result<String, LobbyError> {
+        let mut outbox = Outbox::new();
+        let code = {
+            let mut lobbies = self.lobbies.lock().unwrap();
+            let code = find_member(&lobbies, player_id).ok_or(LobbyError::NotInLobby)?;
+            remove_member(&mut lobbies, &code, player_id, &mut outbox);
+            code
+        };
+        self.deliver(outbox);
+        Ok(code)
+    }
+
+    /// 房主把成員踢出房間
+    pub fn kick(&self, host: &str, target: &str) -> Result<LobbyView, LobbyError> {
+        let mut outbox = Outbox::new();
+        let lobby = {
+            let mut lobbies = self.lobbies.lock().unwrap();
+            let code = hosted_by(&lobbies, host)?;
+            if target == host || !lobbies[&code].members.iter().any(|m| m == target) {
+                return Err(LobbyError::NotInLobby);
+            }
+            remove_member(&mut lobbies, &code, target, &mut outbox);
+            outbox.push((target.to_string(), WsResponse::new("LobbyKicked", json!({ "code": code }))));
+            lobbies[&code].clone()
+        };
+        self.deliver(outbox);
+        Ok(lobby)
+    }
+
+    /// 房主調整規則；不合法時維持原本的規則
+    pub fn set_rules(&self, host: &str, patch: RulesPatch) -> Result<LobbyView, LobbyError> {
+        let mut outbox = Outbox::new();
+        let lobby = {
+            let mut lobbies = self.lobbies.lock().unwrap();
+            let code = hosted_by(&lobbies, host)?;
+            let lobby = lobbies.get_mut(&code).unwrap();
+            lobby.rules = lobby.rules.apply(patch).map_err(|e| LobbyError::InvalidRules(e.0))?;
+            notify_others(lobby, host, &mut outbox);
+            lobby.clone()
+        };
+        self.deliver(outbox);
+        Ok(lobby)
+    }
+
+    /// 房主開始遊戲：以房間規則建立遊戲並解散房間
+    pub fn start(&self, host: &str) -> Result<GameInfo, LobbyError> {
+        let lobby = {
+            let mut lobbies = self.lobbies.lock().unwrap();
+            let code = hosted_by(&lobbies, host)?;
+            if lobbies[&code].members.len() < MIN_LOBBY_MEMBERS {
+                return Err(LobbyError::NotEnoughPlayers);
+            }
+            lobbies.remove(&code).unwrap()
+        };
+        let game = self.games.create_with_rules(rand::random(), lobby.members, lobby.rules);
+        info!("Lobby {} started game {}", lobby.code, game.id);
+        Ok(self.starter.start(&game.id).unwrap_or(game))
+    }
+
+    /// 玩家必須存在且不在進行中的遊戲裡
+    fn check_available(&self, player_id: &str) -> Result<(), LobbyError> {
+        self.player_manager.get_player(player_id).ok_or(LobbyError::PlayerNotFound)?;
+        if self.registry.game_of(player_id).is_some_and(|game_id| self.games.is_in_progress(&game_id)) {
+            return Err(LobbyError::AlreadyInGame);
+        }
+        Ok(())
+    }
+
+    fn deliver(&self, outbox: Outbox) {
+        for (player_id, message) in outbox {
+            let _ = self.registry.push(&player_id, message);
+        }
+    }
+}
+
+fn invite_code() -> String {
+    let mut rng = rand::thread_rng();
+    (0..CODE_LEN).map(|_| CODE_CHARS[rng.gen_range(0..CODE_CHARS.len())] as char).collect()
+}
+
+fn find_member(lobbies: &HashMap<String, LobbyView>, player_id: &str) -> Option<String> {
+    lobbies
+        .values()
+        .find(|lobby| lobby.members.iter().any(|m| m == player_id))
+        .map(|lobby| lobby.code.clone())
+}
+
+/// 玩家擔任房主的房間
+fn hosted_by(lobbies: &HashMap<String, LobbyView>, player_id: &str) -> Result<String, LobbyError> {
+    let code = find_member(lobbies, player_id).ok_or(LobbyError::NotInLobby)?;
+    if lobbies[&code].host != player_id {
+        return Err(LobbyError::NotHost);
+    }
+    Ok(code)
+}
+
+/// 移出成員；房主離開時交給下一位成員，沒有人時解散房間
+fn remove_member(lobbies: &mut HashMap<String, LobbyView>, code: &str, player_id: &str, outbox: &mut Outbox) {
+    let Some(lobby) = lobbies.get_mut(code) else {
+        return;
+    };
+    lobby.members.retain(|m| m != player_id);
+    match lobby.members.first() {
+        None => {
+            info!("Lobby {} closed", code);
+            lobbies.remove(code);
+        }
+        Some(next) => {
+            if lobby.host == player_id {
+                lobby.host = next.clone();
+            }
+            notify_others(lobby, player_id, outbox);
+        }
+    }
+}
+
+/// 推播 `LobbyUpdated` 給 `actor` 以外的成員（`actor` 會收到操作的回應）
+fn notify_others(lobby: &LobbyView, actor: &str, outbox: &mut Outbox) {
+    let message = WsResponse::new("LobbyUpdated", serde_json::to_value(lobby).unwrap_or_default());
+    outbox.extend(
+        lobby
+            .members
+            .iter()
+            .filter(|m| m.as_str() != actor)
+            .map(|m| (m.clone(), message.clone())),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connection::TestClients;
+    use crate::control::StateSync;
+    use crate::game::GameStatus;
+
+    struct Fixture {
+        lobbies: LobbyManager,
+        games: Arc<GameRegistry>,
+        player_manager: Arc<PlayerManager>,
+        clients: TestClients,
+    }
+
+    impl Fixture {
+        fn new(players: &[&str]) -> Self {
+            let player_manager = Arc::new(PlayerManager::new(GameRules::default()));
+            let games = Arc::new(GameRegistry::new());
+            let registry = Arc::new(ConnectionRegistry::new());
+            for &id in players {
+                player_manager.create_player(id);
+            }
+            let clients = TestClients::connect(&registry, players, None);
+            let sync = Arc::new(StateSync::new(player_manager.clone(), registry.clone()));
+            let starter = Arc::new(GameStarter::new(games.clone(), player_manager.clone(), registry.clone(), sync));
+            let lobbies = LobbyManager::new(player_manager.clone(), games.clone(), registry, starter);
+            Self { lobbies, games, player_manager, clients }
+        }
+
+        fn push_types(&mut self, player_id: &str) -> Vec<String> {
+            self.clients.pushes(player_id).into_iter().map(|response| response.type_).collect()
+        }
+    }
+
+    #[test]
+    fn test_join_kick_and_host_handover() {
+        let mut fixture = Fixture::new(&["host", "a", "b"]);
+        let lobby = fixture.lobbies.create("host", None).unwrap();
+        assert_eq!(lobby.code.len(), CODE_LEN);
+        assert_eq!(fixture.lobbies.create("host", None), Err(LobbyError::AlreadyInLobby));
+        assert_eq!(fixture.lobbies.join("a", "nope"), Err(LobbyError::LobbyNotFound));
+
+        fixture.lobbies.join("a", &lobby.code.to_lowercase()).unwrap();
+        fixture.lobbies.join("b", &lobby.code).unwrap();
+        assert_eq!(fixture.push_types("host"), vec!["LobbyUpdated", "LobbyUpdated"]);
+
+        assert_eq!(fixture.lobbies.kick("a", "b"), Err(LobbyError::NotHost));
+        let after_kick = fixture.lobbies.kick("host", "b").unwrap();
+        assert_eq!(after_kick.members, vec!["host", "a"]);
+        assert_eq!(fixture.push_types("b").last().unwrap(), "LobbyKicked");
+
+        fixture.lobbies.leave("host").unwrap();
+        let view = fixture.lobbies.join("b", &lobby.code).unwrap();
+        assert_eq!(view.host, "a");
+        fixture.lobbies.leave("a").unwrap();
+        fixture.lobbies.leave("b").unwrap();
+        assert_eq!(fixture.lobbies.join("host", &lobby.code), Err(LobbyError::LobbyNotFound));
+    }
+
+    #[test]
+    fn test_custom_rules_apply_to_started_game() {
+        let mut fixture = Fixture::new(&["host", "a"]);
+        let patch = RulesPatch { starting_gold: Some(30), ..RulesPatch::default() };
+        let lobby = fixture.lobbies.create("host", Some(patch)).unwrap();
+        assert_eq!(fixture.lobbies.start("host").unwrap_err(), LobbyError::NotEnoughPlayers);
+
+        fixture.lobbies.join("a", &lobby.code).unwrap();
+        let invalid = RulesPatch { shop_size: Some(0), ..RulesPatch::default() };
+        assert!(matches!(fixture.lobbies.set_rules("host", invalid), Err(LobbyError::InvalidRules(_))));
+        fixture
+            .lobbies
+            .set_rules("host", RulesPatch { shop_size: Some(3), ..RulesPatch::default() })
+            .unwrap();
+
+        let game = fixture.lobbies.start("host").unwrap();
+        assert_eq!(fixture.games.get(&game.id).unwrap().status, GameStatus::InProgress);
+        let player = fixture.player_manager.get_player("a").unwrap();
+        assert_eq!(player.money, 30);
+        assert_eq!(player.shop.len(), 3);
+        assert!(fixture.push_types("a").contains(&"GameStarted".to_string()));
+        assert_eq!(fixture.lobbies.leave("host"), Err(LobbyError::NotInLobby));
+    }
+}
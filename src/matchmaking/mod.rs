@@ -0,0 +1,443 @@
+// 配對佇列：把排隊中的玩家湊成一場遊戲，所有人確認（ready check）後才開始
+//
+// 排隊較久的玩家優先；設定了 rating_window 時只配對積分相近的玩家，差距上限隨排隊時間放寬。
+// 確認逾時未回應的玩家重新排到佇列最後，已確認的玩家保留原本的排隊時間；主動拒絕的玩家離開佇列。
+
+pub mod lobby;
+
+pub use lobby::{LobbyError, LobbyManager, LobbyView};
+
+use crate::config::MatchmakingConfig;
+use crate::connection::ConnectionRegistry;
+use crate::game::{GameRegistry, GameStarter};
+use crate::player::PlayerManager;
+use crate::types::response::{ErrorCode, WsResponse};
+use log::*;
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// 背景任務檢查逾時與湊桌的間隔
+const TICK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 配對操作失敗原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchmakingError {
+    PlayerNotFound,
+    AlreadyQueued,
+    AlreadyInGame,
+    MatchNotFound,
+}
+
+impl MatchmakingError {
+    /// 對應的協定錯誤代碼
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            MatchmakingError::PlayerNotFound => ErrorCode::PlayerNotFound,
+            MatchmakingError::AlreadyQueued => ErrorCode::AlreadyQueued,
+            MatchmakingError::AlreadyInGame => ErrorCode::AlreadyInGame,
+            MatchmakingError::MatchNotFound => ErrorCode::MatchNotFound,
+        }
+    }
+}
+
+impl fmt::Display for MatchmakingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MatchmakingError::PlayerNotFound => write!(f, "player not found"),
+            MatchmakingError::AlreadyQueued => write!(f, "already queued or waiting for a ready check"),
+            MatchmakingError::AlreadyInGame => write!(f, "already playing a game"),
+            MatchmakingError::MatchNotFound => write!(f, "no pending match with this id"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Ticket {
+    player_id: String,
+    rating: u32,
+    queued_at: Instant,
+}
+
+/// 等待確認中的配對
+struct ReadyCheck {
+    tickets: Vec<Ticket>,
+    accepted: HashSet<String>,
+    deadline: Instant,
+}
+
+#[derive(Default)]
+struct QueueState {
+    /// 依排隊時間排序
+    queue: Vec<Ticket>,
+    /// gameId -> 等待確認的配對
+    pending: HashMap<String, ReadyCheck>,
+}
+
+impl QueueState {
+    fn contains(&self, player_id: &str) -> bool {
+        self.queue.iter().any(|t| t.player_id == player_id) || self.pending_of(player_id).is_some()
+    }
+
+    fn pending_of(&self, player_id: &str) -> Option<String> {
+        self.pending
+            .iter()
+            .find(|(_, check)| check.tickets.iter().any(|t| t.player_id == player_id))
+            .map(|(game_id, _)| game_id.clone())
+    }
+
+    fn requeue(&mut self, ticket: Ticket) {
+        let index = self.queue.partition_point(|t| t.queued_at <= ticket.queued_at);
+        self.queue.insert(index, ticket);
+    }
+}
+
+/// 鎖外才送出的推播
+type Outbox = Vec<(String, WsResponse)>;
+
+pub struct Matchmaker {
+    config: MatchmakingConfig,
+    player_manager: Arc<PlayerManager>,
+    games: Arc<GameRegistry>,
+    registry: Arc<ConnectionRegistry>,
+    starter: Arc<GameStarter>,
+    state: Mutex<QueueState>,
+}
+
+impl Matchmaker {
+    pub fn new(
+        config: MatchmakingConfig,
+        player_manager: Arc<PlayerManager>,
+        games: Arc<GameRegistry>,
+        registry: Arc<ConnectionRegistry>,
+        starter: Arc<GameStarter>,
+    ) -> Self {
+        Self {
+            config,
+            player_manager,
+            games,
+            registry,
+            starter,
+            state: Mutex::new(QueueState::default()),
+        }
+    }
+
+    /// 加入佇列，回傳目前排隊人數
+    pub fn enqueue(&self, player_id: &str, now: Instant) -> Result<usize, MatchmakingError> {
+        let player = self.player_manager.get_player(player_id).ok_or(MatchmakingError::PlayerNotFound)?;
+        if self.registry.game_of(player_id).is_some_and(|game_id| self.games.is_in_progress(&game_id)) {
+            return Err(MatchmakingError::AlreadyInGame);
+        }
+
+        let mut outbox = Outbox::new();
+        let queued = {
+            let mut state = self.state.lock().unwrap();
+            if state.contains(player_id) {
+                return Err(MatchmakingError::AlreadyQueued);
+            }
+            state.queue.push(Ticket {
+                player_id: player_id.to_string(),
+                rating: player.rating,
+                queued_at: now,
+            });
+            let queued = state.queue.len();
+            self.form_lobbies(&mut state, now, &mut outbox);
+            queued
+        };
+        self.deliver(outbox);
+        Ok(queued)
+    }
+
+    /// 離開佇列；若已在確認階段則視同拒絕
+    pub fn cancel(&self, player_id: &str, now: Instant) -> bool {
+        let mut outbox = Outbox::new();
+        let cancelled = {
+            let mut state = self.state.lock().unwrap();
+            let before = state.queue.len();
+            state.queue.retain(|t| t.player_id != player_id);
+            if state.queue.len() != before {
+                true
+            } else if let Some(game_id) = state.pending_of(player_id) {
+                self.fail_check(&mut state, &game_id, Some(player_id), now, &mut outbox);
+                true
+            } else {
+                false
+            }
+        };
+        self.deliver(outbox);
+        cancelled
+    }
+
+    /// 回應確認，回傳遊戲是否因此開始
+    pub fn respond(&self, player_id: &str, game_id: &str, accept: bool, now: Instant) -> Result<bool, MatchmakingError> {
+        let mut outbox = Outbox::new();
+        let started = {
+            let mut state = self.state.lock().unwrap();
+            let check = state
+                .pending
+                .get_mut(game_id)
+                .filter(|check| check.tickets.iter().any(|t| t.player_id == player_id))
+                .ok_or(MatchmakingError::MatchNotFound)?;
+
+            if !accept {
+                self.fail_check(&mut state, game_id, Some(player_id), now, &mut outbox);
+                false
+            } else {
+                check.accepted.insert(player_id.to_string());
+                if check.accepted.len() == check.tickets.len() {
+                    state.pending.remove(game_id);
+                    self.starter.start(game_id);
+                    true
+                } else {
+                    false
+                }
+            }
+        };
+        self.deliver(outbox);
+        Ok(started)
+    }
+
+    /// 處理確認逾時並嘗試湊桌
+    pub fn tick(&self, now: Instant) {
+        let mut outbox = Outbox::new();
+        {
+            let mut state = self.state.lock().unwrap();
+            let expired: Vec<String> = state
+                .pending
+                .iter()
+                .filter(|(_, check)| check.deadline <= now)
+                .map(|(game_id, _)| game_id.clone())
+                .collect();
+            for game_id in expired {
+                self.fail_check(&mut state, &game_id, None, now, &mut outbox);
+            }
+            self.form_lobbies(&mut state, now, &mut outbox);
+        }
+        self.deliver(outbox);
+    }
+
+    /// 背景任務：定期呼叫 `tick`
+    pub async fn run(self: Arc<Self>) {
+        let mut interval = tokio::time::interval(TICK_INTERVAL);
+        loop {
+            interval.tick().await;
+            self.tick(Instant::now());
+        }
+    }
+
+    /// 依排隊順序湊出所有能成立的遊戲
+    fn form_lobbies(&self, state: &mut QueueState, now: Instant, outbox: &mut Outbox) {
+        while let Some(indices) = self.find_lobby(&state.queue, now) {
+            let mut tickets = Vec::with_capacity(indices.len());
+            for index in indices.into_iter().rev() {
+                tickets.push(state.queue.remove(index));
+            }
+            tickets.reverse();
+
+            let players: Vec<String> = tickets.iter().map(|t| t.player_id.clone()).collect();
+            let game = self.games.create(rand::random(), players.clone());
+            info!("Match found: {} with {:?}", game.id, players);
+            let message = WsResponse::new(
+                "MatchFound",
+                json!({
+                    "gameId": game.id,
+                    "players": players,
+                    "readyCheckSecs": self.config.ready_check_secs,
+                }),
+            );
+            outbox.extend(players.iter().map(|p| (p.clone(), message.clone())));
+            state.pending.insert(
+                game.id,
+                ReadyCheck {
+                    tickets,
+                    accepted: HashSet::new(),
+                    deadline: now + self.config.ready_check(),
+                },
+            );
+        }
+    }
+
+    /// 以排隊最久的玩家為基準，找出 rating 在範圍內的一桌
+    fn find_lobby(&self, queue: &[Ticket], now: Instant) -> Option<Vec<usize>> {
+        let size = self.config.lobby_size;
+        if queue.len() < size {
+            return None;
+        }
+        queue.iter().find_map(|anchor| {
+            let window = self.config.rating_window_after(now.saturating_duration_since(anchor.queued_at));
+            let members: Vec<usize> = queue
+                .iter()
+                .enumerate()
+                .filter(|(_, t)| window.is_none_or(|w| t.rating.abs_diff(anchor.rating) <= w))
+                .map(|(i, _)| i)
+                .take(size)
+                .collect();
+            (members.len() == size).then_some(members)
+        })
+    }
+
+    /// 確認失敗：取消這場遊戲並把玩家放回佇列
+    /// - `decliner`: 主動拒絕的玩家，不會放回佇列；為 `None` 時代表確認逾時
+    fn fail_check(&self, state: &mut QueueState, game_id: &str, decliner: Option<&str>, now: Instant, outbox: &mut Outbox) {
+        let Some(check) = state.pending.remove(game_id) else {
+            return;
+        };
+        self.games.remove(game_id);
+        let reason = if decliner.is_some() { "declined" } else { "ready check timed out" };
+        info!("Match {} cancelled: {}", game_id, reason);
+
+        for mut ticket in check.tickets {
+            let accepted = check.accepted.contains(&ticket.player_id);
+            let requeued = if Some(ticket.player_id.as_str()) == decliner {
+                false
+            } else if accepted || decliner.is_some() {
+                // 已確認（或還來不及回應）的玩家保留原本的排隊時間
+                state.requeue(ticket.clone());
+                true
+            } else if self.registry.is_connected(&ticket.player_id) {
+                // 逾時未回應的玩家排到最後；已斷線的玩家直接移出佇列
+                ticket.queued_at = now;
+                state.requeue(ticket.clone());
+                true
+            } else {
+                false
+            };
+            let message = WsResponse::new(
+                "MatchCancelled",
+                json!({ "gameId": game_id, "reason": reason, "requeued": requeued }),
+            );
+            outbox.push((ticket.player_id, message));
+        }
+    }
+
+    fn deliver(&self, outbox: Outbox) {
+        for (player_id, message) in outbox {
+            let _ = self.registry.push(&player_id, message);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connection::TestClients;
+    use crate::control::StateSync;
+    use crate::game::{GameRules, GameStatus};
+
+    struct Fixture {
+        matchmaker: Matchmaker,
+        games: Arc<GameRegistry>,
+        registry: Arc<ConnectionRegistry>,
+        clients: TestClients,
+    }
+
+    impl Fixture {
+        fn new(config: MatchmakingConfig, players: &[(&str, u32)]) -> Self {
+            let player_manager = Arc::new(PlayerManager::new(GameRules::default()));
+            let games = Arc::new(GameRegistry::new());
+            let registry = Arc::new(ConnectionRegistry::new());
+            for &(id, rating) in players {
+                let mut player = player_manager.create_player(id);
+                player.rating = rating;
+                player_manager.update_player(player);
+            }
+            let ids: Vec<&str> = players.iter().map(|&(id, _)| id).collect();
+            let clients = TestClients::connect(&registry, &ids, None);
+            let sync = Arc::new(StateSync::new(player_manager.clone(), registry.clone()));
+            let starter = Arc::new(GameStarter::new(games.clone(), player_manager.clone(), registry.clone(), sync));
+            let matchmaker = Matchmaker::new(config, player_manager, games.clone(), registry.clone(), starter);
+            Self { matchmaker, games, registry, clients }
+        }
+
+        fn pushes(&mut self, player_id: &str) -> Vec<serde_json::Value> {
+            self.clients
+                .pushes(player_id)
+                .into_iter()
+                .map(|response| json!({ "type": response.type_, "payload": response.payload }))
+                .collect()
+        }
+    }
+
+    fn config(lobby_size: usize) -> MatchmakingConfig {
+        MatchmakingConfig { lobby_size, ..MatchmakingConfig::default() }
+    }
+
+    #[test]
+    fn test_full_lobby_starts_after_everyone_accepts() {
+        let ids: Vec<String> = (1..=8).map(|i| format!("q{}", i)).collect();
+        let players: Vec<(&str, u32)> = ids.iter().map(|id| (id.as_str(), 1000)).collect();
+        let mut fixture = Fixture::new(MatchmakingConfig::default(), &players);
+        let now = Instant::now();
+
+        for id in &ids[..7] {
+            fixture.matchmaker.enqueue(id, now).unwrap();
+        }
+        assert!(fixture.pushes("q1").is_empty());
+        assert_eq!(fixture.matchmaker.enqueue("q1", now), Err(MatchmakingError::AlreadyQueued));
+        fixture.matchmaker.enqueue("q8", now).unwrap();
+
+        let found = fixture.pushes("q1");
+        assert_eq!(found[0]["type"], "MatchFound");
+        let game_id = found[0]["payload"]["gameId"].as_str().unwrap().to_string();
+        assert_eq!(found[0]["payload"]["players"].as_array().unwrap().len(), 8);
+
+        for id in &ids {
+            let started = fixture.matchmaker.respond(id, &game_id, true, now).unwrap();
+            assert_eq!(started, id == "q8");
+        }
+        assert_eq!(fixture.games.get(&game_id).unwrap().status, GameStatus::InProgress);
+        assert_eq!(fixture.registry.game_of("q3"), Some(game_id.clone()));
+        assert_eq!(fixture.pushes("q3")[1]["type"], "GameStarted");
+        assert_eq!(fixture.matchmaker.enqueue("q3", now), Err(MatchmakingError::AlreadyInGame));
+    }
+
+    #[test]
+    fn test_ready_check_timeout_requeues_players() {
+        let mut fixture = Fixture::new(config(3), &[("a", 1000), ("b", 1000), ("c", 1000), ("d", 1000)]);
+        let now = Instant::now();
+        for id in ["a", "b", "c"] {
+            fixture.matchmaker.enqueue(id, now).unwrap();
+        }
+        let game_id = fixture.pushes("a")[0]["payload"]["gameId"].as_str().unwrap().to_string();
+        fixture.matchmaker.respond("a", &game_id, true, now).unwrap();
+        fixture.matchmaker.respond("b", &game_id, true, now).unwrap();
+        fixture.matchmaker.enqueue("d", now + Duration::from_secs(1)).unwrap();
+
+        // c 沒有回應：逾時後 a、b 保留順位，c 排在 d 後面，並立即湊成新的一桌（a、b、d）
+        fixture.matchmaker.tick(now + Duration::from_secs(16));
+        assert!(fixture.games.get(&game_id).is_none());
+        let cancelled = &fixture.pushes("c")[1];
+        assert_eq!(cancelled["type"], "MatchCancelled");
+        assert_eq!(cancelled["payload"]["requeued"], true);
+
+        let pushes = fixture.pushes("d");
+        assert_eq!(pushes[0]["type"], "MatchFound");
+        assert_eq!(pushes[0]["payload"]["players"], json!(["a", "b", "d"]));
+        assert!(fixture.matchmaker.cancel("c", now));
+
+        // 主動拒絕的玩家離開佇列，其他人放回佇列
+        let next_game = pushes[0]["payload"]["gameId"].as_str().unwrap().to_string();
+        fixture.matchmaker.respond("d", &next_game, false, now).unwrap();
+        assert_eq!(fixture.pushes("d")[0]["payload"]["requeued"], false);
+        assert_eq!(fixture.pushes("a").last().unwrap()["payload"]["requeued"], true);
+        assert_eq!(fixture.matchmaker.respond("d", &next_game, true, now), Err(MatchmakingError::MatchNotFound));
+    }
+
+    #[test]
+    fn test_rating_window_widens_over_time() {
+        let mut config = config(2);
+        config.rating_window = Some(100);
+        config.rating_window_growth = 10;
+        let mut fixture = Fixture::new(config, &[("low", 1000), ("high", 1500)]);
+        let now = Instant::now();
+        fixture.matchmaker.enqueue("low", now).unwrap();
+        fixture.matchmaker.enqueue("high", now).unwrap();
+
+        fixture.matchmaker.tick(now + Duration::from_secs(30));
+        assert!(fixture.pushes("low").is_empty());
+        fixture.matchmaker.tick(now + Duration::from_secs(40));
+        assert_eq!(fixture.pushes("low")[0]["type"], "MatchFound");
+    }
+}
@@ -0,0 +1,28 @@
+use super::{Middleware, Next};
+use crate::connection::ConnectionContext;
+use crate::types::response::{ErrorCode, WsRequest, WsResponse};
+use async_trait::async_trait;
+use std::collections::HashSet;
+
+/// 未登入的連線只能使用公開的 action
+pub struct AuthLayer {
+    public_actions: HashSet<&'static str>,
+}
+
+impl AuthLayer {
+    pub fn new(public_actions: &[&'static str]) -> Self {
+        Self {
+            public_actions: public_actions.iter().copied().collect(),
+        }
+    }
+}
+
+#[async_trait]
+impl Middleware for AuthLayer {
+    async fn handle(&self, ctx: &ConnectionContext, request: &WsRequest, next: Next<'_>) -> WsResponse {
+        if ctx.player_id().is_none() && !self.public_actions.contains(request.type_.as_str()) {
+            return WsResponse::error(ErrorCode::Unauthenticated, "login required");
+        }
+        next.run(ctx, request).await
+    }
+}
@@ -0,0 +1,28 @@
+use super::{Middleware, Next};
+use crate::connection::ConnectionContext;
+use crate::types::response::{WsRequest, WsResponse};
+use async_trait::async_trait;
+use futures_util::FutureExt;
+use log::*;
+use std::panic::AssertUnwindSafe;
+
+/// 攔截處理器中的 panic，轉成 internal server error 回應而不是中斷整條連線
+pub struct CatchPanicLayer;
+
+#[async_trait]
+impl Middleware for CatchPanicLayer {
+    async fn handle(&self, ctx: &ConnectionContext, request: &WsRequest, next: Next<'_>) -> WsResponse {
+        match AssertUnwindSafe(next.run(ctx, request)).catch_unwind().await {
+            Ok(response) => response,
+            Err(panic) => {
+                let reason = panic
+                    .downcast_ref::<&str>()
+                    .map(|s| s.to_string())
+                    .or_else(|| panic.downcast_ref::<String>().cloned())
+                    .unwrap_or_else(|| "unknown panic".to_string());
+                error!("Handler for {} panicked ({}): {}", request.type_, ctx.peer_addr(), reason);
+                WsResponse::internal_server_error()
+            }
+        }
+    }
+}
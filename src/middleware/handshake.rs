@@ -0,0 +1,57 @@
+use super::{Middleware, Next};
+use crate::connection::ConnectionContext;
+use crate::handlers::hello::UPGRADE_REQUIRED_CLOSE_CODE;
+use crate::handlers::{HelloHandler, TypedHandler};
+use crate::types::response::{ErrorCode, WsRequest, WsResponse};
+use async_trait::async_trait;
+
+/// 完成 `Hello` 握手之前只接受 `Hello`，其他 action 回傳 `HANDSHAKE_REQUIRED` 並關閉連線，
+/// 避免客戶端略過握手而繞過版本檢查
+pub struct HandshakeLayer;
+
+#[async_trait]
+impl Middleware for HandshakeLayer {
+    async fn handle(&self, ctx: &ConnectionContext, request: &WsRequest, next: Next<'_>) -> WsResponse {
+        if ctx.handshake().is_none() && request.type_ != HelloHandler::ACTION {
+            ctx.close_after_reply(UPGRADE_REQUIRED_CLOSE_CODE, "hello required");
+            return WsResponse::error(ErrorCode::HandshakeRequired, "send Hello before any other action");
+        }
+        next.run(ctx, request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connection::{ConnectionHandle, Outbound};
+    use crate::handlers::{PingHandler, Typed};
+    use crate::router::Router;
+    use crate::types::protocol::PROTOCOL_VERSION;
+    use serde_json::json;
+    use std::sync::Arc;
+    use tokio::sync::mpsc;
+
+    fn request(action: &str, payload: serde_json::Value) -> WsRequest {
+        WsRequest { type_: action.to_string(), payload, request_id: None }
+    }
+
+    #[tokio::test]
+    async fn test_actions_before_hello_are_rejected() {
+        let mut router = Router::new();
+        router.add_handler(Arc::new(Typed(HelloHandler))).unwrap();
+        router.add_handler(Arc::new(PingHandler)).unwrap();
+        router.layer(Arc::new(HandshakeLayer));
+        let (sender, mut receiver) = mpsc::channel(8);
+        let ctx = ConnectionContext::new(ConnectionHandle::new(1, sender), "127.0.0.1:9000".parse().unwrap());
+
+        let response = router.handle(&ctx, &request("ping", json!({}))).await;
+        assert_eq!(response.payload.unwrap()["code"], "HANDSHAKE_REQUIRED");
+        ctx.flush_after_reply().await.unwrap();
+        assert!(matches!(receiver.try_recv(), Ok(Outbound::Close(Some(frame))) if frame.code == UPGRADE_REQUIRED_CLOSE_CODE));
+
+        let response = router.handle(&ctx, &request("Hello", json!({ "protocolVersion": PROTOCOL_VERSION }))).await;
+        assert_eq!(response.type_, "HelloResult");
+        let response = router.handle(&ctx, &request("ping", json!({}))).await;
+        assert_eq!(response.payload.unwrap()["pong"], true);
+    }
+}
@@ -0,0 +1,52 @@
+// 中介層：包在 Router 分派外圍的橫切邏輯（計時、日誌、限流、panic 攔截…）
+//
+// 在 main.rs 建立 Router 時以 `router.layer(...)` 依序疊加，先加入的在最外層。
+// 每一層都能看到請求、連線上下文與回應，也可以不呼叫 `next` 直接回傳（short-circuit）。
+
+use crate::connection::{ConnectionContext, ConnectionId};
+use crate::router::Router;
+use crate::types::response::{WsRequest, WsResponse};
+use async_trait::async_trait;
+use std::sync::Arc;
+
+pub mod auth;
+pub mod catch_panic;
+pub mod handshake;
+pub mod rate_limit;
+pub mod spectator;
+pub mod timing;
+
+pub use auth::AuthLayer;
+pub use catch_panic::CatchPanicLayer;
+pub use handshake::HandshakeLayer;
+pub use rate_limit::RateLimitLayer;
+pub use spectator::SpectatorLayer;
+pub use timing::TimingLayer;
+
+#[async_trait]
+pub trait Middleware: Send + Sync {
+    async fn handle(&self, ctx: &ConnectionContext, request: &WsRequest, next: Next<'_>) -> WsResponse;
+
+    /// 連線結束時呼叫，釋放為該連線保留的狀態
+    fn on_disconnect(&self, _connection_id: ConnectionId) {}
+}
+
+/// 剩下的中介層與最終的 Router 分派
+pub struct Next<'a> {
+    layers: &'a [Arc<dyn Middleware>],
+    router: &'a Router,
+}
+
+impl<'a> Next<'a> {
+    pub fn new(layers: &'a [Arc<dyn Middleware>], router: &'a Router) -> Self {
+        Self { layers, router }
+    }
+
+    /// 交給下一層處理
+    pub async fn run(self, ctx: &ConnectionContext, request: &WsRequest) -> WsResponse {
+        match self.layers.split_first() {
+            Some((layer, rest)) => layer.handle(ctx, request, Next::new(rest, self.router)).await,
+            None => self.router.dispatch(ctx, request).await,
+        }
+    }
+}
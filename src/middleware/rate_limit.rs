@@ -0,0 +1,96 @@
+use super::{Middleware, Next};
+use crate::connection::{ConnectionContext, ConnectionId};
+use crate::types::response::{ErrorCode, WsRequest, WsResponse};
+use async_trait::async_trait;
+use log::*;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// Token bucket
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+/// 以連線為單位的限流（token bucket）
+/// - `per_second`: 每秒補充的請求數
+/// - `burst`: 桶的容量，允許短時間內的突發請求
+///
+/// 連線結束時丟掉該連線的桶
+pub struct RateLimitLayer {
+    per_second: f64,
+    burst: f64,
+    buckets: Mutex<HashMap<ConnectionId, Bucket>>,
+}
+
+impl RateLimitLayer {
+    pub fn new(per_second: u32, burst: u32) -> Self {
+        Self {
+            per_second: per_second as f64,
+            burst: burst.max(1) as f64,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 嘗試取出一個 token，沒有 token 時回傳 false
+    fn try_acquire(&self, connection_id: ConnectionId) -> bool {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap();
+        let bucket = buckets.entry(connection_id).or_insert(Bucket {
+            tokens: self.burst,
+            last_refill: now,
+        });
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.per_second).min(self.burst);
+        bucket.last_refill = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[async_trait]
+impl Middleware for RateLimitLayer {
+    async fn handle(&self, ctx: &ConnectionContext, request: &WsRequest, next: Next<'_>) -> WsResponse {
+        if !self.try_acquire(ctx.id()) {
+            debug!("Rate limited {} from {}", request.type_, ctx.peer_addr());
+            return WsResponse::error(ErrorCode::RateLimited, "too many requests");
+        }
+        next.run(ctx, request).await
+    }
+
+    fn on_disconnect(&self, connection_id: ConnectionId) {
+        self.buckets.lock().unwrap().remove(&connection_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_burst_then_limited() {
+        let layer = RateLimitLayer::new(0, 3);
+        assert!(layer.try_acquire(1));
+        assert!(layer.try_acquire(1));
+        assert!(layer.try_acquire(1));
+        assert!(!layer.try_acquire(1));
+        // 不同連線各自計算
+        assert!(layer.try_acquire(2));
+    }
+
+    #[test]
+    fn test_bucket_dropped_on_disconnect() {
+        let layer = RateLimitLayer::new(0, 1);
+        assert!(layer.try_acquire(1));
+        assert!(layer.try_acquire(2));
+        layer.on_disconnect(1);
+        assert_eq!(layer.buckets.lock().unwrap().len(), 1);
+        assert!(!layer.try_acquire(2));
+    }
+}
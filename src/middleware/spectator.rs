@@ -0,0 +1,31 @@
+use super::{Middleware, Next};
+use crate::connection::{ConnectionContext, ConnectionRegistry};
+use crate::types::response::{ErrorCode, WsRequest, WsResponse};
+use async_trait::async_trait;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// 觀戰中的連線只能使用唯讀的 action，不能操作任何遊戲
+pub struct SpectatorLayer {
+    registry: Arc<ConnectionRegistry>,
+    read_only_actions: HashSet<&'static str>,
+}
+
+impl SpectatorLayer {
+    pub fn new(registry: Arc<ConnectionRegistry>, read_only_actions: &[&'static str]) -> Self {
+        Self {
+            registry,
+            read_only_actions: read_only_actions.iter().copied().collect(),
+        }
+    }
+}
+
+#[async_trait]
+impl Middleware for SpectatorLayer {
+    async fn handle(&self, ctx: &ConnectionContext, request: &WsRequest, next: Next<'_>) -> WsResponse {
+        if !self.read_only_actions.contains(request.type_.as_str()) && self.registry.spectating(ctx.id()).is_some() {
+            return WsResponse::error(ErrorCode::SpectatorReadOnly, "spectators cannot send game actions, send StopSpectating first");
+        }
+        next.run(ctx, request).await
+    }
+}
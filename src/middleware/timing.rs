@@ -0,0 +1,33 @@
+use super::{Middleware, Next};
+use crate::connection::ConnectionContext;
+use crate::types::response::{WsRequest, WsResponse};
+use async_trait::async_trait;
+use log::*;
+use std::time::{Duration, Instant};
+
+/// 記錄每個 action 的處理時間，超過門檻時以 warn 輸出
+pub struct TimingLayer {
+    slow_threshold: Duration,
+}
+
+impl TimingLayer {
+    pub fn new(slow_threshold: Duration) -> Self {
+        Self { slow_threshold }
+    }
+}
+
+#[async_trait]
+impl Middleware for TimingLayer {
+    async fn handle(&self, ctx: &ConnectionContext, request: &WsRequest, next: Next<'_>) -> WsResponse {
+        let started = Instant::now();
+        let response = next.run(ctx, request).await;
+        let elapsed = started.elapsed();
+
+        if elapsed >= self.slow_threshold {
+            warn!("[{}] {} -> {} took {:?}", ctx.peer_addr(), request.type_, response.type_, elapsed);
+        } else {
+            info!("[{}] {} -> {} in {:?}", ctx.peer_addr(), request.type_, response.type_, elapsed);
+        }
+        response
+    }
+}
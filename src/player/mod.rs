@@ -1,12 +1,198 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::{Arc, Mutex};
+use std::fmt;
+use std::io;
+use std::path::Path;
 use serde::{Serialize, Deserialize};
+use rand::seq::SliceRandom;
+use rand::thread_rng;
+use crate::chesses::items::catalog::{combine, find_item};
+use crate::data::{all_chess_pieces, find_chess, initial_experience};
+use crate::game::pool::copies_of;
+use crate::game::augments::{find_augment, modifiers_of, roll_options, AugmentOffer, AugmentTier, Modifiers, AUGMENT_REROLLS};
+use crate::game::creeps::{Loot, LootDrop};
+use crate::game::{GameRules, UnitPool};
+use crate::types::game_state::{ItemOnBench, ShopUnit, UnitOnBench, UnitOnBoard};
+use crate::types::response::ErrorCode;
+
+pub const BENCH_SIZE: usize = 9;    // 備戰區容量
+pub const BOARD_WIDTH: u32 = 7;     // 棋盤寬（x）
+pub const BOARD_HEIGHT: u32 = 4;    // 己方半場高（y）
+pub const STARTING_HP: i32 = 100;   // 玩家初始生命
+pub const MAX_ITEMS_PER_UNIT: usize = 3; // 每個單位最多裝備的道具數
+pub const MAX_STAR_LEVEL: u32 = 3;       // 單位最高星級
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlayerData {
     pub id: String,
     pub money: i32,
     pub xp: XPData,
+    /// 斷線超過寬限期，由回合流程代為操作
+    #[serde(default)]
+    pub afk: bool,
+    /// 玩家等級，決定棋盤上可放的單位數
+    #[serde(default = "default_level")]
+    pub level: u32,
+    #[serde(default)]
+    pub board: Vec<UnitOnBoard>,
+    #[serde(default)]
+    pub bench: Vec<UnitOnBench>,
+    /// 已購買的格子為 None，重新整理前維持空位
+    #[serde(default)]
+    pub shop: Vec<Option<ShopUnit>>,
+    /// 下一個單位編號
+    #[serde(default)]
+    pub next_unit_id: u32,
+    /// 道具欄
+    #[serde(default)]
+    pub items: Vec<ItemOnBench>,
+    /// 下一個道具編號
+    #[serde(default)]
+    pub next_item_id: u32,
+    /// 配對用的積分
+    #[serde(default = "default_rating")]
+    pub rating: u32,
+    /// 玩家生命，輸掉戰鬥時扣除，歸零即淘汰
+    #[serde(default = "default_hp")]
+    pub hp: i32,
+    /// 已選擇的強化符文
+    #[serde(default)]
+    pub augments: Vec<String>,
+    /// 尚未決定的符文選擇
+    #[serde(default)]
+    pub augment_offer: Option<AugmentOffer>,
+    /// 本回合剩餘的免費重新整理次數
+    #[serde(default)]
+    pub free_refreshes: u32,
+}
+
+fn default_level() -> u32 {
+    1
+}
+
+fn default_rating() -> u32 {
+    1000
+}
+
+fn default_hp() -> i32 {
+    STARTING_HP
+}
+
+impl PlayerData {
+    fn new(id: &str, rules: &GameRules, pool: Option<&UnitPool>) -> Self {
+        Self {
+            id: id.to_string(),
+            money: rules.starting_gold,
+            xp: XPData {
+                current: initial_experience() as i32,
+                required: 2,
+            },
+            afk: false,
+            level: default_level(),
+            board: Vec::new(),
+            bench: Vec::new(),
+            shop: roll_shop(rules, pool, &Modifiers::default()),
+            next_unit_id: 1,
+            items: Vec::new(),
+            next_item_id: 1,
+            rating: default_rating(),
+            hp: default_hp(),
+            augments: Vec::new(),
+            augment_offer: None,
+            free_refreshes: 0,
+        }
+    }
+
+    /// 所有強化符文的修正總和
+    pub fn modifiers(&self) -> Modifiers {
+        modifiers_of(&self.augments)
+    }
+
+    fn allocate_unit_id(&mut self) -> String {
+        let id = format!("u{:03}", self.next_unit_id);
+        self.next_unit_id += 1;
+        id
+    }
+
+    fn allocate_item_id(&mut self) -> String {
+        let id = format!("i{:03}", self.next_item_id);
+        self.next_item_id += 1;
+        id
+    }
+
+    /// 已經有兩隻同名同星的單位，再加一隻就會合成
+    fn completes_set(&self, chess: &str, level: u32) -> bool {
+        level < MAX_STAR_LEVEL
+            && self.board.iter().map(|u| (&u.chess, u.level)).chain(self.bench.iter().map(|u| (&u.chess, u.level))).filter(|&(c, l)| c == chess && l == level).count() >= 2
+    }
+
+    /// 三隻同名同星的單位自動合成一隻高一星的單位（可以連鎖）；
+    /// 優先保留棋盤上的那一隻，另外兩隻的道具移到合成後的單位，放不下的回到道具欄
+    fn merge_copies(&mut self) {
+        loop {
+            let mut counts: BTreeMap<(&str, u32), usize> = BTreeMap::new();
+            for (chess, level) in self.board.iter().map(|u| (&u.chess, u.level)).chain(self.bench.iter().map(|u| (&u.chess, u.level))) {
+                if level < MAX_STAR_LEVEL {
+                    *counts.entry((chess.as_str(), level)).or_default() += 1;
+                }
+            }
+            let Some((chess, level)) = counts.into_iter().find(|(_, count)| *count >= 3).map(|((chess, level), _)| (chess.to_string(), level)) else {
+                return;
+            };
+
+            let is_copy = |unit_chess: &str, unit_level: u32| unit_chess == chess && unit_level == level;
+            let copies: Vec<(bool, usize)> = (0..self.board.len())
+                .filter(|&i| is_copy(&self.board[i].chess, self.board[i].level))
+                .map(|i| (true, i))
+                .chain((0..self.bench.len()).filter(|&i| is_copy(&self.bench[i].chess, self.bench[i].level)).map(|i| (false, i)))
+                .take(3)
+                .collect();
+            // 被合掉的兩隻排在保留的那一隻之後，由後往前移除不影響保留的索引
+            let mut items = Vec::new();
+            for &(on_board, index) in copies[1..].iter().rev() {
+                let removed = if on_board { self.board.remove(index).items } else { self.bench.remove(index).items };
+                items.extend(removed);
+            }
+            let (kept_level, kept_items) = match copies[0] {
+                (true, index) => {
+                    let unit = &mut self.board[index];
+                    (&mut unit.level, &mut unit.items)
+                }
+                (false, index) => {
+                    let unit = &mut self.bench[index];
+                    (&mut unit.level, &mut unit.items)
+                }
+            };
+            *kept_level += 1;
+            let room = MAX_ITEMS_PER_UNIT.saturating_sub(kept_items.len());
+            let overflow = items.split_off(room.min(items.len()));
+            kept_items.extend(items);
+            for item in overflow {
+                let id = self.allocate_item_id();
+                self.items.push(ItemOnBench { id, item });
+            }
+        }
+    }
+}
+
+/// 從規則允許、且共用棋子池中還有剩的棋子隨機產生一組商店；符文可以增加格數與調整各費用的機率
+fn roll_shop(rules: &GameRules, pool: Option<&UnitPool>, modifiers: &Modifiers) -> Vec<Option<ShopUnit>> {
+    let mut rng = thread_rng();
+    let candidates: Vec<_> = all_chess_pieces()
+        .into_iter()
+        .filter(|cp| rules.allows(&cp.name))
+        .filter(|cp| pool.is_none_or(|pool| pool.remaining(&cp.name) > 0))
+        .collect();
+    let size = (rules.shop_size + modifiers.shop_slots).min(candidates.len());
+    candidates.choose_multiple_weighted(&mut rng, size, |cp| modifiers.shop_weight(cp.cost) as f64)
+        .map(|chosen| chosen.map(|cp| Some(ShopUnit { chess: cp.name.clone(), level: 1 })).collect())
+        .unwrap_or_default()
+}
+
+/// 單位售價：同名棋子每升一星需要三隻
+fn sell_value(chess: &str, level: u32) -> i32 {
+    let cost = find_chess(chess).map(|cp| cp.cost).unwrap_or(1);
+    (cost * copies_of(level)) as i32
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,32 +201,146 @@ pub struct XPData {
     pub required: i32,
 }
 
+/// 玩家操作失敗原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlayerError {
+    NotFound,
+    NotEnoughMoney,
+    UnitNotFound,
+    SlotEmpty,
+    BenchFull,
+    BoardFull,
+    InvalidPosition,
+    SoldOut,
+    ItemNotFound,
+    TooManyItems,
+    NoAugmentOffer,
+    AugmentNotOffered,
+    NoRerollsLeft,
+    Eliminated,
+}
+
+impl PlayerError {
+    /// 對應的協定錯誤代碼
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            PlayerError::NotFound => ErrorCode::PlayerNotFound,
+            PlayerError::NotEnoughMoney => ErrorCode::NotEnoughMoney,
+            PlayerError::UnitNotFound => ErrorCode::UnitNotFound,
+            PlayerError::SlotEmpty => ErrorCode::SlotEmpty,
+            PlayerError::BenchFull => ErrorCode::BenchFull,
+            PlayerError::BoardFull => ErrorCode::BoardFull,
+            PlayerError::InvalidPosition => ErrorCode::InvalidPosition,
+            PlayerError::SoldOut => ErrorCode::SoldOut,
+            PlayerError::ItemNotFound => ErrorCode::ItemNotFound,
+            PlayerError::TooManyItems => ErrorCode::TooManyItems,
+            PlayerError::NoAugmentOffer => ErrorCode::NoAugmentOffer,
+            PlayerError::AugmentNotOffered => ErrorCode::InvalidField,
+            PlayerError::NoRerollsLeft => ErrorCode::NoRerollsLeft,
+            PlayerError::Eliminated => ErrorCode::Eliminated,
+        }
+    }
+}
+
+impl fmt::Display for PlayerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlayerError::NotFound => write!(f, "player not found"),
+            PlayerError::NotEnoughMoney => write!(f, "not enough money"),
+            PlayerError::UnitNotFound => write!(f, "unit not found"),
+            PlayerError::SlotEmpty => write!(f, "shop slot is empty"),
+            PlayerError::BenchFull => write!(f, "bench is full"),
+            PlayerError::BoardFull => write!(f, "board is full for current level"),
+            PlayerError::InvalidPosition => write!(f, "position is outside the board"),
+            PlayerError::SoldOut => write!(f, "no copies of this unit are left in the pool"),
+            PlayerError::ItemNotFound => write!(f, "item not found"),
+            PlayerError::TooManyItems => write!(f, "unit already holds the maximum number of items"),
+            PlayerError::NoAugmentOffer => write!(f, "no augment choice is pending"),
+            PlayerError::AugmentNotOffered => write!(f, "augmentId is not one of the offered augments"),
+            PlayerError::NoRerollsLeft => write!(f, "no augment rerolls left"),
+            PlayerError::Eliminated => write!(f, "player has been eliminated"),
+        }
+    }
+}
+
+/// 還在遊戲中的玩家；生命歸零（已淘汰）的玩家不能再操作
+fn active<'a>(players: &'a mut HashMap<String, PlayerData>, player_id: &str) -> Result<&'a mut PlayerData, PlayerError> {
+    let player = players.get_mut(player_id).ok_or(PlayerError::NotFound)?;
+    if player.hp <= 0 {
+        return Err(PlayerError::Eliminated);
+    }
+    Ok(player)
+}
+
 pub struct PlayerManager {
     players: Arc<Mutex<HashMap<String, PlayerData>>>,
+    /// 不在遊戲中的玩家使用的規則（伺服器設定）
+    defaults: Arc<GameRules>,
+    /// playerId -> 所在遊戲的規則
+    rules: Mutex<HashMap<String, Arc<GameRules>>>,
+    /// playerId -> 所在遊戲的共用棋子池
+    pools: Mutex<HashMap<String, Arc<UnitPool>>>,
 }
 
 impl PlayerManager {
-    pub fn new() -> Self {
+    pub fn new(defaults: GameRules) -> Self {
         let mut map = HashMap::new();
     
-        // 插入預設玩家 "1"
-        map.insert(
-            "p1".to_string(),
-            PlayerData {
-                id: "p1".to_string(),
-                money: 100,
-                xp: XPData {
-                    current: 0,
-                    required: 2,
-                },
-            },
-        );
+        // 插入預設玩家 "p1"
+        map.insert("p1".to_string(), PlayerData::new("p1", &defaults, None));
     
         Self {
             players: Arc::new(Mutex::new(map)),
+            defaults: Arc::new(defaults),
+            rules: Mutex::new(HashMap::new()),
+            pools: Mutex::new(HashMap::new()),
         }
     }
-    
+
+    /// 玩家目前適用的規則
+    pub fn rules_of(&self, player_id: &str) -> Arc<GameRules> {
+        self.rules
+            .lock()
+            .unwrap()
+            .get(player_id)
+            .cloned()
+            .unwrap_or_else(|| self.defaults.clone())
+    }
+
+    /// 玩家所在遊戲的共用棋子池；不在遊戲中時沒有數量限制
+    pub fn pool_of(&self, player_id: &str) -> Option<Arc<UnitPool>> {
+        self.pools.lock().unwrap().get(player_id).cloned()
+    }
+
+    /// 遊戲開始：依遊戲規則重置玩家的金幣、生命、棋子與商店（保留積分）
+    pub fn start_game(&self, player_id: &str, rules: Arc<GameRules>, pool: Arc<UnitPool>) -> PlayerData {
+        let mut players = self.players.lock().unwrap();
+        let mut player_data = PlayerData::new(player_id, &rules, Some(&pool));
+        if let Some(previous) = players.get(player_id) {
+            player_data.rating = previous.rating;
+        }
+        players.insert(player_id.to_string(), player_data.clone());
+        self.rules.lock().unwrap().insert(player_id.to_string(), rules);
+        self.pools.lock().unwrap().insert(player_id.to_string(), pool);
+        player_data
+    }
+
+    /// 伺服器重啟後接續遊戲：保留玩家狀態，重新登記規則與棋子池，並從池中取回已持有的棋子
+    pub fn resume_game(&self, player_id: &str, rules: Arc<GameRules>, pool: Arc<UnitPool>) {
+        if let Some(player) = self.players.lock().unwrap().get(player_id) {
+            for (chess, level) in player.board.iter().map(|u| (&u.chess, u.level)).chain(player.bench.iter().map(|u| (&u.chess, u.level))) {
+                pool.take(chess, copies_of(level));
+            }
+        }
+        self.rules.lock().unwrap().insert(player_id.to_string(), rules);
+        self.pools.lock().unwrap().insert(player_id.to_string(), pool);
+    }
+
+    /// 遊戲結束：移除玩家登記的遊戲規則與棋子池，之後回到預設規則
+    pub fn end_game(&self, player_id: &str) {
+        self.rules.lock().unwrap().remove(player_id);
+        self.pools.lock().unwrap().remove(player_id);
+    }
 
     pub fn get_player(&self, player_id: &str) -> Option<PlayerData> {
         let players = self.players.lock().unwrap();
@@ -49,38 +349,47 @@ impl PlayerManager {
 
     pub fn create_player(&self, player_id: &str) -> PlayerData {
         let mut players = self.players.lock().unwrap();
-        let player_data = PlayerData {
-            id: player_id.to_string(),
-            money: 0,
-            xp: XPData {
-                current: 0,
-                required: 2,
-            },
-        };
+        let player_data = PlayerData::new(player_id, &self.defaults, None);
         players.insert(player_id.to_string(), player_data.clone());
         player_data
     }
 
+    #[cfg(test)]
     pub fn update_player(&self, player_data: PlayerData) {
         let mut players = self.players.lock().unwrap();
         players.insert(player_data.id.clone(), player_data);
     }
 
-    pub fn buy_xp(&self, player_id: &str) -> Result<PlayerData, String> {
+    /// 設定 AFK 狀態，回傳狀態是否有改變
+    pub fn set_afk(&self, player_id: &str, afk: bool) -> bool {
+        let mut players = self.players.lock().unwrap();
+        match players.get_mut(player_id) {
+            Some(player) if player.afk != afk => {
+                player.afk = afk;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn buy_xp(&self, player_id: &str) -> Result<PlayerData, PlayerError> {
+        let rules = self.rules_of(player_id);
         let mut players = self.players.lock().unwrap();
-        let player = players.get_mut(player_id).ok_or("Player not found")?;
+        let player = active(&mut players, player_id)?;
+        let xp_cost = (rules.xp_cost + player.modifiers().xp_cost).max(0);
         
         // 检查是否有足够的金钱
-        if player.money < 4 {
-            return Err("not enough money".to_string());
+        if player.money < xp_cost {
+            return Err(PlayerError::NotEnoughMoney);
         }
 
         // 扣除金钱并增加经验值
-        player.money -= 4;
+        player.money -= xp_cost;
         player.xp.current += 1;
 
         // 检查是否需要升级
         if player.xp.current >= player.xp.required {
+            player.level += 1;
             player.xp.current = 0;
             player.xp.required = (player.xp.required as f32 * 1.5).ceil() as i32;
         }
@@ -88,16 +397,590 @@ impl PlayerManager {
         Ok(player.clone())
     }
     
-    pub fn refresh_shop(&self, player_id: &str) -> Result<i32, String> {
+    /// 重新整理商店；有免費次數時先使用免費次數
+    pub fn refresh_shop(&self, player_id: &str) -> Result<PlayerData, PlayerError> {
+        let rules = self.rules_of(player_id);
+        let pool = self.pool_of(player_id);
         let mut players = self.players.lock().unwrap();
-        let player = players.get_mut(player_id).ok_or("Player not found")?;
+        let player = active(&mut players, player_id)?;
     
-        if player.money < 2 {
-            return Err("not enough money".to_string());
+        if player.free_refreshes > 0 {
+            player.free_refreshes -= 1;
+        } else if player.money < rules.refresh_cost {
+            return Err(PlayerError::NotEnoughMoney);
+        } else {
+            player.money -= rules.refresh_cost;
         }
-    
-        player.money -= 2;
-        Ok(player.money)
+        player.shop = roll_shop(&rules, pool.as_deref(), &player.modifiers());
+        Ok(player.clone())
+    }
+
+    /// 購買商店中的棋子，放到備戰區；湊滿三隻同名同星時自動合成
+    pub fn buy_unit(&self, player_id: &str, shop_index: usize) -> Result<PlayerData, PlayerError> {
+        let pool = self.pool_of(player_id);
+        let mut players = self.players.lock().unwrap();
+        let player = active(&mut players, player_id)?;
+
+        let offer = player
+            .shop
+            .get(shop_index)
+            .and_then(Option::as_ref)
+            .ok_or(PlayerError::SlotEmpty)?;
+        let cost = sell_value(&offer.chess, offer.level);
+        if player.money < cost {
+            return Err(PlayerError::NotEnoughMoney);
+        }
+        // 備戰區滿了，但買下會湊成三隻立刻合成時仍可購買
+        if player.bench.len() >= BENCH_SIZE && !player.completes_set(&offer.chess, offer.level) {
+            return Err(PlayerError::BenchFull);
+        }
+        if let Some(pool) = &pool {
+            if !pool.take(&offer.chess, copies_of(offer.level)) {
+                return Err(PlayerError::SoldOut);
+            }
+        }
+
+        let offer = player.shop[shop_index].take().ok_or(PlayerError::SlotEmpty)?;
+        player.money -= cost;
+        let id = player.allocate_unit_id();
+        player.bench.push(UnitOnBench { id, chess: offer.chess, level: offer.level, items: Vec::new() });
+        player.merge_copies();
+        Ok(player.clone())
+    }
+
+    /// 賣出備戰區或棋盤上的單位，裝備的道具回到道具欄
+    pub fn sell_unit(&self, player_id: &str, unit_id: &str) -> Result<PlayerData, PlayerError> {
+        let pool = self.pool_of(player_id);
+        let mut players = self.players.lock().unwrap();
+        let player = active(&mut players, player_id)?;
+
+        let (chess, level, items) = if let Some(index) = player.bench.iter().position(|u| u.id == unit_id) {
+            let unit = player.bench.remove(index);
+            (unit.chess, unit.level, unit.items)
+        } else if let Some(index) = player.board.iter().position(|u| u.id == unit_id) {
+            let unit = player.board.remove(index);
+            (unit.chess, unit.level, unit.items)
+        } else {
+            return Err(PlayerError::UnitNotFound);
+        };
+
+        for item in items {
+            let id = player.allocate_item_id();
+            player.items.push(ItemOnBench { id, item });
+        }
+        player.money += sell_value(&chess, level);
+        if let Some(pool) = &pool {
+            pool.put_back(&chess, copies_of(level));
+        }
+        Ok(player.clone())
+    }
+
+    /// 將道具欄的道具裝備到備戰區或棋盤上的單位；
+    /// 單位身上已有基礎道具時，再裝上基礎道具會自動合成（不佔新的欄位）
+    pub fn equip_item(&self, player_id: &str, item_id: &str, unit_id: &str) -> Result<PlayerData, PlayerError> {
+        let mut players = self.players.lock().unwrap();
+        let player = active(&mut players, player_id)?;
+
+        let index = player.items.iter().position(|i| i.id == item_id).ok_or(PlayerError::ItemNotFound)?;
+        let item = find_item(&player.items[index].item).ok_or(PlayerError::ItemNotFound)?;
+        let equipped = if let Some(unit) = player.bench.iter_mut().find(|u| u.id == unit_id) {
+            &mut unit.items
+        } else if let Some(unit) = player.board.iter_mut().find(|u| u.id == unit_id) {
+            &mut unit.items
+        } else {
+            return Err(PlayerError::UnitNotFound);
+        };
+
+        let combined = item
+            .is_component()
+            .then(|| {
+                equipped.iter().enumerate().find_map(|(slot, id)| {
+                    find_item(id).filter(|other| other.is_component()).and_then(|other| combine(&other.id, &item.id)).map(|c| (slot, c))
+                })
+            })
+            .flatten();
+        match combined {
+            Some((slot, combined)) => equipped[slot] = combined.id,
+            None if equipped.len() >= MAX_ITEMS_PER_UNIT => return Err(PlayerError::TooManyItems),
+            None => equipped.push(item.id),
+        }
+        player.items.remove(index);
+        Ok(player.clone())
+    }
+
+    /// 移動單位：`position` 為 None 時收回備戰區，否則放到棋盤上的指定格子
+    /// 目標格子已有單位時兩者交換位置
+    pub fn move_unit(&self, player_id: &str, unit_id: &str, position: Option<[u32; 2]>) -> Result<PlayerData, PlayerError> {
+        let mut players = self.players.lock().unwrap();
+        let player = active(&mut players, player_id)?;
+
+        let on_bench = player.bench.iter().position(|u| u.id == unit_id);
+        let on_board = player.board.iter().position(|u| u.id == unit_id);
+
+        match (position, on_bench, on_board) {
+            (_, None, None) => Err(PlayerError::UnitNotFound),
+            // 收回備戰區
+            (None, Some(_), _) => Ok(player.clone()),
+            (None, None, Some(index)) => {
+                if player.bench.len() >= BENCH_SIZE {
+                    return Err(PlayerError::BenchFull);
+                }
+                let unit = player.board.remove(index);
+                player.bench.push(UnitOnBench { id: unit.id, chess: unit.chess, level: unit.level, items: unit.items });
+                Ok(player.clone())
+            }
+            (Some(target), bench_index, board_index) => {
+                if target[0] >= BOARD_WIDTH || target[1] >= BOARD_HEIGHT {
+                    return Err(PlayerError::InvalidPosition);
+                }
+                let occupant = player.board.iter().position(|u| u.position == target);
+
+                if let Some(index) = board_index {
+                    // 棋盤上移動，若有佔位者則互換
+                    let from = player.board[index].position;
+                    if let Some(other) = occupant {
+                        player.board[other].position = from;
+                    }
+                    player.board[index].position = target;
+                } else if let Some(index) = bench_index {
+                    match occupant {
+                        // 與棋盤上的單位交換，對方回到備戰區原本的位置
+                        Some(other) => {
+                            let unit = player.bench[index].clone();
+                            let replaced = std::mem::replace(
+                                &mut player.board[other],
+                                UnitOnBoard { id: unit.id, chess: unit.chess, level: unit.level, position: target, items: unit.items },
+                            );
+                            player.bench[index] = UnitOnBench { id: replaced.id, chess: replaced.chess, level: replaced.level, items: replaced.items };
+                        }
+                        None => {
+                            if player.board.len() >= player.level as usize {
+                                return Err(PlayerError::BoardFull);
+                            }
+                            let unit = player.bench.remove(index);
+                            player.board.push(UnitOnBoard { id: unit.id, chess: unit.chess, level: unit.level, position: target, items: unit.items });
+                        }
+                    }
+                }
+                Ok(player.clone())
+            }
+        }
+    }
+
+    /// 將所有玩家寫入 JSON 檔（先寫暫存檔再改名，避免寫到一半的檔案）
+    pub fn save(&self, path: &Path) -> io::Result<usize> {
+        let mut players: Vec<PlayerData> = self.players.lock().unwrap().values().cloned().collect();
+        players.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let json = serde_json::to_vec_pretty(&players)?;
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, json)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(players.len())
+    }
+
+    /// 啟動時載入先前保存的玩家，檔案不存在時視為沒有資料
+    pub fn load(&self, path: &Path) -> io::Result<usize> {
+        let json = match std::fs::read(path) {
+            Ok(json) => json,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e),
+        };
+        let saved: Vec<PlayerData> = serde_json::from_slice(&json)?;
+
+        let mut players = self.players.lock().unwrap();
+        let count = saved.len();
+        for player in saved {
+            players.insert(player.id.clone(), player);
+        }
+        Ok(count)
+    }
+
+    /// 輸掉戰鬥扣除生命，最低為 0
+    pub fn take_damage(&self, player_id: &str, damage: i32) -> Result<PlayerData, PlayerError> {
+        let mut players = self.players.lock().unwrap();
+        let player = players.get_mut(player_id).ok_or(PlayerError::NotFound)?;
+        player.hp = (player.hp - damage.max(0)).max(0);
+        Ok(player.clone())
+    }
+
+    /// 淘汰：棋盤與備戰區的單位全部放回共用棋子池，金幣與待選的符文一併清空
+    pub fn eliminate(&self, player_id: &str) -> Result<PlayerData, PlayerError> {
+        let pool = self.pool_of(player_id);
+        let mut players = self.players.lock().unwrap();
+        let player = players.get_mut(player_id).ok_or(PlayerError::NotFound)?;
+
+        let units = player
+            .board
+            .drain(..)
+            .map(|u| (u.chess, u.level))
+            .chain(player.bench.drain(..).map(|u| (u.chess, u.level)));
+        for (chess, level) in units {
+            if let Some(pool) = &pool {
+                pool.put_back(&chess, copies_of(level));
+            }
+        }
+        player.shop.clear();
+        player.money = 0;
+        player.augment_offer = None;
+        player.hp = 0;
+        Ok(player.clone())
+    }
+
+    /// 發放野怪掉落的戰利品；隨機棋子從共用棋子池取出，備戰區已滿或池中沒有時改發等值金幣
+    pub fn grant_loot(&self, player_id: &str, loot: &[Loot]) -> Result<(PlayerData, Vec<LootDrop>), PlayerError> {
+        let rules = self.rules_of(player_id);
+        let pool = self.pool_of(player_id);
+        let mut players = self.players.lock().unwrap();
+        let player = active(&mut players, player_id)?;
+        let mut rng = thread_rng();
+
+        let mut drops = Vec::with_capacity(loot.len());
+        for item in loot {
+            match *item {
+                Loot::Gold(amount) => {
+                    player.money += amount;
+                    drops.push(LootDrop::Gold { amount });
+                }
+                Loot::Item(item) => {
+                    let item_id = player.allocate_item_id();
+                    player.items.push(ItemOnBench { id: item_id.clone(), item: item.to_string() });
+                    drops.push(LootDrop::Item { item_id, item: item.to_string() });
+                }
+                Loot::Unit { cost } => {
+                    let candidates: Vec<_> = all_chess_pieces()
+                        .into_iter()
+                        .filter(|cp| cp.cost == cost && rules.allows(&cp.name))
+                        .filter(|cp| pool.as_ref().is_none_or(|pool| pool.remaining(&cp.name) > 0))
+                        .collect();
+                    let chosen = candidates
+                        .choose(&mut rng)
+                        .filter(|_| player.bench.len() < BENCH_SIZE)
+                        .filter(|cp| pool.as_ref().is_none_or(|pool| pool.take(&cp.name, 1)));
+                    match chosen {
+                        Some(piece) => {
+                            let unit_id = player.allocate_unit_id();
+                            player.bench.push(UnitOnBench { id: unit_id.clone(), chess: piece.name.clone(), level: 1, items: Vec::new() });
+                            drops.push(LootDrop::Unit { unit_id, chess: piece.name.clone() });
+                        }
+                        None => {
+                            player.money += cost as i32;
+                            drops.push(LootDrop::Gold { amount: cost as i32 });
+                        }
+                    }
+                }
+            }
+        }
+        player.merge_copies();
+        Ok((player.clone(), drops))
+    }
+
+    /// 收下已從棋子池取出的棋子（例如選秀），連同道具放到備戰區；
+    /// 備戰區已滿時棋子放回池中並改發等值金幣，道具放到道具欄
+    pub fn receive_unit(&self, player_id: &str, chess: &str, items: Vec<String>) -> Result<PlayerData, PlayerError> {
+        let pool = self.pool_of(player_id);
+        let mut players = self.players.lock().unwrap();
+        let player = active(&mut players, player_id)?;
+
+        if player.bench.len() < BENCH_SIZE {
+            let id = player.allocate_unit_id();
+            player.bench.push(UnitOnBench { id, chess: chess.to_string(), level: 1, items });
+            player.merge_copies();
+            return Ok(player.clone());
+        }
+        for item in items {
+            let id = player.allocate_item_id();
+            player.items.push(ItemOnBench { id, item });
+        }
+        player.money += sell_value(chess, 1);
+        if let Some(pool) = &pool {
+            pool.put_back(chess, 1);
+        }
+        Ok(player.clone())
+    }
+
+    /// 回合收入：基本 5 金，每 10 金額外 1 金利息（最多 5），加上符文的額外收入並重置免費重新整理次數
+    pub fn grant_income(&self, player_id: &str) -> Result<PlayerData, PlayerError> {
+        let mut players = self.players.lock().unwrap();
+        let player = active(&mut players, player_id)?;
+
+        let modifiers = player.modifiers();
+        let interest = (player.money / 10).clamp(0, 5);
+        player.money += (5 + interest + modifiers.income).max(0);
+        player.free_refreshes = modifiers.free_refreshes;
+        Ok(player.clone())
+    }
+
+    /// 提供一次符文選擇（覆蓋尚未決定的選擇）
+    pub fn offer_augments(&self, player_id: &str, tier: AugmentTier) -> Result<AugmentOffer, PlayerError> {
+        let mut players = self.players.lock().unwrap();
+        let player = active(&mut players, player_id)?;
+
+        let options = roll_options(tier, &player.augments, &mut thread_rng());
+        let offer = AugmentOffer { tier, options, rerolls: AUGMENT_REROLLS };
+        player.augment_offer = Some(offer.clone());
+        Ok(offer)
+    }
+
+    /// 以同一階級重抽選項
+    pub fn reroll_augments(&self, player_id: &str) -> Result<AugmentOffer, PlayerError> {
+        let mut players = self.players.lock().unwrap();
+        let player = active(&mut players, player_id)?;
+
+        let offer = player.augment_offer.as_mut().ok_or(PlayerError::NoAugmentOffer)?;
+        if offer.rerolls == 0 {
+            return Err(PlayerError::NoRerollsLeft);
+        }
+        offer.rerolls -= 1;
+        offer.options = roll_options(offer.tier, &player.augments, &mut thread_rng());
+        Ok(offer.clone())
+    }
+
+    /// 從目前的選項中選一個符文；免費重新整理次數立即生效
+    pub fn pick_augment(&self, player_id: &str, augment_id: &str) -> Result<PlayerData, PlayerError> {
+        let mut players = self.players.lock().unwrap();
+        let player = active(&mut players, player_id)?;
+
+        let offer = player.augment_offer.as_ref().ok_or(PlayerError::NoAugmentOffer)?;
+        if !offer.options.iter().any(|id| id == augment_id) {
+            return Err(PlayerError::AugmentNotOffered);
+        }
+        let augment = find_augment(augment_id).ok_or(PlayerError::AugmentNotOffered)?;
+        player.augment_offer = None;
+        player.augments.push(augment.id);
+        player.free_refreshes += augment.modifiers.free_refreshes;
+        Ok(player.clone())
+    }
+
+    /// 準備時間結束仍未選擇時代選第一個選項，回傳選到的符文
+    pub fn auto_pick_augment(&self, player_id: &str) -> Option<String> {
+        let augment_id = self.get_player(player_id)?.augment_offer?.options.first()?.clone();
+        self.pick_augment(player_id, &augment_id).ok().map(|_| augment_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manager_with_shop(shop: &[&str]) -> PlayerManager {
+        let manager = PlayerManager::new(GameRules::default());
+        let mut player = manager.get_player("p1").unwrap();
+        player.shop = shop.iter().map(|&chess| Some(ShopUnit { chess: chess.to_string(), level: 1 })).collect();
+        manager.update_player(player);
+        manager
+    }
+
+    #[test]
+    fn test_buy_and_sell_unit() {
+        let manager = manager_with_shop(&["Mage", "Knight"]);
+
+        let player = manager.buy_unit("p1", 1).unwrap();
+        assert_eq!(player.money, 98);
+        assert_eq!(player.shop[1], None);
+        assert_eq!(player.bench[0].chess, "Knight");
+        assert_eq!(manager.buy_unit("p1", 1).unwrap_err(), PlayerError::SlotEmpty);
+
+        let unit_id = player.bench[0].id.clone();
+        let player = manager.sell_unit("p1", &unit_id).unwrap();
+        assert_eq!(player.money, 100);
+        assert!(player.bench.is_empty());
+
+        assert_eq!(manager.buy_unit("p1", 5).unwrap_err(), PlayerError::SlotEmpty);
+        assert_eq!(manager.sell_unit("p1", &unit_id).unwrap_err(), PlayerError::UnitNotFound);
+    }
+
+    #[test]
+    fn test_equip_combine_and_sell_returns_items() {
+        let manager = manager_with_shop(&["Knight"]);
+        let unit_id = manager.buy_unit("p1", 0).unwrap().bench[0].id.clone();
+        let loot = [Loot::Item("sword"), Loot::Item("sword"), Loot::Item("vest"), Loot::Item("belt"), Loot::Item("rod")];
+        let (player, _) = manager.grant_loot("p1", &loot).unwrap();
+        let ids: Vec<String> = player.items.iter().map(|i| i.id.clone()).collect();
+
+        // 兩把劍合成死亡之刃，之後的基礎道具各佔一格
+        manager.equip_item("p1", &ids[0], &unit_id).unwrap();
+        let player = manager.equip_item("p1", &ids[1], &unit_id).unwrap();
+        assert_eq!(player.bench[0].items, vec!["deathblade"]);
+        manager.equip_item("p1", &ids[2], &unit_id).unwrap();
+        let player = manager.move_unit("p1", &unit_id, Some([3, 0])).unwrap();
+        assert_eq!(player.board[0].items, vec!["deathblade", "vest"]);
+        // 背心 + 腰帶合成日炎斗篷，不佔新的欄位
+        let player = manager.equip_item("p1", &ids[3], &unit_id).unwrap();
+        assert_eq!(player.board[0].items, vec!["deathblade", "sunfire_cape"]);
+        manager.equip_item("p1", &ids[4], &unit_id).unwrap();
+
+        let (player, _) = manager.grant_loot("p1", &[Loot::Item("deathblade")]).unwrap();
+        let extra = player.items[0].id.clone();
+        assert_eq!(manager.equip_item("p1", &extra, &unit_id).unwrap_err(), PlayerError::TooManyItems);
+        assert_eq!(manager.equip_item("p1", "i999", &unit_id).unwrap_err(), PlayerError::ItemNotFound);
+        assert_eq!(manager.equip_item("p1", &extra, "u999").unwrap_err(), PlayerError::UnitNotFound);
+
+        let player = manager.sell_unit("p1", &unit_id).unwrap();
+        let mut items: Vec<&str> = player.items.iter().map(|i| i.item.as_str()).collect();
+        items.sort();
+        assert_eq!(items, vec!["deathblade", "deathblade", "rod", "sunfire_cape"]);
+    }
+
+    #[test]
+    fn test_three_copies_merge_into_higher_star() {
+        let manager = manager_with_shop(&["Knight", "Knight", "Knight", "Knight"]);
+        let first = manager.buy_unit("p1", 0).unwrap().bench[0].id.clone();
+        manager.move_unit("p1", &first, Some([3, 3])).unwrap();
+        let second = manager.buy_unit("p1", 1).unwrap().bench[0].id.clone();
+        let (player, _) = manager.grant_loot("p1", &[Loot::Item("sword")]).unwrap();
+        manager.equip_item("p1", &player.items[0].id, &second).unwrap();
+
+        // 第三隻買下時合成，保留棋盤上那一隻並拿到另一隻的道具
+        let player = manager.buy_unit("p1", 2).unwrap();
+        assert!(player.bench.is_empty());
+        assert_eq!((player.board[0].id.clone(), player.board[0].level, player.board[0].items.clone()), (first, 2, vec!["sword".to_string()]));
+
+        // 連鎖合成：再湊出一組一星合成二星後，三隻二星合成三星
+        let mut player = manager.get_player("p1").unwrap();
+        player.bench = ["u101", "u102", "u103"]
+            .iter()
+            .enumerate()
+            .map(|(i, id)| UnitOnBench { id: id.to_string(), chess: "Knight".into(), level: if i == 0 { 2 } else { 1 }, items: Vec::new() })
+            .collect();
+        manager.update_player(player);
+        let player = manager.buy_unit("p1", 3).unwrap();
+        assert_eq!((player.board.len(), player.bench.len(), player.board[0].level), (1, 0, 3));
+        assert_eq!(sell_value("Knight", player.board[0].level), 18);
+    }
+
+    #[test]
+    fn test_full_bench_can_buy_the_third_copy() {
+        let manager = manager_with_shop(&["Knight", "Mage"]);
+        let mut player = manager.get_player("p1").unwrap();
+        let units = [("Knight", 1), ("Knight", 1), ("Tank", 1), ("Tank", 2), ("Priest", 1), ("Priest", 2), ("Assassin", 1), ("Assassin", 2), ("Mage", 2)];
+        player.bench = units
+            .iter()
+            .enumerate()
+            .map(|(i, &(chess, level))| UnitOnBench { id: format!("u{}", 100 + i), chess: chess.into(), level, items: Vec::new() })
+            .collect();
+        manager.update_player(player);
+
+        // 第三隻騎士會立刻合成，備戰區滿了也能買；法師則放不下
+        assert_eq!(manager.buy_unit("p1", 1).unwrap_err(), PlayerError::BenchFull);
+        let player = manager.buy_unit("p1", 0).unwrap();
+        assert_eq!(player.bench.len(), BENCH_SIZE - 1);
+        assert!(player.bench.iter().any(|u| u.chess == "Knight" && u.level == 2));
+    }
+
+    #[test]
+    fn test_move_unit_respects_level_and_swaps() {
+        let manager = manager_with_shop(&["Mage", "Knight"]);
+        manager.buy_unit("p1", 0).unwrap();
+        let player = manager.buy_unit("p1", 1).unwrap();
+        let (mage, knight) = (player.bench[0].id.clone(), player.bench[1].id.clone());
+
+        // 等級 1 只能上場一隻
+        let player = manager.move_unit("p1", &mage, Some([3, 0])).unwrap();
+        assert_eq!(player.board[0].id, mage);
+        assert_eq!(manager.move_unit("p1", &knight, Some([4, 0])).unwrap_err(), PlayerError::BoardFull);
+        assert_eq!(manager.move_unit("p1", &knight, Some([9, 0])).unwrap_err(), PlayerError::InvalidPosition);
+
+        // 放到已佔用的格子則互換
+        let player = manager.move_unit("p1", &knight, Some([3, 0])).unwrap();
+        assert_eq!(player.board[0].id, knight);
+        assert_eq!(player.bench[0].id, mage);
+
+        let player = manager.move_unit("p1", &knight, None).unwrap();
+        assert!(player.board.is_empty());
+        assert_eq!(player.bench.len(), 2);
+    }
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let path = std::env::temp_dir().join(format!("chess_fight_players_{}.json", std::process::id()));
+        let manager = manager_with_shop(&["Mage"]);
+        manager.create_player("p2");
+        manager.buy_unit("p1", 0).unwrap();
+        assert_eq!(manager.save(&path).unwrap(), 2);
+
+        let restored = PlayerManager::new(GameRules::default());
+        assert_eq!(restored.load(&path).unwrap(), 2);
+        std::fs::remove_file(&path).unwrap();
+
+        let player = restored.get_player("p1").unwrap();
+        assert_eq!(player.money, 97);
+        assert_eq!(player.bench[0].chess, "Mage");
+        assert!(restored.get_player("p2").is_some());
+        assert_eq!(restored.load(&path).unwrap(), 0);
+
+        // 重啟後接續遊戲：已持有的棋子從新的棋子池取回
+        let rules = Arc::new(GameRules::default());
+        let pool = Arc::new(UnitPool::new(&rules));
+        let full = pool.remaining("Mage");
+        restored.resume_game("p1", rules, pool.clone());
+        assert_eq!(pool.remaining("Mage"), full - 1);
+        assert!(restored.pool_of("p1").is_some());
+    }
+
+    #[test]
+    fn test_eliminated_player_cannot_act() {
+        let manager = PlayerManager::new(GameRules::default());
+        let rules = Arc::new(GameRules::default());
+        let pool = Arc::new(UnitPool::new(&rules));
+        let chess = manager.start_game("p1", rules, pool.clone()).shop[0].clone().unwrap().chess;
+        manager.buy_unit("p1", 0).unwrap();
+
+        let player = manager.eliminate("p1").unwrap();
+        assert_eq!((player.money, player.hp), (0, 0));
+        let remaining = pool.remaining(&chess);
+        let mut player = manager.get_player("p1").unwrap();
+        player.money = 100;
+        player.shop = vec![Some(ShopUnit { chess: chess.clone(), level: 1 })];
+        manager.update_player(player);
+
+        assert_eq!(manager.buy_unit("p1", 0).unwrap_err(), PlayerError::Eliminated);
+        assert_eq!(manager.refresh_shop("p1").unwrap_err(), PlayerError::Eliminated);
+        assert_eq!(manager.buy_xp("p1").unwrap_err(), PlayerError::Eliminated);
+        assert_eq!(pool.remaining(&chess), remaining);
+    }
+
+    #[test]
+    fn test_income_with_interest() {
+        let manager = PlayerManager::new(GameRules::default());
+        assert_eq!(manager.grant_income("p1").unwrap().money, 110);
+        let mut player = manager.get_player("p1").unwrap();
+        player.money = 23;
+        manager.update_player(player);
+        assert_eq!(manager.grant_income("p1").unwrap().money, 30);
+    }
+
+    #[test]
+    fn test_augments_modify_economy_and_shop() {
+        let manager = manager_with_shop(&["Knight"]);
+        let rules = GameRules::default();
+        assert_eq!(manager.pick_augment("p1", "allowance").unwrap_err(), PlayerError::NoAugmentOffer);
+
+        let offer = manager.offer_augments("p1", AugmentTier::Gold).unwrap();
+        let rerolled = manager.reroll_augments("p1").unwrap();
+        assert_eq!((offer.options.len(), rerolled.rerolls), (3, 0));
+        assert_eq!(manager.reroll_augments("p1").unwrap_err(), PlayerError::NoRerollsLeft);
+
+        let mut player = manager.get_player("p1").unwrap();
+        player.augment_offer = Some(AugmentOffer { tier: AugmentTier::Gold, options: vec!["rolling_for_days_2".into(), "trade_sector".into()], rerolls: 0 });
+        manager.update_player(player);
+        assert_eq!(manager.pick_augment("p1", "allowance").unwrap_err(), PlayerError::AugmentNotOffered);
+        let player = manager.pick_augment("p1", "rolling_for_days_2").unwrap();
+        assert_eq!((player.free_refreshes, player.augment_offer.clone()), (2, None));
+
+        // 兩次免費重新整理之後才開始扣錢
+        manager.refresh_shop("p1").unwrap();
+        assert_eq!(manager.refresh_shop("p1").unwrap().money, 100);
+        assert_eq!(manager.refresh_shop("p1").unwrap().money, 100 - rules.refresh_cost);
+
+        let mut player = manager.get_player("p1").unwrap();
+        player.augments.extend(["trade_sector".to_string(), "level_up".to_string(), "allowance".to_string()]);
+        player.money = 20;
+        manager.update_player(player);
+        assert_eq!(manager.refresh_shop("p1").unwrap().shop.len(), rules.shop_size + 1);
+        assert_eq!(manager.buy_xp("p1").unwrap().money, 20 - rules.refresh_cost - (rules.xp_cost - 2));
+        let player = manager.grant_income("p1").unwrap();
+        assert_eq!(player.free_refreshes, 2);
+        assert_eq!(player.money, 20 - rules.refresh_cost - (rules.xp_cost - 2) + 5 + 1 + 1);
     }
-    
 } 
\ No newline at end of file
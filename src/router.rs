@@ -1,31 +1,264 @@
+use crate::connection::{ConnectionContext, ConnectionId};
 use crate::handlers::MessageHandler;
+use crate::middleware::{Middleware, Next};
 use crate::types::response::{WsRequest, WsResponse};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fmt;
 use std::sync::Arc;
 
+/// 內建的自我描述 action，回傳所有已註冊的 action 與 payload schema
+pub const LIST_ACTIONS: &str = "ListActions";
+
+/// 註冊處理器時的錯誤
+#[derive(Debug)]
+pub enum RouterError {
+    DuplicateAction(String),
+}
+
+impl fmt::Display for RouterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RouterError::DuplicateAction(action) =>
+                write!(f, "action {} is already registered", action),
+        }
+    }
+}
+
+impl std::error::Error for RouterError {}
+
+/// ListActions 回傳的單一 action 描述
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionInfo {
+    pub action: String,
+    pub payload_schema: Value,
+}
+
 #[derive(Clone)]
 pub struct Router {
-    handlers: Vec<Arc<dyn MessageHandler>>,
+    handlers: HashMap<&'static str, Arc<dyn MessageHandler>>,
+    fallback: Option<Arc<dyn MessageHandler>>,
+    layers: Vec<Arc<dyn Middleware>>,
 }
 
 impl Router {
     pub fn new() -> Self {
         Self {
-            handlers: Vec::new(),
+            handlers: HashMap::new(),
+            fallback: None,
+            layers: Vec::new(),
+        }
+    }
+
+    /// 疊加一層中介層，先加入的在最外層
+    pub fn layer(&mut self, layer: Arc<dyn Middleware>) {
+        self.layers.push(layer);
+    }
+
+    /// 以 action 名稱註冊處理器，重複的 action 直接拒絕
+    pub fn add_handler(&mut self, handler: Arc<dyn MessageHandler>) -> Result<(), RouterError> {
+        let action = handler.action();
+        if action == LIST_ACTIONS || self.handlers.contains_key(action) {
+            return Err(RouterError::DuplicateAction(action.to_string()));
+        }
+        self.handlers.insert(action, handler);
+        Ok(())
+    }
+
+    /// 設定找不到對應 action 時使用的處理器
+    pub fn set_fallback(&mut self, handler: Arc<dyn MessageHandler>) {
+        self.fallback = Some(handler);
+    }
+
+    /// 所有已註冊的 action（依名稱排序）
+    pub fn actions(&self) -> Vec<ActionInfo> {
+        let mut actions: Vec<ActionInfo> = self
+            .handlers
+            .values()
+            .map(|handler| ActionInfo {
+                action: handler.action().to_string(),
+                payload_schema: handler.payload_schema(),
+            })
+            .collect();
+        actions.push(ActionInfo {
+            action: LIST_ACTIONS.to_string(),
+            payload_schema: json!({ "type": "object" }),
+        });
+        actions.sort_by(|a, b| a.action.cmp(&b.action));
+        actions
+    }
+
+    /// 未登入也能使用的 action（含 ListActions），供 `AuthLayer` 使用
+    pub fn public_actions(&self) -> Vec<&'static str> {
+        self.actions_where(|handler| handler.public())
+    }
+
+    /// 觀戰中也能使用的唯讀 action（含 ListActions），供 `SpectatorLayer` 使用
+    pub fn read_only_actions(&self) -> Vec<&'static str> {
+        self.actions_where(|handler| handler.read_only())
+    }
+
+    fn actions_where(&self, filter: impl Fn(&dyn MessageHandler) -> bool) -> Vec<&'static str> {
+        let mut actions: Vec<&'static str> = self
+            .handlers
+            .iter()
+            .filter(|(_, handler)| filter(handler.as_ref()))
+            .map(|(action, _)| *action)
+            .chain(std::iter::once(LIST_ACTIONS))
+            .collect();
+        actions.sort();
+        actions
+    }
+
+    /// 經過所有中介層後分派請求
+    pub async fn handle(&self, ctx: &ConnectionContext, request: &WsRequest) -> WsResponse {
+        Next::new(&self.layers, self).run(ctx, request).await
+    }
+
+    /// 連線結束時通知所有中介層
+    pub fn disconnected(&self, connection_id: ConnectionId) {
+        for layer in &self.layers {
+            layer.on_disconnect(connection_id);
+        }
+    }
+
+    /// 直接依 action 分派到處理器（不經過中介層）
+    pub async fn dispatch(&self, ctx: &ConnectionContext, request: &WsRequest) -> WsResponse {
+        let action = request.type_.as_str();
+        if action == LIST_ACTIONS {
+            return WsResponse::new("ListActionsResult", json!({ "actions": self.actions() }));
+        }
+
+        if let Some(handler) = self.handlers.get(action) {
+            return handler.handle(ctx, request).await;
+        }
+
+        match &self.fallback {
+            Some(fallback) => fallback.handle(ctx, request).await,
+            // 如果没有找到处理器，返回错误
+            None => WsResponse::unknown_action(action),
         }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connection::ConnectionRegistry;
+    use crate::handlers::{EchoHandler, PingHandler};
+    use crate::middleware::CatchPanicLayer;
+    use async_trait::async_trait;
+
+    struct PanicHandler;
+
+    #[async_trait]
+    impl MessageHandler for PanicHandler {
+        async fn handle(&self, _ctx: &ConnectionContext, _val: &WsRequest) -> WsResponse {
+            panic!("boom");
+        }
 
-    pub fn add_handler(&mut self, handler: Arc<dyn MessageHandler>) {
-        self.handlers.push(handler);
+        fn action(&self) -> &'static str {
+            "panic"
+        }
+    }
+
+    /// 直接回應、不往下傳遞的中介層
+    struct ShortCircuit;
+
+    #[async_trait]
+    impl Middleware for ShortCircuit {
+        async fn handle(&self, _ctx: &ConnectionContext, _request: &WsRequest, _next: Next<'_>) -> WsResponse {
+            WsResponse::ok(None)
+        }
+    }
+
+    fn context(registry: &ConnectionRegistry) -> ConnectionContext {
+        let (handle, _receiver) = registry.open();
+        ConnectionContext::new(handle, "127.0.0.1:9000".parse().unwrap())
     }
 
-    pub async fn handle(&self, action: &str, request: &WsRequest) -> WsResponse {
-        for handler in &self.handlers {
-            if handler.can_handle(action) {
-                return handler.handle(request).await;
-            }
+    fn request(action: &str) -> WsRequest {
+        WsRequest {
+            type_: action.to_string(),
+            payload: json!({ "msg": "hi" }),
+            request_id: None,
         }
+    }
+
+    #[test]
+    fn test_duplicate_action_is_rejected() {
+        let mut router = Router::new();
+        assert!(router.add_handler(Arc::new(PingHandler)).is_ok());
+        assert!(matches!(
+            router.add_handler(Arc::new(PingHandler)),
+            Err(RouterError::DuplicateAction(action)) if action == "ping"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_and_unknown_action() {
+        let mut router = Router::new();
+        router.add_handler(Arc::new(EchoHandler)).unwrap();
+        let registry = ConnectionRegistry::new();
+        let ctx = context(&registry);
+
+        let response = router.dispatch(&ctx, &request("echo")).await;
+        assert_eq!(response.type_, "Success");
+
+        let response = router.dispatch(&ctx, &request("nope")).await;
+        assert_eq!(response.payload.unwrap()["code"], "UNKNOWN_ACTION");
+    }
+
+    #[tokio::test]
+    async fn test_list_actions() {
+        let mut router = Router::new();
+        router.add_handler(Arc::new(EchoHandler)).unwrap();
+        router.add_handler(Arc::new(PingHandler)).unwrap();
+        let registry = ConnectionRegistry::new();
+        let ctx = context(&registry);
+
+        let response = router.dispatch(&ctx, &request(LIST_ACTIONS)).await;
+        let payload = response.payload.unwrap();
+        let actions: Vec<&str> = payload["actions"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|a| a["action"].as_str().unwrap())
+            .collect();
+        assert_eq!(actions, vec![LIST_ACTIONS, "echo", "ping"]);
+    }
+
+    #[test]
+    fn test_public_and_read_only_actions_come_from_handlers() {
+        let mut router = Router::new();
+        router.add_handler(Arc::new(EchoHandler)).unwrap();
+        router.add_handler(Arc::new(PanicHandler)).unwrap();
+
+        assert_eq!(router.public_actions(), vec![LIST_ACTIONS, "echo"]);
+        assert_eq!(router.read_only_actions(), vec![LIST_ACTIONS, "echo"]);
+    }
+
+    #[tokio::test]
+    async fn test_catch_panic_layer() {
+        let mut router = Router::new();
+        router.add_handler(Arc::new(PanicHandler)).unwrap();
+        router.layer(Arc::new(CatchPanicLayer));
+
+        let registry = ConnectionRegistry::new();
+        let response = router.handle(&context(&registry), &request("panic")).await;
+        assert_eq!(response.payload.unwrap()["code"], "INTERNAL_ERROR");
+    }
+
+    #[tokio::test]
+    async fn test_layer_can_short_circuit() {
+        let mut router = Router::new();
+        router.add_handler(Arc::new(PanicHandler)).unwrap();
+        router.layer(Arc::new(ShortCircuit));
 
-        // 如果没有找到处理器，返回错误
-        WsResponse::error(format!("unknown action: {}", action))
+        let registry = ConnectionRegistry::new();
+        let response = router.handle(&context(&registry), &request("panic")).await;
+        assert_eq!(response.type_, "Success");
     }
 }
@@ -0,0 +1,103 @@
+// 優雅關機：收到 SIGINT / SIGTERM 後停止接受新連線，
+// 通知所有客戶端稍後重連、以 Close frame 關閉連線，並在期限內等待連線結束
+
+use crate::connection::ConnectionRegistry;
+use crate::types::response::WsResponse;
+use log::*;
+use serde_json::json;
+use std::time::Duration;
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+
+/// 關機時關閉連線使用的 close code（1012 Service Restart）
+pub const SHUTDOWN_CLOSE_CODE: CloseCode = CloseCode::Restart;
+
+/// 等待 SIGINT（Ctrl+C）或 SIGTERM
+pub async fn signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            error!("Failed to listen for Ctrl+C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut stream) => {
+                stream.recv().await;
+            }
+            Err(e) => {
+                error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => info!("Received SIGINT"),
+        _ = terminate => info!("Received SIGTERM"),
+    }
+}
+
+/// 通知每條連線伺服器即將關閉，並排入 Close frame
+/// 訊息排在各自佇列既有的回應之後；全程不等待佇列空間，
+/// 佇列已滿而排不進 Close frame 的連線留給關機期限強制結束
+/// 回傳通知的連線數
+pub fn notify_all(registry: &ConnectionRegistry, reconnect_after: Duration) -> usize {
+    let message = WsResponse::new("ServerShuttingDown", json!({
+        "reconnectAfter": reconnect_after.as_secs()
+    }));
+    let connections = registry.connections();
+    for handle in &connections {
+        if let Err(e) = handle.try_push(message.clone()) {
+            debug!("Could not notify connection {} of shutdown: {}", handle.id(), e);
+        }
+        if let Err(e) = handle.try_close(SHUTDOWN_CLOSE_CODE, "server shutting down") {
+            debug!("Could not queue close frame for connection {}: {}", handle.id(), e);
+        }
+    }
+    connections.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connection::Outbound;
+
+    #[tokio::test]
+    async fn test_notify_all_sends_message_then_close() {
+        let registry = ConnectionRegistry::new();
+        let (_h1, mut r1) = registry.open();
+        let (h2, mut r2) = registry.open();
+        registry.bind("p2", None, &h2);
+
+        assert_eq!(notify_all(&registry, Duration::from_secs(5)), 2);
+
+        for receiver in [&mut r1, &mut r2] {
+            match receiver.try_recv() {
+                Ok(Outbound::Response(response)) => {
+                    assert_eq!(response.type_, "ServerShuttingDown");
+                    assert_eq!(response.payload.unwrap()["reconnectAfter"], 5);
+                }
+                other => panic!("unexpected outbound: {:?}", other),
+            }
+            match receiver.try_recv() {
+                Ok(Outbound::Close(Some(frame))) => assert_eq!(frame.code, SHUTDOWN_CLOSE_CODE),
+                other => panic!("unexpected outbound: {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_notify_all_does_not_wait_for_full_queues() {
+        let registry = ConnectionRegistry::with_queue_size(1);
+        let (stalled, _r1) = registry.open();
+        stalled.try_push(WsResponse::ok(None)).unwrap();
+
+        // 佇列已滿的連線不會卡住關機流程
+        assert_eq!(notify_all(&registry, Duration::from_secs(5)), 1);
+    }
+}
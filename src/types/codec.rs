@@ -0,0 +1,183 @@
+// 訊息編碼：同一組 WsRequest / WsResponse 可用 JSON、MessagePack 或 CBOR 傳輸
+//
+// JSON 使用文字 frame（預設），MessagePack 與 CBOR 使用二進位 frame。
+// 連線可透過 WebSocket 子協定（Sec-WebSocket-Protocol）或 Hello 訊息協商編碼。
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Encoding {
+    #[default]
+    Json,
+    #[serde(rename = "msgpack")]
+    MessagePack,
+    Cbor,
+}
+
+/// 編解碼失敗
+#[derive(Debug)]
+pub struct CodecError(String);
+
+impl fmt::Display for CodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Encoding {
+    /// 依偏好順序列出支援的子協定
+    pub const SUBPROTOCOLS: [(&'static str, Encoding); 3] = [
+        ("chess-fight.msgpack", Encoding::MessagePack),
+        ("chess-fight.cbor", Encoding::Cbor),
+        ("chess-fight.json", Encoding::Json),
+    ];
+
+    /// 對應的 WebSocket 子協定名稱
+    pub fn subprotocol(&self) -> &'static str {
+        Self::SUBPROTOCOLS
+            .iter()
+            .find(|(_, encoding)| encoding == self)
+            .map(|(name, _)| *name)
+            .unwrap_or("chess-fight.json")
+    }
+
+    /// 從客戶端提供的子協定清單（`Sec-WebSocket-Protocol`）中挑出第一個支援的
+    pub fn negotiate(offered: &str) -> Option<Encoding> {
+        offered
+            .split(',')
+            .map(str::trim)
+            .find_map(|name| {
+                Self::SUBPROTOCOLS
+                    .iter()
+                    .find(|(protocol, _)| protocol.eq_ignore_ascii_case(name))
+                    .map(|(_, encoding)| *encoding)
+            })
+    }
+
+    /// 是否使用二進位 frame
+    pub fn is_binary(&self) -> bool {
+        !matches!(self, Encoding::Json)
+    }
+
+    pub fn encode<T: Serialize>(&self, value: &T) -> Result<Vec<u8>, CodecError> {
+        match self {
+            Encoding::Json => serde_json::to_vec(value).map_err(|e| CodecError(e.to_string())),
+            // 以欄位名稱編碼 struct，與 JSON 的物件結構一致
+            Encoding::MessagePack => rmp_serde::to_vec_named(value).map_err(|e| CodecError(e.to_string())),
+            Encoding::Cbor => {
+                let mut buffer = Vec::new();
+                ciborium::into_writer(value, &mut buffer).map_err(|e| CodecError(e.to_string()))?;
+                Ok(buffer)
+            }
+        }
+    }
+
+    /// 解成通用的 JSON 值，再交給後續流程轉型
+    pub fn decode_value(&self, bytes: &[u8]) -> Result<Value, CodecError> {
+        match self {
+            Encoding::Json => serde_json::from_slice(bytes).map_err(|e| CodecError(e.to_string())),
+            Encoding::MessagePack => rmp_serde::from_slice(bytes).map_err(|e| CodecError(e.to_string())),
+            Encoding::Cbor => ciborium::from_reader(bytes).map_err(|e| CodecError(e.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::response::{ErrorCode, WsRequest, WsResponse};
+    use serde_json::json;
+
+    const ENCODINGS: [Encoding; 3] = [Encoding::Json, Encoding::MessagePack, Encoding::Cbor];
+
+    fn sample_requests() -> Vec<WsRequest> {
+        vec![
+            WsRequest {
+                type_: "Login".to_string(),
+                payload: json!({}),
+                request_id: None,
+            },
+            WsRequest {
+                type_: "Resume".to_string(),
+                payload: json!({ "sessionToken": "p1.1.abc", "lastSeq": 42 }),
+                request_id: Some("r-1".to_string()),
+            },
+            WsRequest {
+                type_: "echo".to_string(),
+                payload: json!({
+                    "nested": { "list": [1, -2, 3.5, null, true], "text": "中文" },
+                    "big": u64::MAX,
+                }),
+                request_id: Some("r-2".to_string()),
+            },
+        ]
+    }
+
+    fn sample_responses() -> Vec<WsResponse> {
+        let mut push = WsResponse::new("ChatMessage", json!({ "gameId": "g1", "message": "hi" }));
+        push.seq = Some(7);
+        vec![
+            WsResponse::ok(None),
+            WsResponse::error(ErrorCode::NotEnoughMoney, "not enough money").with_request_id(Some("r-3".into())),
+            WsResponse::new("GetGameStateResult", json!({
+                "state": { "board": [], "bench": [{ "id": "u1", "chess": "Mage", "level": 1 }], "money": 100 },
+                "success": true,
+            })),
+            push,
+        ]
+    }
+
+    #[test]
+    fn test_requests_round_trip_in_every_encoding() {
+        for encoding in ENCODINGS {
+            for request in sample_requests() {
+                let bytes = encoding.encode(&request).unwrap();
+                let value = encoding.decode_value(&bytes).unwrap();
+                let decoded: WsRequest = serde_json::from_value(value).unwrap();
+
+                assert_eq!(decoded.type_, request.type_, "{:?}", encoding);
+                assert_eq!(decoded.payload, request.payload, "{:?}", encoding);
+                assert_eq!(decoded.request_id, request.request_id, "{:?}", encoding);
+            }
+        }
+    }
+
+    #[test]
+    fn test_responses_are_equivalent_to_json() {
+        for response in sample_responses() {
+            let expected = serde_json::to_value(&response).unwrap();
+            for encoding in ENCODINGS {
+                let bytes = encoding.encode(&response).unwrap();
+                let value = encoding.decode_value(&bytes).unwrap();
+                assert_eq!(value, expected, "{:?}", encoding);
+            }
+        }
+    }
+
+    #[test]
+    fn test_binary_encodings_are_smaller() {
+        let response = &sample_responses()[2];
+        let json = Encoding::Json.encode(response).unwrap().len();
+        assert!(Encoding::MessagePack.encode(response).unwrap().len() < json);
+        assert!(Encoding::Cbor.encode(response).unwrap().len() < json);
+    }
+
+    #[test]
+    fn test_negotiate_subprotocol() {
+        assert_eq!(Encoding::negotiate("foo, chess-fight.cbor, chess-fight.msgpack"), Some(Encoding::Cbor));
+        assert_eq!(Encoding::negotiate("chess-fight.json"), Some(Encoding::Json));
+        assert_eq!(Encoding::negotiate("graphql-ws"), None);
+        assert_eq!(Encoding::Cbor.subprotocol(), "chess-fight.cbor");
+    }
+
+    #[test]
+    fn test_garbage_is_rejected() {
+        for encoding in ENCODINGS {
+            assert!(encoding.decode_value(&[0xc1, 0xff, 0x00]).is_err(), "{:?}", encoding);
+        }
+    }
+}
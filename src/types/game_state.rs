@@ -1,4 +1,5 @@
 // 後端純資料結構定義
+use crate::game::augments::AugmentOffer;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -8,59 +9,71 @@ pub struct ChessPiece {
     pub level: u32,
 }
 
-#[derive(Serialize, Deserialize, Clone)]
-pub struct PlayerGameState {
-    pub player_id: String,
-    pub money: u32,
-    pub experience: u32,
-    pub bench: Vec<ChessPiece>,
-}
-
-
-// use serde_json::Value;
-
-#[derive(Debug, Serialize)]
+/// GetGameState 回應中的玩家遊戲狀態
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct GameState {
     pub round: u32,
     pub money: u32,
-    pub playerId: String,
+    pub hp: i32,
+    pub player_id: String,
     pub board: Vec<UnitOnBoard>,
     pub bench: Vec<UnitOnBench>,
-    pub shop: Vec<ShopUnit>,
+    pub shop: Vec<Option<ShopUnit>>,   // 已購買的格子為 null
+    pub items: Vec<ItemOnBench>,
     pub synergies: Vec<Synergy>,
+    /// 已選擇的強化符文 id
+    pub augments: Vec<String>,
+    /// 尚未決定的符文選擇
+    pub augment_offer: Option<AugmentOffer>,
+    /// 本回合剩餘的免費重新整理次數
+    pub free_refreshes: u32,
     pub level: u32,
     pub xp: XpInfo,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct UnitOnBoard {
     pub id: String,
     pub chess: String,
     pub level: u32,
     pub position: [u32; 2],
+    /// 裝備的道具 id（最多 3 個）
+    #[serde(default)]
+    pub items: Vec<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct UnitOnBench {
     pub id: String,
     pub chess: String,
     pub level: u32,
+    #[serde(default)]
+    pub items: Vec<String>,
+}
+
+/// 道具欄中尚未裝備的道具
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ItemOnBench {
+    pub id: String,
+    pub item: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ShopUnit {
     pub chess: String,
     pub level: u32,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Synergy {
     pub name: String,
     pub count: u32,
-    pub bonusLevel: u32,
+    pub bonus_level: u32,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct XpInfo {
     pub current: u32,
     pub required: u32,
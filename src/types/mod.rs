@@ -1,2 +1,5 @@
 pub mod response;
-pub mod game_state;
\ No newline at end of file
+pub mod game_state;
+pub mod codec;
+pub mod protocol;
+pub mod patch;
\ No newline at end of file
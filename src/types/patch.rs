@@ -0,0 +1,137 @@
+// JSON Patch（RFC 6902 子集）：比較兩份狀態，產生 add / remove / replace 操作
+//
+// 陣列逐一比對同索引的元素，多出的元素依序 add，減少的元素由尾端往前 remove，
+// 依序套用即可從舊狀態得到新狀態。
+
+use serde::Serialize;
+use serde_json::Value;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PatchOp {
+    Add { path: String, value: Value },
+    Remove { path: String },
+    Replace { path: String, value: Value },
+}
+
+/// 產生把 `old` 變成 `new` 的操作清單；兩者相同時為空
+pub fn diff(old: &Value, new: &Value) -> Vec<PatchOp> {
+    let mut ops = Vec::new();
+    diff_at("", old, new, &mut ops);
+    ops
+}
+
+fn diff_at(path: &str, old: &Value, new: &Value, ops: &mut Vec<PatchOp>) {
+    match (old, new) {
+        (Value::Object(old_map), Value::Object(new_map)) => {
+            for key in old_map.keys().filter(|key| !new_map.contains_key(*key)) {
+                ops.push(PatchOp::Remove { path: child(path, key) });
+            }
+            for (key, value) in new_map {
+                match old_map.get(key) {
+                    Some(previous) => diff_at(&child(path, key), previous, value, ops),
+                    None => ops.push(PatchOp::Add { path: child(path, key), value: value.clone() }),
+                }
+            }
+        }
+        (Value::Array(old_items), Value::Array(new_items)) => {
+            let common = old_items.len().min(new_items.len());
+            for index in 0..common {
+                diff_at(&child(path, &index.to_string()), &old_items[index], &new_items[index], ops);
+            }
+            for (index, value) in new_items.iter().enumerate().skip(common) {
+                ops.push(PatchOp::Add { path: child(path, &index.to_string()), value: value.clone() });
+            }
+            for index in (common..old_items.len()).rev() {
+                ops.push(PatchOp::Remove { path: child(path, &index.to_string()) });
+            }
+        }
+        _ if old != new => ops.push(PatchOp::Replace { path: path.to_string(), value: new.clone() }),
+        _ => {}
+    }
+}
+
+/// JSON Pointer：`~` 與 `/` 需要跳脫
+fn child(path: &str, token: &str) -> String {
+    format!("{}/{}", path, token.replace('~', "~0").replace('/', "~1"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// 測試用的套用器，驗證 diff 產生的操作確實能重建新狀態
+    fn apply(target: &mut Value, ops: &[PatchOp]) {
+        for op in ops {
+            let (path, value) = match op {
+                PatchOp::Add { path, value } | PatchOp::Replace { path, value } => (path, Some(value.clone())),
+                PatchOp::Remove { path } => (path, None),
+            };
+            if path.is_empty() {
+                *target = value.unwrap();
+                continue;
+            }
+            let (parent, last) = path.rsplit_once('/').unwrap();
+            let last = last.replace("~1", "/").replace("~0", "~");
+            let container = target.pointer_mut(parent).unwrap();
+            match (container, value) {
+                (Value::Object(map), Some(value)) => {
+                    map.insert(last, value);
+                }
+                (Value::Object(map), None) => {
+                    map.remove(&last);
+                }
+                (Value::Array(items), value) => {
+                    let index: usize = last.parse().unwrap();
+                    match (op, value) {
+                        (PatchOp::Add { .. }, Some(value)) => items.insert(index, value),
+                        (PatchOp::Replace { .. }, Some(value)) => items[index] = value,
+                        _ => {
+                            items.remove(index);
+                        }
+                    }
+                }
+                _ => panic!("invalid patch target {}", path),
+            }
+        }
+    }
+
+    #[test]
+    fn test_identical_values_produce_no_ops() {
+        let state = json!({ "money": 10, "bench": [{ "id": "u1" }] });
+        assert!(diff(&state, &state).is_empty());
+    }
+
+    #[test]
+    fn test_field_change_is_a_replace() {
+        let ops = diff(&json!({ "money": 10, "level": 1 }), &json!({ "money": 6, "level": 1 }));
+        assert_eq!(ops, vec![PatchOp::Replace { path: "/money".into(), value: json!(6) }]);
+        assert_eq!(
+            serde_json::to_value(&ops[0]).unwrap(),
+            json!({ "op": "replace", "path": "/money", "value": 6 })
+        );
+    }
+
+    #[test]
+    fn test_diff_round_trips() {
+        let old = json!({
+            "money": 10,
+            "bench": [{ "id": "u1", "chess": "Mage" }, { "id": "u2", "chess": "Tank" }, { "id": "u3", "chess": "Druid" }],
+            "shop": [{ "chess": "Knight" }],
+            "a/b": { "~x": 1 },
+            "gone": true,
+        });
+        let new = json!({
+            "money": 7,
+            "bench": [{ "id": "u2", "chess": "Tank" }],
+            "shop": [{ "chess": "Knight" }, { "chess": "Priest" }, { "chess": "Archer" }],
+            "a/b": { "~x": 2 },
+            "board": [],
+        });
+
+        let mut patched = old.clone();
+        apply(&mut patched, &diff(&old, &new));
+        assert_eq!(patched, new);
+    }
+}
@@ -0,0 +1,129 @@
+// 協定版本與功能旗標
+//
+// 任何會改變 WsRequest / WsResponse 外觀的修改（新增或移除欄位、改名、改變語意）
+// 都必須調高 PROTOCOL_VERSION，並在下方記錄變更；
+// 不再相容的舊版客戶端則調高 MIN_PROTOCOL_VERSION，握手時會被要求升級。
+//
+// 版本紀錄：
+// 1 - type / payload / requestId / seq；錯誤回應帶 code 與 error
+// 2 - StateSnapshot / StateDelta 推播與 Resync；遊戲動作的結果帶 version；
+//     GameState.shop 中已購買的格子為 null
+// 3 - 關機前推播 ServerShuttingDown { reconnectAfter }，並以 close code 1012 關閉連線
+// 4 - 配對：QueueForMatch / CancelQueue / AcceptMatch，
+//     推播 MatchFound / MatchCancelled / GameStarted
+// 5 - 自訂房間：CreateLobby / JoinLobby / LeaveLobby / KickFromLobby / SetLobbyRules / StartLobby，
+//     推播 LobbyUpdated / LobbyKicked；GameStarted 帶 rules
+// 6 - 回合流程：準備階段開始時推播 RoundStarted { round, stage, stageRound, kind, planningSecs, opponent }
+// 7 - 戰鬥與淘汰：推播 CombatResult / PlayerEliminated / GameOver；GameState 帶 hp；
+//     共用棋子池用完時購買回傳 SOLD_OUT
+// 8 - 野怪回合：CombatResult 帶 loot；GameState 帶 items（道具欄）
+// 9 - 道具：EquipItem { itemId, unitId }；棋盤與備戰區的單位帶 items；
+//     新錯誤代碼 ITEM_NOT_FOUND / TOO_MANY_ITEMS
+// 10 - 選秀：PickCarousel { slotIndex }，推播 CarouselStarted / CarouselWave / CarouselPick / CarouselEnded；
+//      新錯誤代碼 CAROUSEL_NOT_ACTIVE / NOT_YOUR_TURN / SLOT_TAKEN
+// 11 - 強化符文：PickAugment { augmentId } / RerollAugments，推播 AugmentOffered / AugmentPicked；
+//      GameState 帶 augments / augmentOffer / freeRefreshes，synergies 改以羈絆標籤計算；
+//      新錯誤代碼 NO_AUGMENT_OFFER / NO_REROLLS_LEFT
+// 12 - 升星：同一棋子同一星級湊滿 3 隻時自動合成高一星的單位（最高 3 星）
+// 13 - 觀戰：Spectate { gameId, playerId? } / StopSpectating，觀戰推播補上 playerId；
+//      新錯誤代碼 NOT_SPECTATING / SPECTATOR_READ_ONLY
+// 14 - 已淘汰的玩家不能再操作（商店、經驗、棋子、道具、符文），回傳新錯誤代碼 ELIMINATED；淘汰時清空金幣
+// 15 - Hello 必須是第一則訊息，握手前的其他 action 回傳新錯誤代碼 HANDSHAKE_REQUIRED 並以 close code 4001 關閉連線；
+//      不再支援版本 1（GameState.shop 的格子可能為 null）
+// 16 - GameState.round 為所在遊戲目前的回合（不在遊戲中為 0），先前固定為 1
+
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 伺服器目前的協定版本
+pub const PROTOCOL_VERSION: u32 = 16;
+
+/// 仍然支援的最舊協定版本：版本 2 起 GameState.shop 的格子可能為 null，之後的改變都是新增欄位或訊息
+pub const MIN_PROTOCOL_VERSION: u32 = 2;
+
+/// 可協商的功能
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum Feature {
+    Msgpack, // MessagePack 二進位編碼
+    Cbor,    // CBOR 二進位編碼
+    Resume,  // 斷線後以 Resume 補回推播
+    Chat,    // 遊戲內聊天
+    StateSync, // StateDelta 差異推播
+}
+
+impl Feature {
+    /// 伺服器啟用的功能
+    pub const ENABLED: [Feature; 5] = [Feature::Msgpack, Feature::Cbor, Feature::Resume, Feature::Chat, Feature::StateSync];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Feature::Msgpack => "msgpack",
+            Feature::Cbor => "cbor",
+            Feature::Resume => "resume",
+            Feature::Chat => "chat",
+            Feature::StateSync => "stateSync",
+        }
+    }
+}
+
+/// 客戶端版本是否仍受支援
+pub fn is_supported(version: u32) -> bool {
+    (MIN_PROTOCOL_VERSION..=PROTOCOL_VERSION).contains(&version)
+}
+
+/// 雙方都支援的功能（依伺服器順序），客戶端未提供清單時視為全部接受
+pub fn negotiate_features(offered: Option<&[String]>) -> Vec<Feature> {
+    Feature::ENABLED
+        .iter()
+        .filter(|feature| match offered {
+            Some(offered) => offered.iter().any(|name| name == feature.name()),
+            None => true,
+        })
+        .copied()
+        .collect()
+}
+
+/// 伺服器時間（Unix epoch 毫秒），讓客戶端校正倒數計時
+pub fn server_time_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::response::WsResponse;
+    use serde_json::json;
+
+    #[test]
+    fn test_supported_versions() {
+        assert!(is_supported(PROTOCOL_VERSION));
+        assert!(!is_supported(MIN_PROTOCOL_VERSION - 1));
+        assert!(!is_supported(PROTOCOL_VERSION + 1));
+    }
+
+    #[test]
+    fn test_negotiate_features() {
+        let offered = vec!["cbor".to_string(), "chat".to_string(), "telepathy".to_string()];
+        assert_eq!(negotiate_features(Some(&offered)), vec![Feature::Cbor, Feature::Chat]);
+        assert_eq!(negotiate_features(None), Feature::ENABLED.to_vec());
+    }
+
+    /// 回應外觀的快照：這個測試失敗代表 WsResponse 的欄位改變了，
+    /// 請調高 PROTOCOL_VERSION、記錄在版本紀錄中，再更新這裡的預期值
+    #[test]
+    fn test_response_shape_is_tied_to_protocol_version() {
+        let mut response = WsResponse::new("Push", json!({})).with_request_id(Some("r".into()));
+        response.seq = Some(1);
+        let value = serde_json::to_value(&response).unwrap();
+        let mut fields: Vec<&str> = value.as_object().unwrap().keys().map(String::as_str).collect();
+        fields.sort();
+
+        assert_eq!(PROTOCOL_VERSION, 16);
+        assert_eq!(fields, ["payload", "requestId", "seq", "type"]);
+    }
+}
@@ -1,52 +1,175 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::fmt;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct WsRequest {
     #[serde(rename = "type")]
     pub type_: String,
     pub payload: Value,
+    /// 客戶端自訂的請求編號，會原樣帶回對應的回應
+    #[serde(rename = "requestId", default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct WsResponse {
     #[serde(rename = "type")]
     pub type_: String,
     pub payload: Option<Value>,
+    /// 對應請求的 requestId；伺服器主動推播時為空
+    #[serde(rename = "requestId", skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    /// 推播序號（每位玩家遞增），重新連線時用來補送遺漏的推播
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seq: Option<u64>,
+}
+
+/// 機器可讀的錯誤代碼，與錯誤訊息一起放在 payload 中
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    InvalidJson,
+    MissingAction,
+    UnknownAction,
+    MissingField,
+    InvalidField,
+    NotEnoughMoney,
+    UnitNotFound,
+    SlotEmpty,
+    BenchFull,
+    BoardFull,
+    InvalidPosition,
+    SoldOut,
+    ItemNotFound,
+    TooManyItems,
+    CarouselNotActive,
+    NotYourTurn,
+    SlotTaken,
+    NoAugmentOffer,
+    NoRerollsLeft,
+    Eliminated,
+    PlayerNotFound,
+    GameNotFound,
+    AlreadyQueued,
+    AlreadyInGame,
+    MatchNotFound,
+    LobbyNotFound,
+    LobbyFull,
+    AlreadyInLobby,
+    NotEnoughPlayers,
+    NotInGame,
+    GameInProgress,
+    NotSpectating,
+    SpectatorReadOnly,
+    RateLimited,
+    Unauthenticated,
+    Forbidden,
+    InvalidToken,
+    BinaryNotSupported,
+    HandshakeRequired,
+    UpgradeRequired,
+    InternalError,
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let code = serde_json::to_value(self)
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_default();
+        write!(f, "{}", code)
+    }
 }
 
 impl WsResponse {
+    pub fn new(type_: &str, payload: Value) -> Self {
+        Self {
+            type_: type_.to_string(),
+            payload: Some(payload),
+            request_id: None,
+            seq: None,
+        }
+    }
+
     pub fn ok(data: Option<Value>) -> Self {
         Self {
             type_: "Success".to_string(),
             payload: data,
+            request_id: None,
+            seq: None,
         }
     }
 
-    pub fn error(reason: String) -> Self {
-        Self {
-            type_: "Error".to_string(),
-            payload: Some(serde_json::json!({ "error": reason })),
-        }
+    pub fn error(code: ErrorCode, reason: impl Into<String>) -> Self {
+        Self::new(
+            "Error",
+            serde_json::json!({ "code": code, "error": reason.into() }),
+        )
+    }
+
+    /// 帶回請求的 requestId
+    pub fn with_request_id(mut self, request_id: Option<String>) -> Self {
+        self.request_id = request_id;
+        self
     }
 
     pub fn invalid_json() -> Self {
-        Self::error("invalid json".to_string())
+        Self::error(ErrorCode::InvalidJson, "invalid json")
     }
 
     pub fn internal_server_error() -> Self {
-        Self::error("internal server error".to_string())
+        Self::error(ErrorCode::InternalError, "internal server error")
     }
 
     pub fn missing_action() -> Self {
-        Self::error("missing action".to_string())
+        Self::error(ErrorCode::MissingAction, "missing action")
     }
 
     pub fn unknown_action(action: &str) -> Self {
-        Self::error(format!("unknown action: {}", action))
+        Self::error(ErrorCode::UnknownAction, format!("unknown action: {}", action))
+    }
+
+    /// payload 驗證失敗，`field` 為出錯的欄位路徑
+    pub fn validation_error(code: ErrorCode, field: &str, reason: &str) -> Self {
+        Self::new(
+            "Error",
+            serde_json::json!({ "code": code, "field": field, "error": reason }),
+        )
     }
 
     pub fn binary_not_supported() -> Self {
-        Self::error("binary not supported".to_string())
+        Self::error(ErrorCode::BinaryNotSupported, "binary not supported")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_id_is_optional() {
+        let request: WsRequest = serde_json::from_str(r#"{"type":"ping","payload":{}}"#).unwrap();
+        assert_eq!(request.request_id, None);
+
+        let request: WsRequest =
+            serde_json::from_str(r#"{"type":"ping","payload":{},"requestId":"r1"}"#).unwrap();
+        assert_eq!(request.request_id.as_deref(), Some("r1"));
+    }
+
+    #[test]
+    fn test_error_payload_has_code() {
+        let response = WsResponse::unknown_action("Foo").with_request_id(Some("r1".into()));
+        let value = serde_json::to_value(&response).unwrap();
+        assert_eq!(value["payload"]["code"], "UNKNOWN_ACTION");
+        assert_eq!(value["payload"]["error"], "unknown action: Foo");
+        assert_eq!(value["requestId"], "r1");
+    }
+
+    #[test]
+    fn test_push_omits_request_id() {
+        let value = serde_json::to_value(WsResponse::ok(None)).unwrap();
+        assert!(value.get("requestId").is_none());
+        assert_eq!(ErrorCode::NotEnoughMoney.to_string(), "NOT_ENOUGH_MONEY");
     }
 }
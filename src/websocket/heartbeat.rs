@@ -1,9 +1,119 @@
-use futures_util::SinkExt;
-use tokio_tungstenite::tungstenite::{Error, Message, Result};
-
-pub async fn send_heartbeat(
-    write: &mut (impl SinkExt<Message, Error = Error> + Unpin),
-) -> Result<()> {
-    write.send(Message::Ping(vec![])).await?;
-    Ok(())
+// 心跳：獨立於收訊流程、依固定間隔送出 ping，並追蹤客戶端最後一次有回應的時間
+
+use crate::connection::ConnectionHandle;
+use log::*;
+use std::sync::Mutex;
+use tokio::time::{interval, Duration, Instant, MissedTickBehavior};
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+
+/// 閒置逾時關閉連線時使用的 close code
+pub const IDLE_TIMEOUT_CLOSE_CODE: CloseCode = CloseCode::Library(4000);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HeartbeatConfig {
+    /// 送出 ping 的間隔
+    pub ping_interval: Duration,
+    /// 超過此時間沒有收到任何訊息（包含 pong）即視為斷線
+    pub idle_timeout: Duration,
+}
+
+impl Default for HeartbeatConfig {
+    fn default() -> Self {
+        Self {
+            ping_interval: Duration::from_secs(15),
+            idle_timeout: Duration::from_secs(45),
+        }
+    }
+}
+
+/// 客戶端最後一次有動靜的時間
+pub struct Liveness {
+    last_seen: Mutex<Instant>,
+}
+
+impl Liveness {
+    pub fn new() -> Self {
+        Self {
+            last_seen: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// 收到任何訊息（含 pong）時更新
+    pub fn touch(&self) {
+        *self.last_seen.lock().unwrap() = Instant::now();
+    }
+
+    pub fn idle_for(&self) -> Duration {
+        self.last_seen.lock().unwrap().elapsed()
+    }
+}
+
+/// 心跳任務：定期送出 ping，閒置逾時則送出 Close frame 後結束
+/// 連線已關閉（出站佇列失效）時也會結束
+pub async fn run_heartbeat(handle: ConnectionHandle, liveness: &Liveness, config: HeartbeatConfig) {
+    let mut ticker = interval(config.ping_interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    ticker.tick().await; // 第一次 tick 會立即完成
+
+    loop {
+        ticker.tick().await;
+
+        if liveness.idle_for() >= config.idle_timeout {
+            debug!("Connection {} idle for {:?}", handle.id(), liveness.idle_for());
+            let _ = handle.close(IDLE_TIMEOUT_CLOSE_CODE, "idle timeout").await;
+            return;
+        }
+        if handle.ping().await.is_err() {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connection::{ConnectionRegistry, Outbound};
+
+    #[tokio::test(start_paused = true)]
+    async fn test_pings_until_idle_timeout() {
+        let registry = ConnectionRegistry::new();
+        let (handle, mut receiver) = registry.open();
+        let liveness = Liveness::new();
+        let config = HeartbeatConfig {
+            ping_interval: Duration::from_secs(10),
+            idle_timeout: Duration::from_secs(25),
+        };
+
+        run_heartbeat(handle, &liveness, config).await;
+
+        assert!(matches!(receiver.try_recv(), Ok(Outbound::Ping)));
+        assert!(matches!(receiver.try_recv(), Ok(Outbound::Ping)));
+        match receiver.try_recv() {
+            Ok(Outbound::Close(Some(frame))) => assert_eq!(frame.code, IDLE_TIMEOUT_CLOSE_CODE),
+            other => panic!("expected close frame, got {:?}", other),
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_activity_keeps_connection_alive() {
+        let registry = ConnectionRegistry::new();
+        let (handle, mut receiver) = registry.open();
+        let liveness = Liveness::new();
+        let config = HeartbeatConfig {
+            ping_interval: Duration::from_secs(10),
+            idle_timeout: Duration::from_secs(25),
+        };
+
+        let heartbeat = run_heartbeat(handle, &liveness, config);
+        tokio::pin!(heartbeat);
+        for _ in 0..5 {
+            tokio::select! {
+                _ = &mut heartbeat => panic!("heartbeat should not time out"),
+                _ = tokio::time::sleep(Duration::from_secs(10)) => liveness.touch(),
+            }
+        }
+        while let Ok(item) = receiver.try_recv() {
+            assert!(matches!(item, Outbound::Ping));
+        }
+    }
 }
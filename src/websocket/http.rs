@@ -0,0 +1,166 @@
+use super::stats::ServerStats;
+use crate::connection::ConnectionRegistry;
+use crate::types::protocol::PROTOCOL_VERSION;
+use serde_json::json;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+/// 等待完整請求標頭的時限，避免連上後不送資料的連線（例如 port scanner）佔住任務
+pub const REQUEST_HEAD_TIMEOUT: Duration = Duration::from_secs(10);
+/// 請求標頭的上限，超過時只依已收到的部分判斷
+const MAX_HEAD_SIZE: usize = 8 * 1024;
+
+/// 升級前先看到的 HTTP 請求標頭
+#[derive(Debug, PartialEq)]
+pub struct RequestHead {
+    pub method: String,
+    pub path: String,
+    /// 帶有 `Upgrade: websocket` 時才交給 WebSocket 握手
+    pub websocket: bool,
+    /// 標頭（含結尾空行）的位元組數
+    len: usize,
+}
+
+/// 以 peek 讀取請求標頭但不消耗，WebSocket 握手仍能從頭讀到完整請求。
+/// 失敗時回傳握手失敗的原因。
+pub async fn peek_request(stream: &TcpStream) -> Result<RequestHead, &'static str> {
+    let mut buf = vec![0u8; MAX_HEAD_SIZE];
+    let peeked = tokio::time::timeout(REQUEST_HEAD_TIMEOUT, async {
+        loop {
+            let n = stream.peek(&mut buf).await.map_err(|_| "read error")?;
+            if n == 0 {
+                return Err("closed before request");
+            }
+            if n == buf.len() || find_head_end(&buf[..n]).is_some() {
+                return Ok(n);
+            }
+            // peek 不會消耗資料，有資料時會立即返回，稍等更多資料到達
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .map_err(|_| "request timeout")??;
+    parse_head(&buf[..peeked]).ok_or("not http")
+}
+
+/// 回應非 WebSocket 的 HTTP 請求後關閉連線
+pub async fn respond(
+    mut stream: TcpStream,
+    head: &RequestHead,
+    stats: &ServerStats,
+    registry: &ConnectionRegistry,
+) -> std::io::Result<()> {
+    let (status, body) = match (head.method.as_str(), head.path.as_str()) {
+        ("GET" | "HEAD", "/health") => ("200 OK", json!({ "status": "ok" })),
+        ("GET" | "HEAD", "/" | "/status") => (
+            "200 OK",
+            json!({
+                "status": "ok",
+                "protocolVersion": PROTOCOL_VERSION,
+                "connections": registry.connections().len(),
+                "stats": stats.snapshot(),
+            }),
+        ),
+        ("GET" | "HEAD", _) => ("404 Not Found", json!({ "error": "not found" })),
+        _ => ("405 Method Not Allowed", json!({ "error": "method not allowed" })),
+    };
+    let body = body.to_string();
+    let mut response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        body.len()
+    );
+    if head.method != "HEAD" {
+        response.push_str(&body);
+    }
+
+    // 先讀掉已 peek 的標頭，關閉時接收緩衝區若還有資料會送出 RST，客戶端可能收不到回應
+    let mut consumed = vec![0u8; head.len];
+    stream.read_exact(&mut consumed).await?;
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+fn find_head_end(bytes: &[u8]) -> Option<usize> {
+    bytes.windows(4).position(|w| w == b"\r\n\r\n").map(|i| i + 4)
+}
+
+fn parse_head(bytes: &[u8]) -> Option<RequestHead> {
+    let len = find_head_end(bytes).unwrap_or(bytes.len());
+    let text = String::from_utf8_lossy(&bytes[..len]);
+    let mut lines = text.split("\r\n");
+
+    let mut request_line = lines.next()?.split_whitespace();
+    let method = request_line.next()?;
+    let path = request_line.next()?;
+    if !request_line.next()?.starts_with("HTTP/") {
+        return None;
+    }
+
+    let websocket = lines.any(|line| {
+        line.split_once(':').is_some_and(|(name, value)| {
+            name.trim().eq_ignore_ascii_case("upgrade")
+                && value.split(',').any(|v| v.trim().eq_ignore_ascii_case("websocket"))
+        })
+    });
+    Some(RequestHead {
+        method: method.to_string(),
+        path: path.split('?').next().unwrap_or(path).to_string(),
+        websocket,
+        len,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn test_parse_head_detects_websocket_upgrade() {
+        let upgrade = parse_head(b"GET /ws HTTP/1.1\r\nHost: x\r\nUPGRADE: WebSocket\r\n\r\n").unwrap();
+        assert!(upgrade.websocket);
+        assert_eq!(upgrade.path, "/ws");
+
+        let plain = parse_head(b"GET /health?full=1 HTTP/1.1\r\nHost: x\r\n\r\n").unwrap();
+        assert!(!plain.websocket);
+        assert_eq!(plain.path, "/health");
+        assert_eq!(plain.len, 40);
+
+        assert_eq!(parse_head(b"\x16\x03\x01\x02\x00"), None);
+        assert_eq!(parse_head(b"SSH-2.0-OpenSSH_9.6\r\n"), None);
+    }
+
+    #[tokio::test]
+    async fn test_plain_http_request_gets_health_response() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let client = tokio::spawn(async move {
+            let mut stream = TcpStream::connect(addr).await.unwrap();
+            stream.write_all(b"GET /health HTTP/1.1\r\nHost: localhost\r\n\r\n").await.unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).await.unwrap();
+            response
+        });
+
+        let (stream, _) = listener.accept().await.unwrap();
+        let head = peek_request(&stream).await.unwrap();
+        assert!(!head.websocket);
+        respond(stream, &head, &ServerStats::new(), &ConnectionRegistry::new()).await.unwrap();
+
+        let response = client.await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.ends_with(r#"{"status":"ok"}"#));
+    }
+
+    #[tokio::test]
+    async fn test_closed_connection_is_a_handshake_failure() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(TcpStream::connect(addr).await.unwrap());
+
+        let (stream, _) = listener.accept().await.unwrap();
+        assert_eq!(peek_request(&stream).await, Err("closed before request"));
+    }
+}
@@ -1,41 +1,174 @@
+use crate::connection::{ConnectionContext, PushError};
 use crate::router::Router;
+use crate::types::codec::Encoding;
 use crate::types::response::{WsRequest, WsResponse};
-use futures_util::SinkExt;
-use log::*;
-use tokio_tungstenite::tungstenite::{Error, Message, Result};
+use serde::Deserialize;
+use serde_json::Value;
+use tokio_tungstenite::tungstenite::Message;
 
+/// 文字 frame 一律以 JSON 解析
 pub async fn handle_text_message(
     text: &str,
     router: &Router,
-    write: &mut (impl SinkExt<Message, Error = Error> + Unpin),
-) -> Result<()> {
-    let response = match serde_json::from_str::<WsRequest>(text) {
-        Ok(request) => router.handle(&request.type_, &request).await,
-        Err(_) => WsResponse::invalid_json(),
+    ctx: &ConnectionContext,
+) -> Result<(), PushError> {
+    let value = serde_json::from_str::<Value>(text).ok();
+    handle_value(value, router, ctx).await
+}
+
+/// 二進位 frame 依連線協商的編碼解析；仍使用 JSON 的連線不接受二進位訊息
+pub async fn handle_binary_message(
+    bytes: &[u8],
+    router: &Router,
+    ctx: &ConnectionContext,
+) -> Result<(), PushError> {
+    let encoding = ctx.encoding();
+    if !encoding.is_binary() {
+        return ctx.handle().send(WsResponse::binary_not_supported()).await;
+    }
+    let value = encoding.decode_value(bytes).ok();
+    handle_value(value, router, ctx).await
+}
+
+async fn handle_value(
+    value: Option<Value>,
+    router: &Router,
+    ctx: &ConnectionContext,
+) -> Result<(), PushError> {
+    let response = match value {
+        Some(value) => match WsRequest::deserialize(&value) {
+            Ok(request) => {
+                router
+                    .handle(ctx, &request)
+                    .await
+                    .with_request_id(request.request_id.clone())
+            }
+            // 格式合法但結構不符時，仍盡量帶回 requestId
+            Err(_) => {
+                let request_id = value
+                    .get("requestId")
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string);
+                let response = if value.get("type").is_none() {
+                    WsResponse::missing_action()
+                } else {
+                    WsResponse::invalid_json()
+                };
+                response.with_request_id(request_id)
+            }
+        },
+        None => WsResponse::invalid_json(),
     };
 
-    let response_text = serde_json::to_string(&response)
-        .unwrap_or_else(|_| r#"{"type":"Error","payload":{"error":"internal server error"}}"#.to_string());
-    write.send(Message::Text(response_text)).await?;
-    Ok(())
+    ctx.handle().send(response).await?;
+    ctx.flush_after_reply().await
 }
 
-pub async fn handle_binary_message(
-    write: &mut (impl SinkExt<Message, Error = Error> + Unpin),
-) -> Result<()> {
-    let response = WsResponse::binary_not_supported();
-    let response_text = serde_json::to_string(&response)
-        .unwrap_or_else(|_| r#"{"type":"Error","payload":{"error":"internal server error"}}"#.to_string());
-    write.send(Message::Text(response_text)).await?;
-    Ok(())
+/// 依編碼將回應序列化成 frame：JSON 為文字，其他為二進位
+pub fn encode_response(response: &WsResponse, encoding: Encoding) -> Message {
+    let bytes = encoding
+        .encode(response)
+        .or_else(|_| encoding.encode(&WsResponse::internal_server_error()))
+        .unwrap_or_default();
+    if encoding.is_binary() {
+        Message::Binary(bytes)
+    } else {
+        Message::Text(String::from_utf8(bytes).unwrap_or_default())
+    }
 }
 
-pub async fn send_timeout_message(
-    write: &mut (impl SinkExt<Message, Error = Error> + Unpin),
-) -> Result<()> {
-    let timeout_msg = WsResponse::error("connection timeout".to_string());
-    let timeout_text = serde_json::to_string(&timeout_msg)
-        .unwrap_or_else(|_| r#"{"type":"Error","payload":{"error":"timeout"}}"#.to_string());
-    write.send(Message::Text(timeout_text)).await?;
-    Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connection::{ConnectionHandle, Outbound};
+    use crate::handlers::{HelloHandler, PingHandler, Typed};
+    use std::sync::Arc;
+    use tokio::sync::mpsc;
+
+    fn router() -> Router {
+        let mut router = Router::new();
+        router.add_handler(Arc::new(Typed(HelloHandler))).unwrap();
+        router.add_handler(Arc::new(PingHandler)).unwrap();
+        router
+    }
+
+    fn context() -> (ConnectionContext, mpsc::Receiver<Outbound>) {
+        let (sender, receiver) = mpsc::channel(8);
+        let ctx = ConnectionContext::new(ConnectionHandle::new(1, sender), "127.0.0.1:9000".parse().unwrap());
+        (ctx, receiver)
+    }
+
+    #[tokio::test]
+    async fn test_hello_switches_encoding_after_reply() {
+        let router = router();
+        let (ctx, mut receiver) = context();
+
+        handle_text_message(r#"{"type":"Hello","payload":{"protocolVersion":2,"encoding":"cbor"}}"#, &router, &ctx)
+            .await
+            .unwrap();
+
+        // 先收到以 JSON 送出的確認，才切換編碼
+        match receiver.try_recv() {
+            Ok(Outbound::Response(response)) => assert_eq!(response.type_, "HelloResult"),
+            other => panic!("unexpected outbound: {:?}", other),
+        }
+        assert!(matches!(receiver.try_recv(), Ok(Outbound::SwitchEncoding(Encoding::Cbor))));
+        assert_eq!(ctx.encoding(), Encoding::Cbor);
+
+        // 之後的二進位訊息以 CBOR 解析
+        let request = Encoding::Cbor
+            .encode(&serde_json::json!({ "type": "ping", "payload": {}, "requestId": "7" }))
+            .unwrap();
+        handle_binary_message(&request, &router, &ctx).await.unwrap();
+        match receiver.try_recv() {
+            Ok(Outbound::Response(response)) => {
+                assert_eq!(response.request_id.as_deref(), Some("7"));
+                assert_eq!(response.payload.unwrap()["pong"], true);
+            }
+            other => panic!("unexpected outbound: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hello_with_unsupported_version_closes() {
+        let router = router();
+        let (ctx, mut receiver) = context();
+
+        handle_text_message(r#"{"type":"Hello","payload":{"protocolVersion":999}}"#, &router, &ctx)
+            .await
+            .unwrap();
+
+        match receiver.try_recv() {
+            Ok(Outbound::Response(response)) => {
+                let payload = response.payload.unwrap();
+                assert_eq!(payload["success"], false);
+                assert_eq!(payload["code"], "UPGRADE_REQUIRED");
+            }
+            other => panic!("unexpected outbound: {:?}", other),
+        }
+        match receiver.try_recv() {
+            Ok(Outbound::Close(Some(frame))) => assert_eq!(frame.code, crate::handlers::hello::UPGRADE_REQUIRED_CLOSE_CODE),
+            other => panic!("unexpected outbound: {:?}", other),
+        }
+        assert!(ctx.handshake().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_binary_rejected_on_json_connection() {
+        let router = router();
+        let (ctx, mut receiver) = context();
+
+        handle_binary_message(&[0x80], &router, &ctx).await.unwrap();
+        match receiver.try_recv() {
+            Ok(Outbound::Response(response)) => assert_eq!(response.payload.unwrap()["code"], "BINARY_NOT_SUPPORTED"),
+            other => panic!("unexpected outbound: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_encode_response_frame_kind() {
+        let response = WsResponse::ok(None);
+        assert!(matches!(encode_response(&response, Encoding::Json), Message::Text(_)));
+        assert!(matches!(encode_response(&response, Encoding::MessagePack), Message::Binary(_)));
+    }
 }
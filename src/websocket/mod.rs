@@ -1,57 +1,135 @@
+use crate::connection::{ConnectionContext, ConnectionRegistry, PresenceMonitor};
 use crate::router::Router;
-use futures_util::{SinkExt, StreamExt};
+use crate::types::codec::Encoding;
+use futures_util::StreamExt;
 use log::*;
+use std::sync::Arc;
 use tokio::net::TcpStream;
-use tokio::time::{timeout, Duration};
 use tokio_tungstenite::{
-    accept_async,
-    tungstenite::{Error, Message, Result},
+    accept_hdr_async,
+    tungstenite::{
+        handshake::server::{ErrorResponse, Request, Response},
+        http::{header::SEC_WEBSOCKET_PROTOCOL, HeaderValue},
+        Message, Result,
+    },
 };
 
 mod heartbeat;
+mod http;
 mod message;
+mod stats;
+mod writer;
 
-use heartbeat::send_heartbeat;
-use message::{handle_binary_message, handle_text_message, send_timeout_message};
+pub use heartbeat::HeartbeatConfig;
+pub use stats::ServerStats;
+use heartbeat::{run_heartbeat, Liveness};
+use message::{handle_binary_message, handle_text_message};
+use writer::write_outbound;
 
-pub async fn handle_client(stream: TcpStream, router: Router) -> Result<()> {
-    let addr = stream
-        .peer_addr()
-        .expect("connected streams should have a peer address");
+pub async fn handle_client(
+    stream: TcpStream,
+    router: Arc<Router>,
+    registry: Arc<ConnectionRegistry>,
+    presence: Arc<PresenceMonitor>,
+    heartbeat: HeartbeatConfig,
+    stats: Arc<ServerStats>,
+) -> Result<()> {
+    // 握手前的任何失敗都只記錄原因並結束這條連線，不讓客戶端的輸入造成 panic
+    let addr = match stream.peer_addr() {
+        Ok(addr) => addr,
+        Err(e) => {
+            stats.handshake_failed("peer address unavailable");
+            warn!("Dropping connection without peer address: {}", e);
+            return Ok(());
+        }
+    };
     info!("Client connected: {}", addr);
 
-    let ws_stream = accept_async(stream)
-        .await
-        .expect("Error during the websocket handshake occurred");
-    info!("WebSocket connection established: {}", addr);
-
-    let (mut write, mut read) = ws_stream.split();
-    let timeout_duration = Duration::from_secs(30);
+    match http::peek_request(&stream).await {
+        Ok(head) if head.websocket => {}
+        // 一般 HTTP 請求（健康檢查、瀏覽器）回覆狀態頁而不是升級失敗
+        Ok(head) => {
+            stats.http_request();
+            debug!("HTTP {} {} from {}", head.method, head.path, addr);
+            if let Err(e) = http::respond(stream, &head, &stats, &registry).await {
+                debug!("Failed to answer HTTP request from {}: {}", addr, e);
+            }
+            return Ok(());
+        }
+        Err(reason) => {
+            stats.handshake_failed(reason);
+            warn!("Handshake with {} failed: {}", addr, reason);
+            return Ok(());
+        }
+    }
 
-    loop {
-        // 發送心跳
-        if let Err(e) = send_heartbeat(&mut write).await {
-            error!("Failed to send ping to {}: {}", addr, e);
-            break;
+    // 握手時依 Sec-WebSocket-Protocol 協商編碼，未指定則使用 JSON
+    let mut encoding = Encoding::default();
+    // 回傳型別由 tungstenite 的 Callback 決定
+    #[allow(clippy::result_large_err)]
+    let negotiate = |request: &Request, mut response: Response| -> std::result::Result<Response, ErrorResponse> {
+        let offered = request
+            .headers()
+            .get(SEC_WEBSOCKET_PROTOCOL)
+            .and_then(|value| value.to_str().ok());
+        if let Some(chosen) = offered.and_then(Encoding::negotiate) {
+            encoding = chosen;
+            response.headers_mut().insert(
+                SEC_WEBSOCKET_PROTOCOL,
+                HeaderValue::from_static(chosen.subprotocol()),
+            );
+        }
+        Ok(response)
+    };
+    let ws_stream = match tokio::time::timeout(http::REQUEST_HEAD_TIMEOUT, accept_hdr_async(stream, negotiate)).await {
+        Ok(Ok(ws_stream)) => ws_stream,
+        Ok(Err(e)) => {
+            stats.handshake_failed("websocket upgrade rejected");
+            warn!("WebSocket handshake with {} failed: {}", addr, e);
+            return Ok(());
         }
+        Err(_) => {
+            stats.handshake_failed("request timeout");
+            warn!("WebSocket handshake with {} timed out", addr);
+            return Ok(());
+        }
+    };
+    stats.websocket_connected();
+    info!("WebSocket connection established: {} ({:?})", addr, encoding);
+
+    let (write, mut read) = ws_stream.split();
+    let (handle, receiver) = registry.open();
+    let writer = tokio::spawn(write_outbound(write, receiver, encoding));
+    let ctx = ConnectionContext::new(handle.clone(), addr).with_encoding(encoding);
 
-        // 等待消息
-        let read_result = timeout(timeout_duration, read.next()).await;
+    // 心跳與收訊各自獨立：心跳任務閒置逾時送出 Close frame 後結束，收訊迴圈隨之停止
+    let liveness = Liveness::new();
+    let mut idle = Box::pin(run_heartbeat(handle.clone(), &liveness, heartbeat));
 
-        match read_result {
-            Ok(Some(Ok(msg))) => match msg {
+    loop {
+        let next = tokio::select! {
+            next = read.next() => next,
+            _ = &mut idle => {
+                warn!("Connection idle timeout from {}", addr);
+                break;
+            }
+        };
+        liveness.touch();
+
+        match next {
+            Some(Ok(msg)) => match msg {
                 Message::Text(text) => {
-                    println!("收到前端文字訊息: {}", text);
+                    trace!("Received text message from {}: {}", addr, text);
 
-                    if let Err(e) = handle_text_message(&text, &router, &mut write).await {
+                    if let Err(e) = handle_text_message(&text, &router, &ctx).await {
                         error!("Failed to handle text message from {}: {}", addr, e);
                         break;
                     }
                 }
-                Message::Binary(_) => {
-                    println!("收到前端二進位訊息");
+                Message::Binary(bytes) => {
+                    debug!("Received binary message from {} ({} bytes)", addr, bytes.len());
 
-                    if let Err(e) = handle_binary_message(&mut write).await {
+                    if let Err(e) = handle_binary_message(&bytes, &router, &ctx).await {
                         error!("Failed to handle binary message from {}: {}", addr, e);
                         break;
                     }
@@ -65,23 +143,28 @@ pub async fn handle_client(stream: TcpStream, router: Router) -> Result<()> {
                 }
                 _ => {}
             },
-            Ok(Some(Err(e))) => {
+            Some(Err(e)) => {
                 error!("WebSocket read error: {} from {}", e, addr);
                 break;
             }
-            Ok(None) => {
+            None => {
                 info!("Client {} disconnected", addr);
                 break;
             }
-            Err(_) => {
-                warn!("Connection timeout from {}", addr);
-                if let Err(e) = send_timeout_message(&mut write).await {
-                    error!("Failed to send timeout message to {}: {}", addr, e);
-                }
-                break;
-            }
         }
     }
 
-    Ok(())
+    // 解除玩家綁定（保留座位）、清掉中介層的連線狀態並釋放出站端，寫入任務送完剩餘訊息後自行結束
+    presence.on_disconnect(handle.id());
+    router.disconnected(handle.id());
+    drop(idle); // 心跳任務也持有出站端
+    drop(ctx);
+    drop(handle);
+    match writer.await {
+        Ok(result) => result,
+        Err(e) => {
+            error!("Writer task for {} failed: {}", addr, e);
+            Ok(())
+        }
+    }
 }
@@ -0,0 +1,61 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// 連線層級的統計：握手失敗依原因分類計數，供狀態頁與日誌使用
+pub struct ServerStats {
+    started: Instant,
+    websocket_connections: AtomicU64,
+    http_requests: AtomicU64,
+    handshake_failures: Mutex<BTreeMap<&'static str, u64>>,
+}
+
+/// 狀態頁輸出的統計快照
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatsSnapshot {
+    pub uptime_secs: u64,
+    pub websocket_connections: u64,
+    pub http_requests: u64,
+    pub handshake_failures: BTreeMap<&'static str, u64>,
+}
+
+impl ServerStats {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            websocket_connections: AtomicU64::new(0),
+            http_requests: AtomicU64::new(0),
+            handshake_failures: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn websocket_connected(&self) {
+        self.websocket_connections.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn http_request(&self) {
+        self.http_requests.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn handshake_failed(&self, reason: &'static str) {
+        *self.handshake_failures.lock().unwrap().entry(reason).or_default() += 1;
+    }
+
+    pub fn snapshot(&self) -> StatsSnapshot {
+        StatsSnapshot {
+            uptime_secs: self.started.elapsed().as_secs(),
+            websocket_connections: self.websocket_connections.load(Ordering::Relaxed),
+            http_requests: self.http_requests.load(Ordering::Relaxed),
+            handshake_failures: self.handshake_failures.lock().unwrap().clone(),
+        }
+    }
+}
+
+impl Default for ServerStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,31 @@
+use crate::connection::Outbound;
+use crate::types::codec::Encoding;
+use super::message::encode_response;
+use futures_util::{Sink, SinkExt};
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::{Error, Message, Result};
+
+/// 寫入任務：依序消化出站佇列並寫入 socket
+/// 所有 sender 都被釋放（連線已從註冊表移除）或送出 Close frame 後結束
+pub async fn write_outbound(
+    mut write: impl Sink<Message, Error = Error> + Unpin,
+    mut receiver: mpsc::Receiver<Outbound>,
+    mut encoding: Encoding,
+) -> Result<()> {
+    while let Some(item) = receiver.recv().await {
+        let message = match item {
+            Outbound::Response(response) => encode_response(&response, encoding),
+            Outbound::Ping => Message::Ping(vec![]),
+            Outbound::SwitchEncoding(next) => {
+                encoding = next;
+                continue;
+            }
+            Outbound::Close(frame) => {
+                write.send(Message::Close(frame)).await?;
+                break;
+            }
+        };
+        write.send(message).await?;
+    }
+    Ok(())
+}